@@ -0,0 +1,5 @@
+-- Per-store MAX(price_update_date) lookups for data-freshness reporting
+-- (/metrics, readiness checks). Lets a LATERAL max-per-store read the index
+-- tail instead of scanning every item row.
+CREATE INDEX IF NOT EXISTS idx_items_store_price_date
+    ON items(store_pk, price_update_date DESC);
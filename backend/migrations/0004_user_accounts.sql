@@ -0,0 +1,39 @@
+-- User accounts with server-side grocery lists (ROADMAP Phase 6 — Authentication).
+-- Sessions are opaque bearer tokens; only their SHA-256 is stored.
+
+CREATE TABLE IF NOT EXISTS users (
+    id BIGSERIAL PRIMARY KEY,
+    email VARCHAR(254) NOT NULL UNIQUE,       -- stored trimmed + lowercased
+    password_hash TEXT NOT NULL,              -- argon2id PHC string
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS sessions (
+    token_hash BYTEA PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id);
+
+CREATE TABLE IF NOT EXISTS grocery_lists (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_grocery_lists_user ON grocery_lists(user_id, updated_at DESC);
+
+-- One row per entry, in list order. An entry is either a catalog product
+-- (barcode set) or free text matched by name.
+CREATE TABLE IF NOT EXISTS grocery_list_items (
+    list_id UUID NOT NULL REFERENCES grocery_lists(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    barcode VARCHAR(13) REFERENCES products(barcode),
+    name TEXT NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (list_id, position)
+);
@@ -0,0 +1,16 @@
+-- Short-link shares of a grocery list, replacing base64 lists in the URL.
+-- The list is a JSONB snapshot of v2 entries ({barcode, name, quantity});
+-- editable shares can be overwritten by anyone holding the link.
+
+CREATE TABLE IF NOT EXISTS shared_lists (
+    id VARCHAR(16) PRIMARY KEY,
+    name VARCHAR(100),
+    grocery_list JSONB NOT NULL,
+    editable BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_shared_lists_expires
+    ON shared_lists(expires_at) WHERE expires_at IS NOT NULL;
@@ -0,0 +1,35 @@
+-- Hebrew-normalized product names for search. Spelling variants of the same
+-- word ("קוטג'" / "קוטג׳" / "קוטג", "שוקולד" / "שקולד") normalize alike, so a
+-- substring match on the normalized column finds them all.
+--
+-- normalize_hebrew: lowercase; drop niqqud, cantillation and quote marks; map
+-- final letters to regular forms and maqaf to a space; drop ו/י between two
+-- letters (full vs defective spelling); collapse whitespace. Search terms are
+-- normalized the same way in src/hebrew.rs — keep the two in sync.
+
+CREATE OR REPLACE FUNCTION normalize_hebrew(input TEXT) RETURNS TEXT
+LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE AS $$
+    SELECT btrim(regexp_replace(
+        regexp_replace(
+            translate(
+                regexp_replace(lower(input), '[\u0591-\u05BD\u05BF\u05C1\u05C2\u05C4\u05C5\u05C7''"`׳״‘’“”]', '', 'g'),
+                'ךםןףץ־', 'כמנפצ '),
+            '(?<=[א-ת])[וי](?=[א-ת])', '', 'g'),
+        '\s+', ' ', 'g'))
+$$;
+
+-- Generated at write time, so ingest needs no changes and existing rows are filled here.
+ALTER TABLE items
+    ADD COLUMN IF NOT EXISTS item_name_normalized TEXT
+    GENERATED ALWAYS AS (normalize_hebrew(item_name)) STORED;
+ALTER TABLE products
+    ADD COLUMN IF NOT EXISTS canonical_name_normalized TEXT
+    GENERATED ALWAYS AS (normalize_hebrew(canonical_name)) STORED;
+
+-- Every name search now goes through the normalized columns.
+DROP INDEX IF EXISTS idx_items_lower_name_trgm;
+DROP INDEX IF EXISTS idx_products_lower_name_trgm;
+CREATE INDEX IF NOT EXISTS idx_items_normalized_name_trgm
+    ON items USING gin (item_name_normalized gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_products_normalized_name_trgm
+    ON products USING gin (canonical_name_normalized gin_trgm_ops);
@@ -0,0 +1,21 @@
+-- Operator-managed search synonyms. A rule makes searches for `term` also
+-- match `alias`; bidirectional rules work both ways ("שמנת מתוקה" ↔
+-- "שמנת להקצפה"). One-way rules let "קולה" find "קוקה קולה" without a
+-- "קוקה קולה" search matching every cola. Rules apply to whole words of the
+-- normalized search term (src/synonyms.rs).
+
+CREATE TABLE IF NOT EXISTS search_synonyms (
+    id SERIAL PRIMARY KEY,
+    term VARCHAR(100) NOT NULL,
+    alias VARCHAR(100) NOT NULL,
+    bidirectional BOOLEAN NOT NULL DEFAULT TRUE,
+    term_normalized TEXT GENERATED ALWAYS AS (normalize_hebrew(term)) STORED,
+    alias_normalized TEXT GENERATED ALWAYS AS (normalize_hebrew(alias)) STORED,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (term_normalized, alias_normalized)
+);
+
+INSERT INTO search_synonyms (term, alias, bidirectional) VALUES
+    ('שמנת מתוקה', 'שמנת להקצפה', TRUE),
+    ('קולה', 'קוקה קולה', FALSE)
+ON CONFLICT DO NOTHING;
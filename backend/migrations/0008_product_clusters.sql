@@ -0,0 +1,38 @@
+-- Cross-chain identity for items without a catalog barcode (store brands,
+-- produce, weighed goods). A rebuild (src/clustering.rs) groups each chain's
+-- internal codes on normalized name, pack size and manufacturer; compare can
+-- then match a cluster like a barcode ("cluster:<id>" terms).
+--
+-- Members are keyed by (chain_id, item_code): internal codes are per chain.
+-- status 'auto' rows are recomputed on every rebuild; 'confirmed' and
+-- 'rejected' are operator overrides that rebuilds leave alone.
+
+CREATE TABLE IF NOT EXISTS product_clusters (
+    id SERIAL PRIMARY KEY,
+    cluster_key TEXT NOT NULL UNIQUE,        -- manufacturer|name words|size, normalized
+    name TEXT NOT NULL,
+    manufacturer TEXT,
+    size_amount DOUBLE PRECISION,
+    size_unit VARCHAR(16),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS product_cluster_members (
+    cluster_id INTEGER NOT NULL REFERENCES product_clusters(id) ON DELETE CASCADE,
+    chain_id VARCHAR NOT NULL,
+    item_code VARCHAR NOT NULL,
+    item_name TEXT NOT NULL,
+    confidence DOUBLE PRECISION NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'auto' CHECK (status IN ('auto', 'confirmed', 'rejected')),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (cluster_id, chain_id, item_code)
+);
+
+-- An item belongs to at most one cluster; rejections are per cluster.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_cluster_members_item
+    ON product_cluster_members(chain_id, item_code) WHERE status <> 'rejected';
+
+-- Saved list entries can name a cluster instead of a barcode.
+ALTER TABLE grocery_list_items
+    ADD COLUMN IF NOT EXISTS cluster_id INTEGER REFERENCES product_clusters(id) ON DELETE SET NULL;
@@ -0,0 +1,22 @@
+-- Every chain's name for a catalog barcode, with how often it arrived.
+-- products.canonical_name is elected from these (src/naming.rs) after each
+-- ingest scan instead of sticking with whichever chain's name came first.
+
+CREATE TABLE IF NOT EXISTS product_name_variants (
+    barcode VARCHAR(13) NOT NULL REFERENCES products(barcode) ON DELETE CASCADE,
+    chain_id VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    seen_count INTEGER NOT NULL DEFAULT 1,
+    first_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (barcode, chain_id, name)
+);
+
+-- Backfill from the price rows already ingested.
+INSERT INTO product_name_variants (barcode, chain_id, name, seen_count)
+SELECT i.item_code, s.chain_id, i.item_name, COUNT(*)
+FROM items i
+JOIN stores s ON s.id = i.store_pk
+JOIN products p ON p.barcode = i.item_code
+GROUP BY i.item_code, s.chain_id, i.item_name
+ON CONFLICT DO NOTHING;
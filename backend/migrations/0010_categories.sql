@@ -0,0 +1,157 @@
+-- Product category taxonomy. Items are classified per (chain_id, item_code)
+-- — internal codes are per chain — by rules at ingest (src/categories.rs):
+-- keywords matched as whole words of the normalized item name, manufacturer
+-- hints, and barcode prefix ranges. Operators can pin an item to a category
+-- ('manual'); reclassification only rewrites 'rule' rows.
+
+CREATE TABLE IF NOT EXISTS categories (
+    id SERIAL PRIMARY KEY,
+    slug VARCHAR(50) NOT NULL UNIQUE,
+    name VARCHAR(100) NOT NULL,
+    parent_id INTEGER REFERENCES categories(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS category_rules (
+    id SERIAL PRIMARY KEY,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    kind VARCHAR(16) NOT NULL CHECK (kind IN ('keyword', 'manufacturer', 'barcode_prefix')),
+    pattern VARCHAR(100) NOT NULL,
+    -- Higher wins; among equals keywords beat barcode prefixes beat manufacturers,
+    -- then the longer pattern wins ("משקה חלב" over "משקה").
+    priority INTEGER NOT NULL DEFAULT 0,
+    pattern_normalized TEXT GENERATED ALWAYS AS (normalize_hebrew(pattern)) STORED,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (kind, pattern_normalized)
+);
+
+CREATE TABLE IF NOT EXISTS item_categories (
+    chain_id VARCHAR NOT NULL,
+    item_code VARCHAR NOT NULL,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    source VARCHAR(16) NOT NULL DEFAULT 'rule' CHECK (source IN ('rule', 'manual')),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (chain_id, item_code)
+);
+CREATE INDEX IF NOT EXISTS idx_item_categories_category ON item_categories(category_id);
+
+INSERT INTO categories (slug, name) VALUES
+    ('dairy', 'מוצרי חלב וביצים'),
+    ('bakery', 'לחם ומאפים'),
+    ('meat-fish', 'בשר, עוף ודגים'),
+    ('produce', 'פירות וירקות'),
+    ('beverages', 'משקאות'),
+    ('pantry', 'מזווה'),
+    ('snacks', 'חטיפים ומתוקים'),
+    ('frozen', 'קפואים'),
+    ('cleaning', 'ניקיון וכביסה'),
+    ('personal-care', 'טיפוח והיגיינה'),
+    ('baby', 'תינוקות')
+ON CONFLICT (slug) DO NOTHING;
+
+INSERT INTO categories (slug, name, parent_id)
+SELECT c.slug, c.name, p.id
+FROM (VALUES
+    ('milk', 'חלב ומשקאות חלב', 'dairy'),
+    ('cheese', 'גבינות', 'dairy'),
+    ('yogurt', 'יוגורטים ומעדנים', 'dairy'),
+    ('eggs', 'ביצים', 'dairy'),
+    ('soft-drinks', 'משקאות קלים ומים', 'beverages'),
+    ('alcohol', 'יין ואלכוהול', 'beverages'),
+    ('coffee-tea', 'קפה ותה', 'beverages'),
+    ('pasta-rice', 'פסטה, אורז ודגנים', 'pantry'),
+    ('canned', 'שימורים', 'pantry'),
+    ('oils-spices', 'שמנים, רטבים ותבלינים', 'pantry')
+) AS c(slug, name, parent)
+JOIN categories p ON p.slug = c.parent
+ON CONFLICT (slug) DO NOTHING;
+
+INSERT INTO category_rules (category_id, kind, pattern, priority)
+SELECT c.id, r.kind, r.pattern, r.priority
+FROM (VALUES
+    ('milk', 'keyword', 'חלב', 0),
+    ('milk', 'keyword', 'משקה חלב', 0),
+    ('milk', 'keyword', 'שוקו', 0),
+    ('milk', 'keyword', 'שמנת', 0),
+    ('cheese', 'keyword', 'גבינה', 0),
+    ('cheese', 'keyword', 'גבינת', 0),
+    ('cheese', 'keyword', 'קוטג', 0),
+    ('cheese', 'keyword', 'מוצרלה', 0),
+    ('cheese', 'keyword', 'צפתית', 0),
+    ('yogurt', 'keyword', 'יוגורט', 0),
+    ('yogurt', 'keyword', 'מעדן', 0),
+    ('eggs', 'keyword', 'ביצים', 0),
+    ('bakery', 'keyword', 'לחם', 0),
+    ('bakery', 'keyword', 'לחמניות', 0),
+    ('bakery', 'keyword', 'פיתות', 0),
+    ('bakery', 'keyword', 'חלה', 0),
+    ('bakery', 'keyword', 'עוגה', 0),
+    ('meat-fish', 'keyword', 'עוף', 0),
+    ('meat-fish', 'keyword', 'בקר', 0),
+    ('meat-fish', 'keyword', 'טחון', 0),
+    ('meat-fish', 'keyword', 'שניצל', 0),
+    ('meat-fish', 'keyword', 'סלמון', 0),
+    ('meat-fish', 'keyword', 'פילה', 0),
+    ('produce', 'keyword', 'עגבניה', 0),
+    ('produce', 'keyword', 'עגבניות', 0),
+    ('produce', 'keyword', 'מלפפון', 0),
+    ('produce', 'keyword', 'מלפפונים', 0),
+    ('produce', 'keyword', 'בצל', 0),
+    ('produce', 'keyword', 'גזר', 0),
+    ('produce', 'keyword', 'תפוח', 0),
+    ('produce', 'keyword', 'תפוחים', 0),
+    ('produce', 'keyword', 'תפוחי אדמה', 0),
+    ('produce', 'keyword', 'בננה', 0),
+    ('produce', 'keyword', 'אבוקדו', 0),
+    ('produce', 'keyword', 'לימון', 0),
+    ('soft-drinks', 'keyword', 'קולה', 0),
+    ('soft-drinks', 'keyword', 'מיץ', 1),
+    ('soft-drinks', 'keyword', 'סודה', 0),
+    ('soft-drinks', 'keyword', 'מים מינרלים', 0),
+    ('alcohol', 'keyword', 'יין', 0),
+    ('alcohol', 'keyword', 'בירה', 0),
+    ('alcohol', 'keyword', 'וודקה', 0),
+    ('alcohol', 'keyword', 'ויסקי', 0),
+    ('coffee-tea', 'keyword', 'קפה', 0),
+    ('coffee-tea', 'keyword', 'תה', 0),
+    ('pasta-rice', 'keyword', 'פסטה', 0),
+    ('pasta-rice', 'keyword', 'ספגטי', 0),
+    ('pasta-rice', 'keyword', 'אורז', 0),
+    ('pasta-rice', 'keyword', 'קוסקוס', 0),
+    ('canned', 'keyword', 'טונה', 0),
+    ('canned', 'keyword', 'תירס', 0),
+    ('canned', 'keyword', 'זיתים', 0),
+    ('canned', 'keyword', 'שימורי', 0),
+    ('oils-spices', 'keyword', 'שמן', 0),
+    ('oils-spices', 'keyword', 'שמן זית', 0),
+    ('oils-spices', 'keyword', 'מלח', 0),
+    ('oils-spices', 'keyword', 'פלפל שחור', 0),
+    ('oils-spices', 'keyword', 'קטשופ', 0),
+    ('oils-spices', 'keyword', 'מיונז', 0),
+    ('snacks', 'keyword', 'במבה', 0),
+    ('snacks', 'keyword', 'ביסלי', 0),
+    ('snacks', 'keyword', 'חטיף', 0),
+    ('snacks', 'keyword', 'עוגיות', 0),
+    ('snacks', 'keyword', 'וופל', 0),
+    ('snacks', 'keyword', 'שוקולד', 1),
+    ('frozen', 'keyword', 'קפוא', 1),
+    ('frozen', 'keyword', 'קפואה', 1),
+    ('frozen', 'keyword', 'גלידה', 0),
+    ('cleaning', 'keyword', 'אקונומיקה', 0),
+    ('cleaning', 'keyword', 'נוזל כביסה', 0),
+    ('cleaning', 'keyword', 'אבקת כביסה', 0),
+    ('cleaning', 'keyword', 'מרכך כביסה', 0),
+    ('cleaning', 'keyword', 'נוזל כלים', 0),
+    ('personal-care', 'keyword', 'שמפו', 0),
+    ('personal-care', 'keyword', 'סבון', 0),
+    ('personal-care', 'keyword', 'משחת שיניים', 0),
+    ('personal-care', 'keyword', 'דאודורנט', 0),
+    ('baby', 'keyword', 'חיתולים', 0),
+    ('baby', 'keyword', 'מגבונים', 0),
+    ('baby', 'keyword', 'מטרנה', 0),
+    ('dairy', 'manufacturer', 'תנובה', 0),
+    ('dairy', 'manufacturer', 'יטבתה', 0),
+    ('dairy', 'manufacturer', 'טרה', 0),
+    ('cleaning', 'manufacturer', 'סנו', 0)
+) AS r(category, kind, pattern, priority)
+JOIN categories c ON c.slug = r.category
+ON CONFLICT (kind, pattern_normalized) DO NOTHING;
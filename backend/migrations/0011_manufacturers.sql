@@ -0,0 +1,38 @@
+-- Manufacturer identity behind the free-text items.manufacturer_name. Each raw
+-- spelling seen at ingest becomes an alias resolved to one manufacturer by key
+-- (src/brands.rs); a NULL manufacturer marks placeholders like "לא ידוע".
+-- 'manual' aliases are operator mappings, e.g. Latin spellings of Hebrew names.
+
+CREATE TABLE IF NOT EXISTS manufacturers (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    name_key TEXT NOT NULL UNIQUE,           -- brands::manufacturer_key(name)
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS manufacturer_aliases (
+    alias VARCHAR PRIMARY KEY,               -- raw manufacturer_name, as sent
+    manufacturer_id INTEGER REFERENCES manufacturers(id) ON DELETE CASCADE,
+    source VARCHAR(16) NOT NULL DEFAULT 'auto' CHECK (source IN ('auto', 'manual')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_manufacturer_aliases_manufacturer ON manufacturer_aliases(manufacturer_id);
+CREATE INDEX IF NOT EXISTS idx_items_manufacturer_name ON items(manufacturer_name);
+
+INSERT INTO manufacturers (name, name_key)
+SELECT name, normalize_hebrew(name)
+FROM (VALUES ('תנובה'), ('שטראוס'), ('אסם'), ('עלית'), ('טרה'), ('יטבתה')) AS m(name)
+ON CONFLICT (name_key) DO NOTHING;
+
+INSERT INTO manufacturer_aliases (alias, manufacturer_id, source)
+SELECT a.alias, m.id, 'manual'
+FROM (VALUES
+    ('Tnuva', 'תנובה'),
+    ('Strauss', 'שטראוס'),
+    ('Osem', 'אסם'),
+    ('Elite', 'עלית'),
+    ('Tara', 'טרה'),
+    ('Yotvata', 'יטבתה')
+) AS a(alias, manufacturer)
+JOIN manufacturers m ON m.name = a.manufacturer
+ON CONFLICT (alias) DO NOTHING;
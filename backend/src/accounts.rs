@@ -0,0 +1,539 @@
+use anyhow::Context;
+use argon2::{
+    password_hash::{rand_core::{OsRng, RngCore}, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{rejection::{JsonRejection, PathRejection}, FromRequestParts, Path, State},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+use crate::api::{compare_entries, compare_options_errors, entry_errors, AppState};
+use crate::database::{is_ean13, DatabaseManager};
+use crate::error::{validate, ApiError, FieldError, Problem};
+use crate::models::{
+    CompareOptions, Credentials, PriceComparisonResponseV2, SavedList, SavedListInput, SavedListSummary,
+    SessionResponse, UserInfo,
+};
+
+const SESSION_TTL_DAYS: i64 = 30;
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_PASSWORD_LEN: usize = 128;
+const MAX_LIST_NAME_LEN: usize = 100;
+const MAX_LISTS_PER_USER: i64 = 100;
+
+/// Trimmed, lowercased email if it looks deliverable (`local@domain.tld`), else None.
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.split_once('@')?;
+    let plausible = email.len() <= 254
+        && !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace);
+    plausible.then_some(email)
+}
+
+fn credential_errors(credentials: &Credentials) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if normalize_email(&credentials.email).is_none() {
+        errors.push(FieldError::new("email", "invalid_email", "email must be a valid address"));
+    }
+    let len = credentials.password.chars().count();
+    if !(MIN_PASSWORD_LEN..=MAX_PASSWORD_LEN).contains(&len) {
+        errors.push(FieldError::new(
+            "password",
+            "password_length",
+            format!("password must be {} to {} characters", MIN_PASSWORD_LEN, MAX_PASSWORD_LEN),
+        ));
+    }
+    errors
+}
+
+/// Argon2id with the crate's default (OWASP-recommended) parameters. CPU-bound,
+/// so it runs off the async workers.
+async fn hash_password(password: String) -> anyhow::Result<String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|h| h.to_string())
+            .map_err(|e| anyhow::anyhow!("hashing password: {}", e))
+    })
+    .await?
+}
+
+async fn verify_password(password: String, hash: String) -> anyhow::Result<bool> {
+    tokio::task::spawn_blocking(move || {
+        let parsed = PasswordHash::new(&hash).map_err(|e| anyhow::anyhow!("stored hash unreadable: {}", e))?;
+        Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+    })
+    .await?
+}
+
+/// Verified against when the email is unknown, so a miss costs as much as a wrong password.
+async fn dummy_hash() -> anyhow::Result<String> {
+    static DUMMY: OnceLock<String> = OnceLock::new();
+    if let Some(hash) = DUMMY.get() {
+        return Ok(hash.clone());
+    }
+    let hash = hash_password("not-a-real-password".to_string()).await?;
+    Ok(DUMMY.get_or_init(|| hash).clone())
+}
+
+/// A new random session token (256 bits, hex) — handed to the client once, never stored.
+fn new_session_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn token_hash(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Extractor for routes that need a signed-in user: resolves the bearer session
+/// token or rejects with 401.
+pub struct AuthUser {
+    pub user: UserInfo,
+    token_hash: Vec<u8>,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, ApiError> {
+        let token = bearer_token(&parts.headers)
+            .ok_or_else(|| ApiError::unauthorized("missing_credentials", "sign in required"))?;
+        let token_hash = token_hash(token);
+        let user = state
+            .db
+            .session_user(&token_hash)
+            .await
+            .context("looking up session")?
+            .ok_or_else(|| ApiError::unauthorized("invalid_session", "session expired or unknown"))?;
+        Ok(Self { user, token_hash })
+    }
+}
+
+async fn start_session(db: &DatabaseManager, user: UserInfo) -> Result<SessionResponse, ApiError> {
+    let token = new_session_token();
+    let expires_at = Utc::now() + Duration::days(SESSION_TTL_DAYS);
+    db.create_session(user.id, &token_hash(&token), expires_at)
+        .await
+        .context("creating session")?;
+    Ok(SessionResponse { token, expires_at, user })
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "accounts",
+    request_body = Credentials,
+    responses(
+        (status = 201, description = "Account created and signed in", body = SessionResponse),
+        (status = 400, description = "Invalid email or password", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Email already registered", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn register(
+    State(db): State<Arc<DatabaseManager>>,
+    credentials: Result<Json<Credentials>, JsonRejection>,
+) -> Result<(StatusCode, Json<SessionResponse>), ApiError> {
+    let Json(credentials) = credentials?;
+    validate(credential_errors(&credentials))?;
+    let email = normalize_email(&credentials.email).unwrap_or_default();
+
+    let hash = hash_password(credentials.password).await?;
+    let user = db
+        .create_user(&email, &hash)
+        .await
+        .context("creating user")?
+        .ok_or_else(|| ApiError::conflict("email_taken", "an account with this email already exists"))?;
+    Ok((StatusCode::CREATED, Json(start_session(&db, user).await?)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "accounts",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "Signed in", body = SessionResponse),
+        (status = 401, description = "Wrong email or password", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn login(
+    State(db): State<Arc<DatabaseManager>>,
+    credentials: Result<Json<Credentials>, JsonRejection>,
+) -> Result<Json<SessionResponse>, ApiError> {
+    let Json(credentials) = credentials?;
+    let found = match normalize_email(&credentials.email) {
+        Some(email) => db.user_credentials(&email).await.context("looking up user")?,
+        None => None,
+    };
+    let (user, hash) = match found {
+        Some((user, hash)) => (Some(user), hash),
+        None => (None, dummy_hash().await?),
+    };
+    let verified = verify_password(credentials.password, hash).await?;
+    match user {
+        Some(user) if verified => Ok(Json(start_session(&db, user).await?)),
+        _ => Err(ApiError::unauthorized("invalid_credentials", "wrong email or password")),
+    }
+}
+
+/// Ends the session whose token authorized this request.
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "accounts",
+    security(("session_token" = [])),
+    responses(
+        (status = 204, description = "Signed out"),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn logout(State(db): State<Arc<DatabaseManager>>, auth: AuthUser) -> Result<StatusCode, ApiError> {
+    db.delete_session(&auth.token_hash).await.context("deleting session")?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "accounts",
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "The signed-in user", body = UserInfo),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn me(auth: AuthUser) -> Json<UserInfo> {
+    Json(auth.user)
+}
+
+/// Name, entries, and barcodes that aren't in the product catalog.
+async fn saved_list_errors(db: &DatabaseManager, list: &SavedListInput) -> Result<Vec<FieldError>, ApiError> {
+    let mut errors = Vec::new();
+    let name_len = list.name.trim().chars().count();
+    if name_len == 0 || name_len > MAX_LIST_NAME_LEN {
+        errors.push(FieldError::new(
+            "name",
+            "name_length",
+            format!("name must be 1 to {} characters", MAX_LIST_NAME_LEN),
+        ));
+    }
+    errors.extend(entry_errors(&list.grocery_list));
+
+    let barcodes: Vec<String> = list.grocery_list.iter().filter_map(|e| e.barcode.clone()).collect();
+    let known = db.known_barcodes(&barcodes).await.context("checking barcodes")?;
+    for (i, entry) in list.grocery_list.iter().enumerate() {
+        if let Some(barcode) = &entry.barcode
+            && !(is_ean13(barcode) && known.contains(barcode))
+        {
+            errors.push(FieldError::new(
+                format!("grocery_list[{}].barcode", i),
+                "unknown_barcode",
+                "barcode must be a product in the catalog",
+            ));
+        }
+    }
+
+    let cluster_ids: Vec<i32> = list.grocery_list.iter().filter_map(|e| e.cluster_id).collect();
+    let known = db.known_clusters(&cluster_ids).await.context("checking clusters")?;
+    for (i, entry) in list.grocery_list.iter().enumerate() {
+        if let Some(cluster_id) = entry.cluster_id
+            && !known.contains(&cluster_id)
+        {
+            errors.push(FieldError::new(
+                format!("grocery_list[{}].cluster_id", i),
+                "unknown_cluster",
+                "cluster_id must be an existing product cluster",
+            ));
+        }
+    }
+    Ok(errors)
+}
+
+/// Trims names and drops empty barcodes so `""` means free text.
+fn normalize_list(mut list: SavedListInput) -> SavedListInput {
+    list.name = list.name.trim().to_string();
+    for entry in &mut list.grocery_list {
+        entry.name = entry.name.trim().to_string();
+        entry.barcode = entry.barcode.take().map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+    }
+    list
+}
+
+fn list_not_found(id: Uuid) -> ApiError {
+    ApiError::not_found("list_not_found", format!("no saved list {}", id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/lists",
+    tag = "accounts",
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "The user's lists, most recently updated first", body = [SavedListSummary]),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_lists(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+) -> Result<Json<Vec<SavedListSummary>>, ApiError> {
+    Ok(Json(db.saved_lists(auth.user.id).await.context("listing saved lists")?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/lists",
+    tag = "accounts",
+    request_body = SavedListInput,
+    security(("session_token" = [])),
+    responses(
+        (status = 201, description = "List saved", body = SavedList),
+        (status = 400, description = "Invalid name, entries or barcodes", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "List limit reached", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_list(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+    list: Result<Json<SavedListInput>, JsonRejection>,
+) -> Result<(StatusCode, Json<SavedList>), ApiError> {
+    let Json(list) = list?;
+    let list = normalize_list(list);
+    validate(saved_list_errors(&db, &list).await?)?;
+    if db.saved_list_count(auth.user.id).await.context("counting saved lists")? >= MAX_LISTS_PER_USER {
+        return Err(ApiError::conflict(
+            "list_limit_reached",
+            format!("at most {} saved lists per account", MAX_LISTS_PER_USER),
+        ));
+    }
+
+    let saved = db.create_saved_list(auth.user.id, &list).await.context("saving list")?;
+    Ok((StatusCode::CREATED, Json(saved)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/lists/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path, description = "Saved list ID")),
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "The list with its entries", body = SavedList),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such list for this user", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_list(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+    id: Result<Path<Uuid>, PathRejection>,
+) -> Result<Json<SavedList>, ApiError> {
+    let Path(id) = id?;
+    let list = db
+        .saved_list(auth.user.id, id)
+        .await
+        .with_context(|| format!("fetching saved list {}", id))?
+        .ok_or_else(|| list_not_found(id))?;
+    Ok(Json(list))
+}
+
+/// Replaces the list's name and every entry.
+#[utoipa::path(
+    put,
+    path = "/lists/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path, description = "Saved list ID")),
+    request_body = SavedListInput,
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Updated list", body = SavedList),
+        (status = 400, description = "Invalid name, entries or barcodes", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such list for this user", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn update_list(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+    id: Result<Path<Uuid>, PathRejection>,
+    list: Result<Json<SavedListInput>, JsonRejection>,
+) -> Result<Json<SavedList>, ApiError> {
+    let Path(id) = id?;
+    let Json(list) = list?;
+    let list = normalize_list(list);
+    validate(saved_list_errors(&db, &list).await?)?;
+
+    let saved = db
+        .replace_saved_list(auth.user.id, id, &list)
+        .await
+        .with_context(|| format!("updating saved list {}", id))?
+        .ok_or_else(|| list_not_found(id))?;
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/lists/{id}",
+    tag = "accounts",
+    params(("id" = Uuid, Path, description = "Saved list ID")),
+    security(("session_token" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such list for this user", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_list(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+    id: Result<Path<Uuid>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let Path(id) = id?;
+    if !db.delete_saved_list(auth.user.id, id).await.with_context(|| format!("deleting saved list {}", id))? {
+        return Err(list_not_found(id));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Every compare option is optional, so an empty or missing body means the defaults.
+fn compare_options_from_body(body: &[u8]) -> Result<CompareOptions, JsonRejection> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(CompareOptions::default());
+    }
+    let Json(options) = Json::from_bytes(body)?;
+    Ok(options)
+}
+
+/// Compares a saved list as-is; the body carries only the compare options
+/// (location, city, paging, max data age) and may be omitted.
+#[utoipa::path(
+    post,
+    path = "/lists/{id}/compare",
+    tag = "compare",
+    params(("id" = Uuid, Path, description = "Saved list ID")),
+    request_body = Option<CompareOptions>,
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Stores ranked by coverage, then quantity-weighted total", body = PriceComparisonResponseV2),
+        (status = 400, description = "Invalid options", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "No valid session", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such list for this user", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn compare_saved_list(
+    State(db): State<Arc<DatabaseManager>>,
+    auth: AuthUser,
+    id: Result<Path<Uuid>, PathRejection>,
+    body: Bytes,
+) -> Result<Json<PriceComparisonResponseV2>, ApiError> {
+    let Path(id) = id?;
+    let options = compare_options_from_body(&body)?;
+    validate(compare_options_errors(&options))?;
+
+    let list = db
+        .saved_list(auth.user.id, id)
+        .await
+        .with_context(|| format!("fetching saved list {}", id))?
+        .ok_or_else(|| list_not_found(id))?;
+    Ok(Json(compare_entries(&db, &list.grocery_list, options).await?))
+}
+
+/// Sign-up, sessions and saved-list CRUD — the same in every API version.
+pub fn account_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/register", post(register))
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(me))
+        .route("/lists", get(get_lists).post(create_list))
+        .route("/lists/:id", get(get_list).put(update_list).delete(delete_list))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GroceryListEntry;
+
+    #[test]
+    fn emails_normalized() {
+        assert_eq!(normalize_email("  Dana@Example.co.IL "), Some("dana@example.co.il".to_string()));
+        assert_eq!(normalize_email("no-at-sign"), None);
+        assert_eq!(normalize_email("a@localhost"), None);
+        assert_eq!(normalize_email("a b@example.com"), None);
+        assert_eq!(normalize_email("a@b@example.com"), None);
+    }
+
+    #[test]
+    fn password_length_enforced() {
+        let short = Credentials { email: "a@example.com".to_string(), password: "1234567".to_string() };
+        let codes: Vec<_> = credential_errors(&short).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["password_length"]);
+        let ok = Credentials { email: "a@example.com".to_string(), password: "correct horse".to_string() };
+        assert!(credential_errors(&ok).is_empty());
+    }
+
+    #[test]
+    fn compare_body_may_be_empty() {
+        assert!(compare_options_from_body(b"").is_ok());
+        assert!(compare_options_from_body(b" \n").is_ok());
+        assert!(compare_options_from_body(b"{}").is_ok());
+        assert!(compare_options_from_body(b"{\"page\": 2}").is_ok_and(|o| o.page == Some(2)));
+        assert!(compare_options_from_body(b"{not json").is_err());
+    }
+
+    #[test]
+    fn session_tokens_are_random_hex() {
+        let (a, b) = (new_session_token(), new_session_token());
+        assert_eq!(a.len(), 64);
+        assert!(a.bytes().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+        assert_eq!(token_hash(&a).len(), 32);
+    }
+
+    #[tokio::test]
+    async fn password_round_trip() {
+        let hash = hash_password("correct horse".to_string()).await.unwrap();
+        assert!(verify_password("correct horse".to_string(), hash.clone()).await.unwrap());
+        assert!(!verify_password("wrong horse".to_string(), hash).await.unwrap());
+    }
+
+    #[test]
+    fn list_input_normalized() {
+        let list = normalize_list(SavedListInput {
+            name: "  שבועי ".to_string(),
+            grocery_list: vec![GroceryListEntry { barcode: Some(" ".to_string()), cluster_id: None, name: " חלב ".to_string(), quantity: 2 }],
+        });
+        assert_eq!(list.name, "שבועי");
+        assert_eq!(list.grocery_list[0].barcode, None);
+        assert_eq!(list.grocery_list[0].name, "חלב");
+    }
+}
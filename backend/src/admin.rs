@@ -0,0 +1,1120 @@
+use anyhow::Context;
+use axum::{
+    extract::{rejection::{JsonRejection, PathRejection}, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{Json, Response},
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::{collections::HashMap, env, sync::Arc};
+use tracing::{error, info, warn};
+
+use crate::api::AppState;
+use crate::categories::parse_prefix_range;
+use crate::database::{is_ean13, DatabaseManager};
+use crate::error::{validate, ApiError, FieldError, Problem};
+use crate::hebrew;
+use crate::models::{
+    CategoryRule, CategoryRuleInput, ChainName, ChainNameUpdate, ClusterListResponse, ClusterMember, ClusterMemberOverride, ClusterRebuildSummary,
+    IngestPauseUpdate, IngestStatus, LedgerResponse, MemberStatus, NameElectionSummary, Pagination, ProductCluster,
+    ItemCategoryOverride, ItemCategoryUpdate, ManufacturerAlias, ManufacturerAliasInput, ManufacturerMerge,
+    ManufacturerMergeSummary, ProductNames, PurgeSummary, ReclassifySummary, RuleKind, SearchSynonym, SynonymInput,
+};
+use crate::xml_processor::{
+    ingest_paused, pending_file_count, scan_in_progress, set_ingest_paused, XmlFileProcessor,
+};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+const MAX_DISPLAY_NAME_LEN: usize = 100;
+/// Matches the `search_synonyms` column width.
+const MAX_SYNONYM_LEN: usize = 100;
+/// Matches the `category_rules.pattern` column width.
+const MAX_RULE_PATTERN_LEN: usize = 100;
+/// Longest manufacturer spelling an operator may map; real ones are a few words.
+const MAX_ALIAS_LEN: usize = 200;
+
+/// Operator API settings, read from the environment.
+///
+/// `ADMIN_TOKEN` is the shared secret for `/admin`, sent as `Authorization: Bearer …`
+/// or `X-Api-Key: …`. Unset or empty leaves the admin router unmounted.
+#[derive(Debug, Clone, Default)]
+pub struct AdminConfig {
+    pub token: Option<Arc<str>>,
+}
+
+impl AdminConfig {
+    pub fn from_env() -> Self {
+        Self {
+            token: env::var("ADMIN_TOKEN")
+                .ok()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .map(Arc::from),
+        }
+    }
+}
+
+/// The credential a request presents: a bearer token, else an API key header.
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+}
+
+/// Compares without short-circuiting on the first differing byte.
+fn tokens_match(presented: &[u8], expected: &[u8]) -> bool {
+    presented.len() == expected.len()
+        && presented.iter().zip(expected).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn require_admin(State(state): State<AppState>, req: Request, next: Next) -> Result<Response, ApiError> {
+    let Some(expected) = state.admin.token.as_deref() else {
+        return Err(ApiError::not_found("route_not_found", "admin API is disabled"));
+    };
+    match presented_token(req.headers()) {
+        Some(token) if tokens_match(token.as_bytes(), expected.as_bytes()) => Ok(next.run(req).await),
+        Some(_) => Err(ApiError::unauthorized("invalid_credentials", "admin token rejected")),
+        None => Err(ApiError::unauthorized("missing_credentials", "admin token required")),
+    }
+}
+
+/// Chain IDs are 13-digit GS1 location numbers.
+pub(crate) fn is_chain_id(chain_id: &str) -> bool {
+    chain_id.len() == 13 && chain_id.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn chain_name_errors(chain_id: &str, update: &ChainNameUpdate) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if !is_chain_id(chain_id) {
+        errors.push(FieldError::new("chain_id", "invalid_chain_id", "chain_id must be 13 digits"));
+    }
+    let name = update.display_name.trim();
+    if name.is_empty() {
+        errors.push(FieldError::new("display_name", "display_name_empty", "display_name must not be empty"));
+    } else if name.chars().count() > MAX_DISPLAY_NAME_LEN {
+        errors.push(FieldError::new(
+            "display_name",
+            "display_name_too_long",
+            format!("display_name may be at most {} characters", MAX_DISPLAY_NAME_LEN),
+        ));
+    }
+    errors
+}
+
+/// Adds or renames a chain's display name — e.g. onboarding a chain the seed
+/// migration doesn't know yet.
+#[utoipa::path(
+    put,
+    path = "/chains/{chain_id}",
+    tag = "admin",
+    params(("chain_id" = String, Path, description = "13-digit chain ID")),
+    request_body = ChainNameUpdate,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Stored display name", body = ChainName),
+        (status = 400, description = "Malformed chain ID or name", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn put_chain_name(
+    State(db): State<Arc<DatabaseManager>>,
+    chain_id: Result<Path<String>, PathRejection>,
+    update: Result<Json<ChainNameUpdate>, JsonRejection>,
+) -> Result<Json<ChainName>, ApiError> {
+    let Path(chain_id) = chain_id?;
+    let Json(update) = update?;
+    validate(chain_name_errors(&chain_id, &update))?;
+
+    let display_name = update.display_name.trim().to_string();
+    db.upsert_chain_name(&chain_id, &display_name)
+        .await
+        .with_context(|| format!("upserting chain name for {}", chain_id))?;
+    Ok(Json(ChainName { chain_id, display_name }))
+}
+
+/// A bare XML filename from the watch directory — no path components.
+fn is_watch_filename(filename: &str) -> bool {
+    filename.len() <= 255
+        && filename.ends_with(".xml")
+        && !filename.starts_with('.')
+        && !filename.contains(['/', '\\'])
+}
+
+async fn ingest_status(state: &AppState) -> IngestStatus {
+    IngestStatus {
+        paused: ingest_paused(),
+        scan_running: scan_in_progress(),
+        pending_files: pending_file_count(&state.db, &state.health.watch_directory).await.ok(),
+    }
+}
+
+/// Scans the watch directory in the background on the ingestion pool.
+fn spawn_scan(state: &AppState) {
+    let processor = XmlFileProcessor::new(state.db.for_ingest(), state.health.watch_directory.clone());
+    tokio::spawn(async move {
+        if let Err(e) = processor.scan_existing_files().await {
+            error!("Admin-triggered scan failed: {}", e);
+        }
+    });
+}
+
+#[utoipa::path(
+    get,
+    path = "/ingest",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Pause flag, scan state and backlog", body = IngestStatus),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_ingest_status(State(state): State<AppState>) -> Json<IngestStatus> {
+    Json(ingest_status(&state).await)
+}
+
+/// Starts a scan of the watch directory, ingesting every file not in the ledger.
+#[utoipa::path(
+    post,
+    path = "/ingest/rescan",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 202, description = "Scan started", body = IngestStatus),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Ingestion paused or a scan is already running", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn rescan(State(state): State<AppState>) -> Result<(StatusCode, Json<IngestStatus>), ApiError> {
+    if ingest_paused() {
+        return Err(ApiError::conflict("ingest_paused", "ingestion is paused; unpause to rescan"));
+    }
+    if scan_in_progress() {
+        return Err(ApiError::conflict("scan_running", "a scan is already running"));
+    }
+    info!("Admin: rescan of {} requested", state.health.watch_directory);
+    spawn_scan(&state);
+    let mut status = ingest_status(&state).await;
+    status.scan_running = true;
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// Clears a file's ledger row and ingests it again in the background, even while paused.
+#[utoipa::path(
+    post,
+    path = "/ingest/files/{filename}/reingest",
+    tag = "admin",
+    params(("filename" = String, Path, description = "XML filename in the watch directory")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 202, description = "Reingest started", body = IngestStatus),
+        (status = 400, description = "Not a bare .xml filename", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such file in the watch directory", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn reingest_file(
+    State(state): State<AppState>,
+    filename: Result<Path<String>, PathRejection>,
+) -> Result<(StatusCode, Json<IngestStatus>), ApiError> {
+    let Path(filename) = filename?;
+    if !is_watch_filename(&filename) {
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "filename",
+            "invalid_filename",
+            "filename must be a bare .xml filename",
+        )]));
+    }
+    let path = std::path::Path::new(&state.health.watch_directory).join(&filename);
+    if !tokio::fs::metadata(&path).await.is_ok_and(|m| m.is_file()) {
+        return Err(ApiError::not_found("file_not_found", format!("{} is not in the watch directory", filename)));
+    }
+
+    state.db.forget_processed_file(&filename).await.with_context(|| format!("clearing ledger row for {}", filename))?;
+    info!("Admin: force-reingest of {}", filename);
+    let processor = XmlFileProcessor::new(state.db.for_ingest(), state.health.watch_directory.clone());
+    tokio::spawn(async move {
+        if let Err(e) = processor.reingest_file(&filename).await {
+            error!("Force-reingest of {} failed: {}", filename, e);
+        }
+    });
+    Ok((StatusCode::ACCEPTED, Json(ingest_status(&state).await)))
+}
+
+/// Pauses or resumes ingestion. Resuming starts a scan to pick up files that
+/// arrived while paused.
+#[utoipa::path(
+    put,
+    path = "/ingest/paused",
+    tag = "admin",
+    request_body = IngestPauseUpdate,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "New ingestion state", body = IngestStatus),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn put_ingest_paused(
+    State(state): State<AppState>,
+    update: Result<Json<IngestPauseUpdate>, JsonRejection>,
+) -> Result<Json<IngestStatus>, ApiError> {
+    let Json(update) = update?;
+    let was_paused = ingest_paused();
+    set_ingest_paused(update.paused);
+    if update.paused != was_paused {
+        info!("Admin: ingestion {}", if update.paused { "paused" } else { "resumed" });
+    }
+    if was_paused && !update.paused && !scan_in_progress() {
+        spawn_scan(&state);
+    }
+    Ok(Json(ingest_status(&state).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/ingest/ledger",
+    tag = "admin",
+    params(
+        ("q" = Option<String>, Query, description = "Filename filter"),
+        ("page" = Option<usize>, Query, description = "1-based page, default 1"),
+        ("limit" = Option<usize>, Query, description = "Page size 1-500, default 100"),
+    ),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Processed files, newest first", body = LedgerResponse),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_ledger(
+    State(db): State<Arc<DatabaseManager>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<LedgerResponse>, ApiError> {
+    let q = params.get("q").map(|s| s.as_str());
+    let page: usize = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1).max(1);
+    let limit: usize = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(100).clamp(1, 500);
+
+    let (entries, total) = db.processed_files_page(q, page, limit).await.context("reading ingest ledger")?;
+    let has_more = (page - 1) * limit + entries.len() < total;
+    Ok(Json(LedgerResponse { entries, pagination: Pagination { page, page_size: limit, total, has_more } }))
+}
+
+/// Deletes all of a chain's stores, items and price history.
+#[utoipa::path(
+    delete,
+    path = "/chains/{chain_id}/data",
+    tag = "admin",
+    params(("chain_id" = String, Path, description = "13-digit chain ID")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Rows deleted", body = PurgeSummary),
+        (status = 400, description = "Malformed chain ID", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn purge_chain(
+    State(db): State<Arc<DatabaseManager>>,
+    chain_id: Result<Path<String>, PathRejection>,
+) -> Result<Json<PurgeSummary>, ApiError> {
+    let Path(chain_id) = chain_id?;
+    if !is_chain_id(&chain_id) {
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "chain_id",
+            "invalid_chain_id",
+            "chain_id must be 13 digits",
+        )]));
+    }
+    let summary = db.purge_chain(&chain_id).await.with_context(|| format!("purging chain {}", chain_id))?;
+    warn!("Admin: purged chain {}: {:?}", chain_id, summary);
+    Ok(Json(summary))
+}
+
+/// Deletes one store with its items and price history.
+#[utoipa::path(
+    delete,
+    path = "/stores/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Store primary key (StoreInfo.id)")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Rows deleted", body = PurgeSummary),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such store", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn purge_store(
+    State(db): State<Arc<DatabaseManager>>,
+    store_id: Result<Path<i32>, PathRejection>,
+) -> Result<Json<PurgeSummary>, ApiError> {
+    let Path(store_id) = store_id?;
+    let summary = db.purge_store(store_id).await.with_context(|| format!("purging store {}", store_id))?;
+    if summary.stores == 0 {
+        return Err(ApiError::not_found("store_not_found", format!("no store with id {}", store_id)));
+    }
+    warn!("Admin: purged store {}: {:?}", store_id, summary);
+    Ok(Json(summary))
+}
+
+fn synonym_errors(input: &SynonymInput) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (field, value) in [("term", &input.term), ("alias", &input.alias)] {
+        if hebrew::normalize(value).is_empty() {
+            errors.push(FieldError::new(field, "synonym_empty", format!("{} must not be empty", field)));
+        } else if value.trim().chars().count() > MAX_SYNONYM_LEN {
+            errors.push(FieldError::new(
+                field,
+                "synonym_too_long",
+                format!("{} may be at most {} characters", field, MAX_SYNONYM_LEN),
+            ));
+        }
+    }
+    if errors.is_empty() && hebrew::normalize(&input.term) == hebrew::normalize(&input.alias) {
+        errors.push(FieldError::new("alias", "synonym_identical", "alias must differ from term"));
+    }
+    errors
+}
+
+fn trimmed_synonym(input: SynonymInput) -> SynonymInput {
+    SynonymInput { term: input.term.trim().to_string(), alias: input.alias.trim().to_string(), ..input }
+}
+
+fn synonym_exists() -> ApiError {
+    ApiError::conflict("synonym_exists", "an equivalent synonym rule already exists")
+}
+
+fn synonym_not_found(id: i32) -> ApiError {
+    ApiError::not_found("synonym_not_found", format!("no synonym rule with id {}", id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/synonyms",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Every synonym rule, by term", body = [SearchSynonym]),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_synonyms(State(db): State<Arc<DatabaseManager>>) -> Result<Json<Vec<SearchSynonym>>, ApiError> {
+    Ok(Json(db.list_synonyms().await.context("listing synonyms")?))
+}
+
+/// Adds a search synonym: searches for `term` also match `alias` (and the reverse,
+/// unless `bidirectional` is false). Applies to search, autocomplete and compare.
+#[utoipa::path(
+    post,
+    path = "/synonyms",
+    tag = "admin",
+    request_body = SynonymInput,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Rule added", body = SearchSynonym),
+        (status = 400, description = "Empty, overlong or identical term and alias", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "An equivalent rule exists", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_synonym(
+    State(db): State<Arc<DatabaseManager>>,
+    input: Result<Json<SynonymInput>, JsonRejection>,
+) -> Result<(StatusCode, Json<SearchSynonym>), ApiError> {
+    let Json(input) = input?;
+    let input = trimmed_synonym(input);
+    validate(synonym_errors(&input))?;
+
+    let synonym = db.create_synonym(&input).await.context("adding synonym")?.ok_or_else(synonym_exists)?;
+    info!("Admin: added synonym {:?} -> {:?}", synonym.term, synonym.alias);
+    Ok((StatusCode::CREATED, Json(synonym)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/synonyms/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Synonym rule ID")),
+    request_body = SynonymInput,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Updated rule", body = SearchSynonym),
+        (status = 400, description = "Empty, overlong or identical term and alias", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such rule", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "An equivalent rule exists", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn update_synonym(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+    input: Result<Json<SynonymInput>, JsonRejection>,
+) -> Result<Json<SearchSynonym>, ApiError> {
+    let Path(id) = id?;
+    let Json(input) = input?;
+    let input = trimmed_synonym(input);
+    validate(synonym_errors(&input))?;
+
+    if db.synonym_taken(&input, Some(id)).await.context("checking synonym")? {
+        return Err(synonym_exists());
+    }
+    let synonym = db
+        .update_synonym(id, &input)
+        .await
+        .with_context(|| format!("updating synonym {}", id))?
+        .ok_or_else(|| synonym_not_found(id))?;
+    Ok(Json(synonym))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/synonyms/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Synonym rule ID")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such rule", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_synonym(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let Path(id) = id?;
+    if !db.delete_synonym(id).await.with_context(|| format!("deleting synonym {}", id))? {
+        return Err(synonym_not_found(id));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-clusters non-catalog items across chains now rather than after the next scan.
+/// Runs on the ingestion pool.
+#[utoipa::path(
+    post,
+    path = "/clusters/rebuild",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Clusters after the rebuild", body = ClusterRebuildSummary),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn rebuild_clusters(State(state): State<AppState>) -> Result<Json<ClusterRebuildSummary>, ApiError> {
+    let summary = state.db.for_ingest().rebuild_product_clusters().await.context("rebuilding product clusters")?;
+    info!("Admin: rebuilt product clusters: {:?}", summary);
+    Ok(Json(summary))
+}
+
+#[utoipa::path(
+    get,
+    path = "/clusters",
+    tag = "admin",
+    params(
+        ("q" = Option<String>, Query, description = "Cluster name filter"),
+        ("page" = Option<usize>, Query, description = "1-based page, default 1"),
+        ("limit" = Option<usize>, Query, description = "Page size 1-100, default 20"),
+    ),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Clusters with their members, largest first", body = ClusterListResponse),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_clusters(
+    State(db): State<Arc<DatabaseManager>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<ClusterListResponse>, ApiError> {
+    let q = params.get("q").map(|s| s.as_str());
+    let page: usize = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1).max(1);
+    let limit: usize = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(20).clamp(1, 100);
+
+    let (clusters, total) = db.product_clusters_page(q, page, limit).await.context("listing product clusters")?;
+    let has_more = (page - 1) * limit + clusters.len() < total;
+    Ok(Json(ClusterListResponse { clusters, pagination: Pagination { page, page_size: limit, total, has_more } }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/clusters/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Cluster ID")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "The cluster with its members", body = ProductCluster),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such cluster", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_cluster(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+) -> Result<Json<ProductCluster>, ApiError> {
+    let Path(id) = id?;
+    let cluster = db
+        .product_cluster(id)
+        .await
+        .with_context(|| format!("fetching cluster {}", id))?
+        .ok_or_else(|| ApiError::not_found("cluster_not_found", format!("no product cluster {}", id)))?;
+    Ok(Json(cluster))
+}
+
+fn cluster_member_errors(chain_id: &str, update: Option<&ClusterMemberOverride>) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if !is_chain_id(chain_id) {
+        errors.push(FieldError::new("chain_id", "invalid_chain_id", "chain_id must be 13 digits"));
+    }
+    if update.is_some_and(|u| u.status == MemberStatus::Auto) {
+        errors.push(FieldError::new(
+            "status",
+            "invalid_override",
+            "status must be confirmed or rejected; DELETE clears an override",
+        ));
+    }
+    errors
+}
+
+fn cluster_member_not_found(id: i32, chain_id: &str, item_code: &str) -> ApiError {
+    ApiError::not_found(
+        "cluster_member_not_found",
+        format!("no cluster {} member for item {} of chain {}", id, item_code, chain_id),
+    )
+}
+
+/// Confirms an item as part of a cluster (moving it out of any other) or rejects it
+/// from this one. Overrides survive rebuilds.
+#[utoipa::path(
+    put,
+    path = "/clusters/{id}/members/{chain_id}/{item_code}",
+    tag = "admin",
+    params(
+        ("id" = i32, Path, description = "Cluster ID"),
+        ("chain_id" = String, Path, description = "13-digit chain ID"),
+        ("item_code" = String, Path, description = "The chain's internal item code"),
+    ),
+    request_body = ClusterMemberOverride,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "The member as overridden", body = ClusterMember),
+        (status = 400, description = "Malformed chain ID or status", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such cluster, or the chain doesn't carry the item", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn put_cluster_member(
+    State(db): State<Arc<DatabaseManager>>,
+    path: Result<Path<(i32, String, String)>, PathRejection>,
+    update: Result<Json<ClusterMemberOverride>, JsonRejection>,
+) -> Result<Json<ClusterMember>, ApiError> {
+    let Path((id, chain_id, item_code)) = path?;
+    let Json(update) = update?;
+    validate(cluster_member_errors(&chain_id, Some(&update)))?;
+
+    let member = db
+        .set_cluster_member_status(id, &chain_id, &item_code, update.status)
+        .await
+        .with_context(|| format!("overriding cluster {} member {}/{}", id, chain_id, item_code))?
+        .ok_or_else(|| cluster_member_not_found(id, &chain_id, &item_code))?;
+    info!("Admin: cluster {} member {}/{} set to {:?}", id, chain_id, item_code, member.status);
+    Ok(Json(member))
+}
+
+/// Removes a member and any override on it; the next rebuild may re-add it.
+#[utoipa::path(
+    delete,
+    path = "/clusters/{id}/members/{chain_id}/{item_code}",
+    tag = "admin",
+    params(
+        ("id" = i32, Path, description = "Cluster ID"),
+        ("chain_id" = String, Path, description = "13-digit chain ID"),
+        ("item_code" = String, Path, description = "The chain's internal item code"),
+    ),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Removed"),
+        (status = 400, description = "Malformed chain ID", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such member", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_cluster_member(
+    State(db): State<Arc<DatabaseManager>>,
+    path: Result<Path<(i32, String, String)>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let Path((id, chain_id, item_code)) = path?;
+    validate(cluster_member_errors(&chain_id, None))?;
+    if !db
+        .clear_cluster_member(id, &chain_id, &item_code)
+        .await
+        .with_context(|| format!("clearing cluster {} member {}/{}", id, chain_id, item_code))?
+    {
+        return Err(cluster_member_not_found(id, &chain_id, &item_code));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn category_rule_errors(input: &CategoryRuleInput) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let pattern = input.pattern.trim();
+    if pattern.chars().count() > MAX_RULE_PATTERN_LEN {
+        errors.push(FieldError::new(
+            "pattern",
+            "rule_too_long",
+            format!("pattern may be at most {} characters", MAX_RULE_PATTERN_LEN),
+        ));
+    } else if input.kind == RuleKind::BarcodePrefix {
+        if parse_prefix_range(pattern).is_none() {
+            errors.push(FieldError::new(
+                "pattern",
+                "invalid_prefix_range",
+                "pattern must be a digit prefix or a from-to range of equal-length digit prefixes",
+            ));
+        }
+    } else if !hebrew::normalize(pattern).chars().any(char::is_alphanumeric) {
+        errors.push(FieldError::new("pattern", "rule_empty", "pattern must contain a word"));
+    }
+    errors
+}
+
+fn unknown_category(id: i32) -> ApiError {
+    ApiError::Validation(vec![FieldError::new("category_id", "unknown_category", format!("no category {}", id))])
+}
+
+#[utoipa::path(
+    get,
+    path = "/categories/rules",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Every classification rule", body = [CategoryRule]),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_category_rules(State(db): State<Arc<DatabaseManager>>) -> Result<Json<Vec<CategoryRule>>, ApiError> {
+    Ok(Json(db.category_rules().await.context("listing category rules")?))
+}
+
+/// Adds a classification rule. It applies to newly ingested items at once and to
+/// existing ones after POST /categories/reclassify.
+#[utoipa::path(
+    post,
+    path = "/categories/rules",
+    tag = "admin",
+    request_body = CategoryRuleInput,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Rule added", body = CategoryRule),
+        (status = 400, description = "Empty, overlong or malformed pattern, or unknown category", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "A rule of this kind has the same pattern", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_category_rule(
+    State(db): State<Arc<DatabaseManager>>,
+    input: Result<Json<CategoryRuleInput>, JsonRejection>,
+) -> Result<(StatusCode, Json<CategoryRule>), ApiError> {
+    let Json(input) = input?;
+    validate(category_rule_errors(&input))?;
+    if !db.category_exists(input.category_id).await.context("looking up category")? {
+        return Err(unknown_category(input.category_id));
+    }
+
+    let rule = db
+        .create_category_rule(&input)
+        .await
+        .context("adding category rule")?
+        .ok_or_else(|| ApiError::conflict("rule_exists", "a rule of this kind already has this pattern"))?;
+    info!("Admin: added {} rule {:?} -> category {}", rule.kind.as_str(), rule.pattern, rule.category_id);
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/categories/rules/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Rule ID")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Rule deleted; items it classified keep their category until reclassified"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such rule", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_category_rule(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let Path(id) = id?;
+    if !db.delete_category_rule(id).await.with_context(|| format!("deleting category rule {}", id))? {
+        return Err(ApiError::not_found("rule_not_found", format!("no category rule with id {}", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-runs the rules over every item that isn't pinned. Runs on the ingestion pool.
+#[utoipa::path(
+    post,
+    path = "/categories/reclassify",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Items classified and left unclassified", body = ReclassifySummary),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn reclassify(State(state): State<AppState>) -> Result<Json<ReclassifySummary>, ApiError> {
+    let summary = state.db.for_ingest().reclassify_items(None, None).await.context("reclassifying items")?;
+    info!("Admin: reclassified items: {:?}", summary);
+    Ok(Json(summary))
+}
+
+fn item_category_errors(item_code: &str, chain_id: Option<&str>) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    match chain_id {
+        Some(chain_id) if !is_chain_id(chain_id) => {
+            errors.push(FieldError::new("chain_id", "invalid_chain_id", "chain_id must be 13 digits"));
+        }
+        None if !is_ean13(item_code) => errors.push(FieldError::new(
+            "chain_id",
+            "chain_id_required",
+            "chain_id is required for internal item codes",
+        )),
+        _ => {}
+    }
+    errors
+}
+
+/// Pins an item to a category, overriding the rules. Without `chain_id` (barcodes
+/// only) every chain's listing of the product is pinned.
+#[utoipa::path(
+    put,
+    path = "/categories/items/{item_code}",
+    tag = "admin",
+    params(("item_code" = String, Path, description = "Barcode or chain-internal item code")),
+    request_body = ItemCategoryOverride,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Chain items pinned", body = ItemCategoryUpdate),
+        (status = 400, description = "Malformed or missing chain ID, or unknown category", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No chain carries the item", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn put_item_category(
+    State(db): State<Arc<DatabaseManager>>,
+    item_code: Result<Path<String>, PathRejection>,
+    update: Result<Json<ItemCategoryOverride>, JsonRejection>,
+) -> Result<Json<ItemCategoryUpdate>, ApiError> {
+    let Path(item_code) = item_code?;
+    let Json(update) = update?;
+    let chain_id = update.chain_id.as_deref();
+    validate(item_category_errors(&item_code, chain_id))?;
+    if !db.category_exists(update.category_id).await.context("looking up category")? {
+        return Err(unknown_category(update.category_id));
+    }
+
+    let items = db
+        .pin_item_category(&item_code, chain_id, update.category_id)
+        .await
+        .with_context(|| format!("pinning category of {}", item_code))?;
+    if items == 0 {
+        return Err(ApiError::not_found("item_not_found", format!("no chain carries item {}", item_code)));
+    }
+    info!("Admin: pinned {} ({} chain items) to category {}", item_code, items, update.category_id);
+    Ok(Json(ItemCategoryUpdate { items }))
+}
+
+/// Unpins an item and lets the rules classify it again.
+#[utoipa::path(
+    delete,
+    path = "/categories/items/{item_code}",
+    tag = "admin",
+    params(
+        ("item_code" = String, Path, description = "Barcode or chain-internal item code"),
+        ("chain_id" = Option<String>, Query, description = "Only this chain's item; required for internal codes"),
+    ),
+    security(("admin_token" = [])),
+    responses(
+        (status = 204, description = "Unpinned"),
+        (status = 400, description = "Malformed or missing chain ID", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "The item isn't pinned", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_item_category(
+    State(db): State<Arc<DatabaseManager>>,
+    item_code: Result<Path<String>, PathRejection>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<StatusCode, ApiError> {
+    let Path(item_code) = item_code?;
+    let chain_id = params.get("chain_id").map(|s| s.as_str());
+    validate(item_category_errors(&item_code, chain_id))?;
+    if !db
+        .unpin_item_category(&item_code, chain_id)
+        .await
+        .with_context(|| format!("unpinning category of {}", item_code))?
+    {
+        return Err(ApiError::not_found("item_not_pinned", format!("item {} has no pinned category", item_code)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Re-elects canonical product names now rather than after the next scan.
+/// Runs on the ingestion pool.
+#[utoipa::path(
+    post,
+    path = "/products/names/elect",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Products considered and renamed", body = NameElectionSummary),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn elect_names(State(state): State<AppState>) -> Result<Json<NameElectionSummary>, ApiError> {
+    let summary = state.db.for_ingest().elect_canonical_names().await.context("electing canonical names")?;
+    info!("Admin: elected canonical names: {:?}", summary);
+    Ok(Json(summary))
+}
+
+#[utoipa::path(
+    get,
+    path = "/products/{barcode}/names",
+    tag = "admin",
+    params(("barcode" = String, Path, description = "EAN-13 barcode")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "The canonical name and every chain's name variants", body = ProductNames),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Barcode not in the catalog", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_product_names(
+    State(db): State<Arc<DatabaseManager>>,
+    barcode: Result<Path<String>, PathRejection>,
+) -> Result<Json<ProductNames>, ApiError> {
+    let Path(barcode) = barcode?;
+    let names = db
+        .product_names(&barcode)
+        .await
+        .with_context(|| format!("fetching name variants of {}", barcode))?
+        .ok_or_else(|| ApiError::not_found("product_not_found", format!("no product with barcode {}", barcode)))?;
+    Ok(Json(names))
+}
+
+fn manufacturer_alias_errors(input: &ManufacturerAliasInput) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if input.alias.trim().is_empty() {
+        errors.push(FieldError::new("alias", "alias_empty", "alias must not be blank"));
+    } else if input.alias.chars().count() > MAX_ALIAS_LEN {
+        errors.push(FieldError::new(
+            "alias",
+            "alias_too_long",
+            format!("alias may be at most {} characters", MAX_ALIAS_LEN),
+        ));
+    }
+    errors
+}
+
+/// Maps a `manufacturer_name` spelling to a manufacturer, or to none with
+/// `manufacturer_id: null`. Ingest never overrides manual mappings.
+#[utoipa::path(
+    put,
+    path = "/manufacturers/aliases",
+    tag = "admin",
+    request_body = ManufacturerAliasInput,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "The alias as stored", body = ManufacturerAlias),
+        (status = 400, description = "Blank or overlong alias, or unknown manufacturer", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn put_manufacturer_alias(
+    State(db): State<Arc<DatabaseManager>>,
+    input: Result<Json<ManufacturerAliasInput>, JsonRejection>,
+) -> Result<Json<ManufacturerAlias>, ApiError> {
+    let Json(input) = input?;
+    validate(manufacturer_alias_errors(&input))?;
+    if let Some(id) = input.manufacturer_id
+        && !db.manufacturer_exists(id).await.context("looking up manufacturer")?
+    {
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "manufacturer_id",
+            "unknown_manufacturer",
+            format!("no manufacturer {}", id),
+        )]));
+    }
+
+    let alias = db
+        .set_manufacturer_alias(&input.alias, input.manufacturer_id)
+        .await
+        .with_context(|| format!("mapping manufacturer alias {:?}", input.alias))?;
+    info!("Admin: mapped manufacturer alias {:?} -> {:?}", alias.alias, alias.manufacturer_id);
+    Ok(Json(alias))
+}
+
+/// Folds one manufacturer into another: its aliases move over and it is deleted.
+#[utoipa::path(
+    post,
+    path = "/manufacturers/{id}/merge",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Manufacturer to merge away")),
+    request_body = ManufacturerMerge,
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Aliases moved to the surviving manufacturer", body = ManufacturerMergeSummary),
+        (status = 400, description = "Merging a manufacturer into itself", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or wrong admin token", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Either manufacturer doesn't exist", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn merge_manufacturer(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+    merge: Result<Json<ManufacturerMerge>, JsonRejection>,
+) -> Result<Json<ManufacturerMergeSummary>, ApiError> {
+    let Path(id) = id?;
+    let Json(merge) = merge?;
+    if merge.into == id {
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "into",
+            "merge_into_self",
+            "a manufacturer can't be merged into itself",
+        )]));
+    }
+
+    let aliases_moved = db
+        .merge_manufacturers(id, merge.into)
+        .await
+        .with_context(|| format!("merging manufacturer {} into {}", id, merge.into))?
+        .ok_or_else(|| {
+            ApiError::not_found("manufacturer_not_found", format!("no manufacturer {} or {}", id, merge.into))
+        })?;
+    info!("Admin: merged manufacturer {} into {} ({} aliases)", id, merge.into, aliases_moved);
+    Ok(Json(ManufacturerMergeSummary { aliases_moved }))
+}
+
+/// `/admin` routes behind the token check, or None when no token is configured.
+pub fn admin_router(state: &AppState) -> Option<Router<AppState>> {
+    state.admin.token.as_ref()?;
+    Some(
+        Router::new()
+            .route("/chains/:chain_id", put(put_chain_name))
+            .route("/chains/:chain_id/data", delete(purge_chain))
+            .route("/stores/:id", delete(purge_store))
+            .route("/ingest", get(get_ingest_status))
+            .route("/ingest/rescan", post(rescan))
+            .route("/ingest/paused", put(put_ingest_paused))
+            .route("/ingest/ledger", get(get_ledger))
+            .route("/ingest/files/:filename/reingest", post(reingest_file))
+            .route("/synonyms", get(list_synonyms).post(create_synonym))
+            .route("/synonyms/:id", put(update_synonym).delete(delete_synonym))
+            .route("/clusters", get(list_clusters))
+            .route("/clusters/rebuild", post(rebuild_clusters))
+            .route("/clusters/:id", get(get_cluster))
+            .route(
+                "/clusters/:id/members/:chain_id/:item_code",
+                put(put_cluster_member).delete(delete_cluster_member),
+            )
+            .route("/categories/rules", get(list_category_rules).post(create_category_rule))
+            .route("/categories/rules/:id", delete(delete_category_rule))
+            .route("/categories/reclassify", post(reclassify))
+            .route("/categories/items/:item_code", put(put_item_category).delete(delete_item_category))
+            .route("/products/names/elect", post(elect_names))
+            .route("/products/:barcode/names", get(get_product_names))
+            .route("/manufacturers/aliases", put(put_manufacturer_alias))
+            .route("/manufacturers/:id/merge", post(merge_manufacturer))
+            .route_layer(middleware::from_fn_with_state(state.clone(), require_admin)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn token_from_bearer_or_api_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_token(&headers), None);
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("k3y"));
+        assert_eq!(presented_token(&headers), Some("k3y"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer s3cret"));
+        assert_eq!(presented_token(&headers), Some("s3cret"));
+    }
+
+    #[test]
+    fn token_comparison() {
+        assert!(tokens_match(b"s3cret", b"s3cret"));
+        assert!(!tokens_match(b"s3cret", b"s3creT"));
+        assert!(!tokens_match(b"s3cre", b"s3cret"));
+    }
+
+    #[test]
+    fn reingest_takes_bare_xml_filenames() {
+        assert!(is_watch_filename("PriceFull7290027600007-001-202506030300.xml"));
+        assert!(!is_watch_filename("../etc/passwd.xml"));
+        assert!(!is_watch_filename("sub\\file.xml"));
+        assert!(!is_watch_filename(".hidden.xml"));
+        assert!(!is_watch_filename("notes.txt"));
+    }
+
+    #[test]
+    fn chain_name_validation() {
+        let ok = ChainNameUpdate { display_name: "אושר עד".to_string() };
+        assert!(chain_name_errors("7290103152017", &ok).is_empty());
+        let blank = ChainNameUpdate { display_name: "  ".to_string() };
+        let codes: Vec<_> = chain_name_errors("72901031", &blank).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["invalid_chain_id", "display_name_empty"]);
+    }
+
+    #[test]
+    fn cluster_override_validation() {
+        let confirm = ClusterMemberOverride { status: MemberStatus::Confirmed };
+        assert!(cluster_member_errors("7290103152017", Some(&confirm)).is_empty());
+        assert!(cluster_member_errors("7290103152017", None).is_empty());
+        let auto = ClusterMemberOverride { status: MemberStatus::Auto };
+        let codes: Vec<_> = cluster_member_errors("729", Some(&auto)).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["invalid_chain_id", "invalid_override"]);
+    }
+
+    #[test]
+    fn synonym_validation() {
+        let input = |term: &str, alias: &str| SynonymInput { term: term.to_string(), alias: alias.to_string(), bidirectional: true };
+        assert!(synonym_errors(&input("שמנת מתוקה", "שמנת להקצפה")).is_empty());
+        let codes = |i: &SynonymInput| synonym_errors(i).iter().map(|e| e.code).collect::<Vec<_>>();
+        assert_eq!(codes(&input(" ", &"א".repeat(101))), ["synonym_empty", "synonym_too_long"]);
+        // Same word once normalized.
+        assert_eq!(codes(&input("קוטג'", "קוטג׳")), ["synonym_identical"]);
+    }
+
+    #[test]
+    fn category_validation() {
+        let rule = |kind: RuleKind, pattern: &str| CategoryRuleInput { category_id: 1, kind, pattern: pattern.to_string(), priority: 0 };
+        let codes = |i: &CategoryRuleInput| category_rule_errors(i).iter().map(|e| e.code).collect::<Vec<_>>();
+        assert!(codes(&rule(RuleKind::Keyword, "משקה חלב")).is_empty());
+        assert!(codes(&rule(RuleKind::BarcodePrefix, "20-29")).is_empty());
+        assert_eq!(codes(&rule(RuleKind::Manufacturer, " ' ")), ["rule_empty"]);
+        assert_eq!(codes(&rule(RuleKind::BarcodePrefix, "29-2")), ["invalid_prefix_range"]);
+        assert_eq!(codes(&rule(RuleKind::Keyword, &"א".repeat(101))), ["rule_too_long"]);
+
+        assert!(item_category_errors("7290000066769", None).is_empty());
+        assert!(item_category_errors("1234", Some("7290103152017")).is_empty());
+        let codes: Vec<_> = item_category_errors("1234", None).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["chain_id_required"]);
+        let codes: Vec<_> = item_category_errors("1234", Some("729")).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["invalid_chain_id"]);
+    }
+
+    #[test]
+    fn manufacturer_alias_validation() {
+        let input = |alias: &str| ManufacturerAliasInput { alias: alias.to_string(), manufacturer_id: None };
+        assert!(manufacturer_alias_errors(&input("Tnuva")).is_empty());
+        let codes: Vec<_> = manufacturer_alias_errors(&input("  ")).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["alias_empty"]);
+        let codes: Vec<_> = manufacturer_alias_errors(&input(&"א".repeat(201))).iter().map(|e| e.code).collect();
+        assert_eq!(codes, ["alias_too_long"]);
+    }
+}
@@ -1,158 +1,1134 @@
+use anyhow::Context;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRef, Path, Query, State,
+    },
+    http::{header, HeaderValue},
+    response::{Json, Response},
+    middleware,
     routing::{get, post},
     Router,
 };
-use std::{collections::HashMap, sync::Arc};
-use tracing::error;
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
 
-use crate::database::DatabaseManager;
-use crate::models::{LocationQuery, PaginatedItems, PriceComparisonRequest, PriceComparisonResponse, ProductSearchResult, StoreInfo};
+use crate::accounts::{account_routes, compare_saved_list};
+use crate::shares::{share_routes, shared_entries};
+use crate::admin::{admin_router, is_chain_id, AdminConfig};
+use crate::database::{chain_local_now, is_ean13, page_bounds, DatabaseManager, ItemFilter, ItemPage};
+use crate::error::{request_id, validate, ApiError, FieldError, Problem};
+use crate::health::{chain_ages, health_check, health_details, pool_stats, ready, HealthConfig};
+use crate::metrics::track_http;
+use crate::openapi::docs_router;
+use crate::clustering::cluster_term;
+use crate::matching::product_families;
+use crate::ranking::Ranking;
+use crate::models::{
+    BasketLine, BrandDetail, Category, ChainSummary, CompareOptions, FreshnessResponse, GroceryListEntry, LocationQuery, MissingPolicy,
+    PaginatedItems, Pagination, PriceComparisonRequest, PriceComparisonRequestV2, PriceComparisonResponse,
+    PriceComparisonResponseV2, ProductDetail, ProductQuery, ProductSearchResult, ProductSort, ProductStorePrice,
+    RankingMode, ResolveQuery, StoreBasket, StoreDetail, StoreFilter, StoreInfo, TermResolution,
+};
+
+/// Shared router state. Handlers that only need the database keep extracting
+/// `State<Arc<DatabaseManager>>` via `FromRef`.
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+    pub health: HealthConfig,
+    pub admin: AdminConfig,
+}
+
+impl FromRef<AppState> for Arc<DatabaseManager> {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
 
 /// Hard limits on user-supplied input (see ARCHITECTURE.md §5.2 — input bounds).
 const MAX_GROCERY_LIST_LEN: usize = 100;
 const MAX_TERM_LEN: usize = 200;
 const MAX_RADIUS_KM: f64 = 200.0;
 
-pub async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "service": "shop-saver-api"
-    }))
+fn location_errors(location: &LocationQuery, prefix: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if let Some(radius_km) = location.radius_km
+        && !(0.0..=MAX_RADIUS_KM).contains(&radius_km)
+    {
+        errors.push(FieldError::new(
+            format!("{}radius_km", prefix),
+            "radius_out_of_range",
+            format!("radius_km must be between 0 and {}", MAX_RADIUS_KM),
+        ));
+    }
+    if !(-90.0..=90.0).contains(&location.latitude) {
+        errors.push(FieldError::new(
+            format!("{}latitude", prefix),
+            "latitude_out_of_range",
+            "latitude must be between -90 and 90",
+        ));
+    }
+    if !(-180.0..=180.0).contains(&location.longitude) {
+        errors.push(FieldError::new(
+            format!("{}longitude", prefix),
+            "longitude_out_of_range",
+            "longitude must be between -180 and 180",
+        ));
+    }
+    errors
 }
 
-pub async fn get_nearby_stores(
-    State(db): State<Arc<DatabaseManager>>,
-    Query(location): Query<LocationQuery>,
-) -> Result<Json<Vec<StoreInfo>>, StatusCode> {
-    let radius_km = location.radius_km.unwrap_or(10.0);
-    if !(0.0..=MAX_RADIUS_KM).contains(&radius_km)
-        || !(-90.0..=90.0).contains(&location.latitude)
-        || !(-180.0..=180.0).contains(&location.longitude)
+fn grocery_list_errors(grocery_list: &[String]) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if grocery_list.is_empty() {
+        errors.push(FieldError::new("grocery_list", "grocery_list_empty", "grocery_list must not be empty"));
+    } else if grocery_list.len() > MAX_GROCERY_LIST_LEN {
+        errors.push(FieldError::new(
+            "grocery_list",
+            "grocery_list_too_long",
+            format!("grocery_list may hold at most {} terms", MAX_GROCERY_LIST_LEN),
+        ));
+    }
+    for (i, term) in grocery_list.iter().enumerate() {
+        if term.is_empty() {
+            errors.push(FieldError::new(format!("grocery_list[{}]", i), "term_empty", "terms must not be empty"));
+        } else if term.len() > MAX_TERM_LEN {
+            errors.push(FieldError::new(
+                format!("grocery_list[{}]", i),
+                "term_too_long",
+                format!("terms may be at most {} bytes", MAX_TERM_LEN),
+            ));
+        }
+    }
+    errors
+}
+
+fn compare_request_errors(request: &PriceComparisonRequest) -> Vec<FieldError> {
+    let mut errors = grocery_list_errors(&request.grocery_list);
+    errors.extend(compare_options_errors(&request.options));
+    errors
+}
+
+/// A compare request names its list either inline or by `share_id`, never both.
+fn list_source_errors<T>(grocery_list: &[T]) -> Vec<FieldError> {
+    if grocery_list.is_empty() {
+        return vec![];
+    }
+    vec![FieldError::new(
+        "share_id",
+        "conflicting_list_source",
+        "send either grocery_list or share_id, not both",
+    )]
+}
+
+pub(crate) fn compare_options_errors(options: &CompareOptions) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if let Some(location) = &options.user_location {
+        errors.extend(location_errors(location, "user_location."));
+    }
+    if let Some(hours) = options.max_data_age_hours
+        && !(hours > 0.0 && hours <= MAX_DATA_AGE_HOURS)
+    {
+        errors.push(FieldError::new(
+            "max_data_age_hours",
+            "max_age_out_of_range",
+            format!("max_data_age_hours must be between 0 and {}", MAX_DATA_AGE_HOURS),
+        ));
+    }
+    if options.ranking == RankingMode::EffectiveCost && options.user_location.is_none() {
+        errors.push(FieldError::new(
+            "ranking",
+            "ranking_requires_location",
+            "effective_cost ranking needs user_location to measure distance",
+        ));
+    }
+    if let Some(cost) = options.cost_per_km
+        && !(0.0..=MAX_COST_PER_KM).contains(&cost)
     {
-        return Err(StatusCode::BAD_REQUEST);
+        errors.push(FieldError::new(
+            "cost_per_km",
+            "cost_out_of_range",
+            format!("cost_per_km must be between 0 and {}", MAX_COST_PER_KM),
+        ));
+    }
+    if let Some(value) = options.time_value_per_hour
+        && !(0.0..=MAX_TIME_VALUE_PER_HOUR).contains(&value)
+    {
+        errors.push(FieldError::new(
+            "time_value_per_hour",
+            "cost_out_of_range",
+            format!("time_value_per_hour must be between 0 and {}", MAX_TIME_VALUE_PER_HOUR),
+        ));
+    }
+    if options.missing_policy == MissingPolicy::EstimatedTotal && options.estimate_missing.is_none() {
+        errors.push(FieldError::new(
+            "missing_policy",
+            "policy_requires_estimates",
+            "estimated_total ranking needs estimate_missing",
+        ));
+    }
+    errors.extend(store_filter_errors(&options.store_filter));
+    errors
+}
+
+/// Upper bounds on the travel-cost knobs; anything above is a unit mix-up.
+const MAX_COST_PER_KM: f64 = 20.0;
+const MAX_TIME_VALUE_PER_HOUR: f64 = 1000.0;
+/// A year; older data is as good as no cutoff.
+const MAX_DATA_AGE_HOURS: f64 = 24.0 * 365.0;
+
+/// Longest include/exclude list a compare request may send.
+const MAX_FILTER_LEN: usize = 200;
+
+fn store_filter_errors(filter: &StoreFilter) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let lens = [
+        ("include_chains", filter.include_chains.len()),
+        ("exclude_chains", filter.exclude_chains.len()),
+        ("include_sub_chains", filter.include_sub_chains.len()),
+        ("exclude_sub_chains", filter.exclude_sub_chains.len()),
+        ("include_store_ids", filter.include_store_ids.len()),
+        ("exclude_store_ids", filter.exclude_store_ids.len()),
+    ];
+    for (field, len) in lens {
+        if len > MAX_FILTER_LEN {
+            errors.push(FieldError::new(
+                field,
+                "filter_too_long",
+                format!("{} may list at most {} entries", field, MAX_FILTER_LEN),
+            ));
+        }
     }
 
-    match db.get_nearby_stores(location.latitude, location.longitude, radius_km).await {
-        Ok(stores) => Ok(Json(stores)),
-        Err(e) => {
-            error!("Error getting nearby stores: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    let chain_fields = [("include_chains", &filter.include_chains), ("exclude_chains", &filter.exclude_chains)];
+    for (field, chains) in chain_fields {
+        for (i, chain_id) in chains.iter().enumerate() {
+            if !is_chain_id(chain_id) {
+                errors.push(FieldError::new(
+                    format!("{}[{}]", field, i),
+                    "invalid_chain_id",
+                    "chain IDs are 13-digit GLNs",
+                ));
+            }
         }
     }
+    let sub_chain_fields = [("include_sub_chains", &filter.include_sub_chains), ("exclude_sub_chains", &filter.exclude_sub_chains)];
+    for (field, sub_chains) in sub_chain_fields {
+        for (i, sub_chain) in sub_chains.iter().enumerate() {
+            if !is_chain_id(&sub_chain.chain_id) {
+                errors.push(FieldError::new(
+                    format!("{}[{}].chain_id", field, i),
+                    "invalid_chain_id",
+                    "chain IDs are 13-digit GLNs",
+                ));
+            }
+        }
+    }
+    let store_fields = [("include_store_ids", &filter.include_store_ids), ("exclude_store_ids", &filter.exclude_store_ids)];
+    for (field, ids) in store_fields {
+        for (i, id) in ids.iter().enumerate() {
+            if *id <= 0 {
+                errors.push(FieldError::new(format!("{}[{}]", field, i), "invalid_store_id", "store IDs are positive"));
+            }
+        }
+    }
+
+    // Listing the same chain or store on both sides is almost certainly a client bug.
+    if filter.include_chains.iter().any(|c| filter.exclude_chains.contains(c)) {
+        errors.push(FieldError::new(
+            "exclude_chains",
+            "conflicting_filter",
+            "a chain can't be both included and excluded",
+        ));
+    }
+    if filter.include_sub_chains.iter().any(|s| filter.exclude_sub_chains.contains(s)) {
+        errors.push(FieldError::new(
+            "exclude_sub_chains",
+            "conflicting_filter",
+            "a sub-chain can't be both included and excluded",
+        ));
+    }
+    if filter.include_store_ids.iter().any(|id| filter.exclude_store_ids.contains(id)) {
+        errors.push(FieldError::new(
+            "exclude_store_ids",
+            "conflicting_filter",
+            "a store can't be both included and excluded",
+        ));
+    }
+    errors
 }
 
+#[utoipa::path(
+    get,
+    path = "/stores/nearby",
+    tag = "stores",
+    params(LocationQuery),
+    responses(
+        (status = 200, description = "Stores within the radius, nearest first", body = [StoreInfo]),
+        (status = 400, description = "Coordinates or radius out of range", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_nearby_stores(
+    State(db): State<Arc<DatabaseManager>>,
+    location: Result<Query<LocationQuery>, QueryRejection>,
+) -> Result<Json<Vec<StoreInfo>>, ApiError> {
+    let Query(location) = location?;
+    validate(location_errors(&location, ""))?;
+    let radius_km = location.radius_km.unwrap_or(10.0);
+
+    let stores = db
+        .get_nearby_stores(location.latitude, location.longitude, radius_km)
+        .await
+        .context("getting nearby stores")?;
+    Ok(Json(stores))
+}
+
+#[utoipa::path(
+    post,
+    path = "/compare-prices",
+    tag = "compare",
+    request_body = PriceComparisonRequest,
+    responses(
+        (status = 200, description = "Stores ranked by coverage, then total price", body = PriceComparisonResponse),
+        (status = 400, description = "Invalid grocery list, location or options", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "`share_id` unknown or expired", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn compare_prices(
     State(db): State<Arc<DatabaseManager>>,
-    Json(request): Json<PriceComparisonRequest>,
-) -> Result<Json<PriceComparisonResponse>, StatusCode> {
-    if request.grocery_list.is_empty()
-        || request.grocery_list.len() > MAX_GROCERY_LIST_LEN
-        || request.grocery_list.iter().any(|t| t.is_empty() || t.len() > MAX_TERM_LEN)
-    {
-        return Err(StatusCode::BAD_REQUEST);
+    request: Result<Json<PriceComparisonRequest>, JsonRejection>,
+) -> Result<Json<PriceComparisonResponse>, ApiError> {
+    let Json(mut request) = request?;
+    if let Some(share_id) = request.share_id.take() {
+        validate(list_source_errors(&request.grocery_list))?;
+        request.grocery_list = shared_entries(&db, &share_id).await?.iter().map(entry_term).collect();
+    }
+    validate(compare_request_errors(&request))?;
+
+    let response = db.compare_prices(request, &[]).await.context("comparing prices")?;
+    Ok(Json(response))
+}
+
+/// Hard limit on a v2 entry's quantity.
+const MAX_QUANTITY: u32 = 99;
+
+/// The term a v2 entry compares on: its barcode when it's a valid EAN-13, else its
+/// cluster, else its name.
+fn entry_term(entry: &GroceryListEntry) -> String {
+    match (&entry.barcode, entry.cluster_id) {
+        (Some(barcode), _) if is_ean13(barcode) => barcode.clone(),
+        (_, Some(cluster_id)) => cluster_term(cluster_id),
+        _ => entry.name.trim().to_string(),
+    }
+}
+
+/// Reshapes the shared comparison result into v2 baskets: one line per entry,
+/// totals weighted by quantity and re-scored on those totals. The page was already
+/// ranked on the same weighted totals across every store; sorting it again here
+/// keeps that order.
+fn basket_response(
+    entries: &[GroceryListEntry],
+    response: PriceComparisonResponse,
+    ranking: &Ranking,
+    page: usize,
+    page_size: usize,
+) -> PriceComparisonResponseV2 {
+    let mut stores: Vec<StoreBasket> = response
+        .stores
+        .into_iter()
+        .map(|comparison| {
+            let estimating = comparison.estimated_total.is_some();
+            let estimates = comparison.missing_estimates;
+            let substitutions = comparison.substitutions;
+            let mut items = comparison.items;
+            let lines: Vec<BasketLine> = entries
+                .iter()
+                .enumerate()
+                .map(|(idx, entry)| {
+                    let item = items
+                        .iter()
+                        .position(|i| i.term_index == idx)
+                        .map(|pos| items.swap_remove(pos));
+                    let line_total = item.as_ref().map(|i| i.price * entry.quantity as f64);
+                    let estimated_line_total = estimates
+                        .iter()
+                        .find(|e| e.term_index == idx)
+                        .map(|e| e.estimated_price * entry.quantity as f64);
+                    BasketLine { entry: entry.clone(), item, line_total, estimated_line_total }
+                })
+                .collect();
+            let items_found = lines.iter().filter(|l| l.item.is_some()).count();
+            let total_price: f64 = lines.iter().filter_map(|l| l.line_total).sum();
+            let estimated_total = estimating
+                .then(|| total_price + lines.iter().filter_map(|l| l.estimated_line_total).sum::<f64>());
+            StoreBasket {
+                score: ranking.score(ranking.basket_total(total_price, estimated_total), comparison.store.distance_km),
+                store: comparison.store,
+                total_price,
+                estimated_total,
+                substitutions,
+                items_found,
+                items_missing: entries.len() - items_found,
+                lines,
+            }
+        })
+        .collect();
+
+    stores.sort_by(|a, b| {
+        let cost = |s: &StoreBasket| ranking.sort_cost(ranking.basket_total(s.total_price, s.estimated_total), s.score.as_ref());
+        ranking.cmp((a.items_found, cost(a)), (b.items_found, cost(b)))
+    });
+
+    PriceComparisonResponseV2 {
+        best_store_id: if page == 1 { stores.first().map(|s| s.store.id) } else { None },
+        stores,
+        pagination: Pagination { page, page_size, total: response.total_stores, has_more: response.has_more },
     }
+}
+
+/// v2 compare: the list is objects (barcode, name, quantity) and each store comes
+/// back as a basket with one line per entry. Same engine as v1 underneath.
+#[utoipa::path(
+    post,
+    path = "/compare-prices",
+    tag = "compare",
+    request_body = PriceComparisonRequestV2,
+    responses(
+        (status = 200, description = "Stores ranked by coverage, then quantity-weighted total", body = PriceComparisonResponseV2),
+        (status = 400, description = "Invalid grocery list, location or options", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "`share_id` unknown or expired", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn compare_prices_v2(
+    State(db): State<Arc<DatabaseManager>>,
+    request: Result<Json<PriceComparisonRequestV2>, JsonRejection>,
+) -> Result<Json<PriceComparisonResponseV2>, ApiError> {
+    let Json(mut request) = request?;
+    if let Some(share_id) = request.share_id.take() {
+        validate(list_source_errors(&request.grocery_list))?;
+        request.grocery_list = shared_entries(&db, &share_id).await?;
+    }
+    let mut errors = entry_errors(&request.grocery_list);
+    errors.extend(compare_options_errors(&request.options));
+    validate(errors)?;
+
+    Ok(Json(compare_entries(&db, &request.grocery_list, request.options).await?))
+}
 
-    match db.compare_prices(request).await {
-        Ok(response) => Ok(Json(response)),
-        Err(e) => {
-            error!("Error comparing prices: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+/// Validation for a `grocery_list` of v2 entries: the terms they compare on, and quantities.
+pub(crate) fn entry_errors(entries: &[GroceryListEntry]) -> Vec<FieldError> {
+    let terms: Vec<String> = entries.iter().map(entry_term).collect();
+    let mut errors = grocery_list_errors(&terms);
+    for (i, entry) in entries.iter().enumerate() {
+        if !(1..=MAX_QUANTITY).contains(&entry.quantity) {
+            errors.push(FieldError::new(
+                format!("grocery_list[{}].quantity", i),
+                "quantity_out_of_range",
+                format!("quantity must be between 1 and {}", MAX_QUANTITY),
+            ));
         }
     }
+    errors
+}
+
+/// Runs the comparison for already-validated v2 entries and shapes it into baskets.
+pub(crate) async fn compare_entries(
+    db: &DatabaseManager,
+    entries: &[GroceryListEntry],
+    options: CompareOptions,
+) -> Result<PriceComparisonResponseV2, ApiError> {
+    let terms: Vec<String> = entries.iter().map(entry_term).collect();
+    let (page, page_size) = page_bounds(&options);
+    let ranking = Ranking::from_options(&options);
+    let quantities: Vec<u32> = entries.iter().map(|e| e.quantity).collect();
+    let response = db
+        .compare_prices(PriceComparisonRequest { grocery_list: terms, share_id: None, options }, &quantities)
+        .await
+        .context("comparing prices (v2)")?;
+    Ok(basket_response(entries, response, &ranking, page, page_size))
 }
 
 /// Autocomplete: short list of {barcode, name} suggestions for a query.
 /// Distinct from GET /api/items (paginated browse with filters) below.
+#[utoipa::path(
+    get,
+    path = "/items/search",
+    tag = "items",
+    params(("q" = String, Query, description = "Search text; fewer than 2 characters returns []")),
+    responses((status = 200, description = "Up to 20 suggestions", body = [ProductSearchResult]))
+)]
 pub async fn search_items(
     State(db): State<Arc<DatabaseManager>>,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Vec<ProductSearchResult>>, StatusCode> {
+) -> Result<Json<Vec<ProductSearchResult>>, ApiError> {
     let q = params.get("q").map(|s| s.as_str()).unwrap_or("");
     if q.len() < 2 || q.len() > MAX_TERM_LEN {
         return Ok(Json(vec![]));
     }
-    match db.search_item_names(q, 20).await {
-        Ok(results) => Ok(Json(results)),
-        Err(e) => {
-            error!("Error searching item names: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
+    let results = db.search_item_names(q, 20).await.context("searching item names")?;
+    Ok(Json(results))
+}
+
+/// Catalog rows considered when resolving a term, before grouping into families.
+const RESOLVE_CANDIDATES: i64 = 300;
+const MAX_RESOLVE_FAMILIES: usize = 30;
+
+fn resolve_query_errors(query: &ResolveQuery) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let len = query.q.trim().chars().count();
+    if len < 2 {
+        errors.push(FieldError::new("q", "term_too_short", "q must be at least 2 characters"));
+    } else if len > MAX_TERM_LEN {
+        errors.push(FieldError::new(
+            "q",
+            "term_too_long",
+            format!("q must be at most {} characters", MAX_TERM_LEN),
+        ));
+    }
+    if let Some(limit) = query.limit
+        && !(1..=MAX_RESOLVE_FAMILIES).contains(&limit)
+    {
+        errors.push(FieldError::new(
+            "limit",
+            "limit_out_of_range",
+            format!("limit must be between 1 and {}", MAX_RESOLVE_FAMILIES),
+        ));
     }
+    errors
 }
 
-pub async fn get_all_stores_handler(
+/// Resolves a free-text term into concrete products, grouped into families of size
+/// variants, so the client can pin barcodes instead of comparing on the name.
+#[utoipa::path(
+    get,
+    path = "/items/resolve",
+    tag = "items",
+    params(ResolveQuery),
+    responses(
+        (status = 200, description = "Candidate product families, most relevant first", body = TermResolution),
+        (status = 400, description = "Term too short or long, or limit out of range", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn resolve_term_handler(
     State(db): State<Arc<DatabaseManager>>,
-) -> Result<Json<Vec<StoreInfo>>, StatusCode> {
-    match db.get_all_stores().await {
-        Ok(stores) => Ok(Json(stores)),
-        Err(e) => {
-            error!("Error fetching all stores: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    query: Result<Query<ResolveQuery>, QueryRejection>,
+) -> Result<Json<TermResolution>, ApiError> {
+    let Query(query) = query?;
+    validate(resolve_query_errors(&query))?;
+    let term = query.q.trim().to_string();
+
+    let candidates = db
+        .resolve_term_candidates(&term, RESOLVE_CANDIDATES)
+        .await
+        .with_context(|| format!("resolving term {:?}", term))?;
+    let families = product_families(&term, candidates, query.limit.unwrap_or(10));
+    Ok(Json(TermResolution { term, families }))
+}
+
+/// Sorts a product's store list: by price (ties nearest first), or by distance
+/// (stores without coordinates last, ties cheapest first).
+fn sort_product_stores(stores: &mut [ProductStorePrice], sort: ProductSort) {
+    let by_distance = |a: &ProductStorePrice, b: &ProductStorePrice| match (a.store.distance_km, b.store.distance_km) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    let by_price = |a: &ProductStorePrice, b: &ProductStorePrice| a.price.partial_cmp(&b.price).unwrap_or(Ordering::Equal);
+    match sort {
+        ProductSort::Price => stores.sort_by(|a, b| by_price(a, b).then_with(|| by_distance(a, b))),
+        ProductSort::Distance => stores.sort_by(|a, b| by_distance(a, b).then_with(|| by_price(a, b))),
+    }
+}
+
+fn product_query_errors(barcode: &str, query: &ProductQuery) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if barcode.is_empty() || barcode.len() > 14 || !barcode.bytes().all(|b| b.is_ascii_digit()) {
+        errors.push(FieldError::new("barcode", "invalid_barcode", "barcode must be up to 14 digits"));
+    }
+    match (query.latitude, query.longitude) {
+        (Some(latitude), Some(longitude)) => {
+            errors.extend(location_errors(&LocationQuery { latitude, longitude, radius_km: None }, ""));
         }
+        (None, None) => {
+            if query.sort == Some(ProductSort::Distance) {
+                errors.push(FieldError::new(
+                    "sort",
+                    "location_required",
+                    "sort=distance requires latitude and longitude",
+                ));
+            }
+        }
+        _ => errors.push(FieldError::new(
+            "latitude",
+            "incomplete_location",
+            "latitude and longitude must be given together",
+        )),
     }
+    errors
+}
+
+/// Everything known about one barcode: catalog data, the stores listing it with
+/// current prices, and the min/avg/max across them.
+#[utoipa::path(
+    get,
+    path = "/products/{barcode}",
+    tag = "items",
+    params(("barcode" = String, Path, description = "Product barcode (EAN-13)"), ProductQuery),
+    responses(
+        (status = 200, description = "Product with per-store prices", body = ProductDetail),
+        (status = 400, description = "Malformed barcode or location", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Barcode not in the catalog", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_product_handler(
+    State(db): State<Arc<DatabaseManager>>,
+    barcode: Result<Path<String>, PathRejection>,
+    query: Result<Query<ProductQuery>, QueryRejection>,
+) -> Result<Json<ProductDetail>, ApiError> {
+    let Path(barcode) = barcode?;
+    let Query(query) = query?;
+    validate(product_query_errors(&barcode, &query))?;
+
+    let origin = query.latitude.zip(query.longitude);
+    let mut product = db
+        .get_product(&barcode, origin)
+        .await
+        .with_context(|| format!("fetching product {}", barcode))?
+        .ok_or_else(|| ApiError::not_found("product_not_found", format!("no product with barcode {}", barcode)))?;
+    sort_product_stores(&mut product.stores, query.sort.unwrap_or_default());
+    Ok(Json(product))
 }
 
+#[utoipa::path(
+    get,
+    path = "/stores",
+    tag = "stores",
+    responses((status = 200, description = "Every known store", body = [StoreInfo]))
+)]
+pub async fn get_all_stores_handler(
+    State(db): State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<StoreInfo>>, ApiError> {
+    let stores = db.get_all_stores().await.context("fetching all stores")?;
+    Ok(Json(stores))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stores/{id}",
+    tag = "stores",
+    params(("id" = i32, Path, description = "Store primary key (StoreInfo.id)")),
+    responses(
+        (status = 200, description = "Store with chain, registry and price-level metadata", body = StoreDetail),
+        (status = 400, description = "Malformed store id", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such store", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_store_handler(
+    State(db): State<Arc<DatabaseManager>>,
+    store_id: Result<Path<i32>, PathRejection>,
+) -> Result<Json<StoreDetail>, ApiError> {
+    let Path(store_id) = store_id?;
+    let store = db
+        .get_store_detail(store_id)
+        .await
+        .with_context(|| format!("fetching store {}", store_id))?
+        .ok_or_else(|| ApiError::not_found("store_not_found", format!("no store with id {}", store_id)))?;
+    Ok(Json(store))
+}
+
+/// The `?category=<slug>` filter as category IDs (the category and its descendants).
+async fn category_filter(db: &DatabaseManager, params: &HashMap<String, String>) -> Result<Option<Vec<i32>>, ApiError> {
+    let Some(slug) = params.get("category").filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let ids = db
+        .category_subtree(slug)
+        .await
+        .with_context(|| format!("resolving category {}", slug))?
+        .ok_or_else(|| {
+            ApiError::Validation(vec![FieldError::new("category", "unknown_category", format!("no category {:?}", slug))])
+        })?;
+    Ok(Some(ids))
+}
+
+/// The `?brand=<manufacturer id>` filter.
+fn brand_filter(params: &HashMap<String, String>) -> Result<Option<i32>, ApiError> {
+    let Some(brand) = params.get("brand").filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let id = brand.parse().map_err(|_| {
+        ApiError::Validation(vec![FieldError::new("brand", "invalid_brand", format!("brand must be a manufacturer ID, got {:?}", brand))])
+    })?;
+    Ok(Some(id))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stores/{id}/items",
+    tag = "stores",
+    params(
+        ("id" = i32, Path, description = "Store primary key (StoreInfo.id)"),
+        ("q" = Option<String>, Query, description = "Name filter"),
+        ("category" = Option<String>, Query, description = "Category slug; includes its subcategories"),
+        ("brand" = Option<i32>, Query, description = "Manufacturer ID (BrandFacet.id)"),
+        ("page" = Option<usize>, Query, description = "1-based page, default 1"),
+        ("limit" = Option<usize>, Query, description = "Page size 1-100, default 20"),
+    ),
+    responses(
+        (status = 200, description = "Cheapest price per distinct item in this store", body = PaginatedItems),
+        (status = 400, description = "Malformed store id or unknown category or malformed brand", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_store_items_handler(
     State(db): State<Arc<DatabaseManager>>,
-    Path(store_id): Path<i32>,
+    store_id: Result<Path<i32>, PathRejection>,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<PaginatedItems>, StatusCode> {
+) -> Result<Json<PaginatedItems>, ApiError> {
+    let Path(store_id) = store_id?;
     let q = params.get("q").map(|s| s.as_str());
+    let categories = category_filter(&db, &params).await?;
+    let filter = ItemFilter { categories: categories.as_deref(), brand: brand_filter(&params)? };
     let page: usize = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1).max(1);
     let limit: usize = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(20).clamp(1, 100);
 
-    match db.get_store_items(store_id, q, page, limit).await {
-        Ok((items, total)) => {
-            let has_more = (page - 1) * limit + items.len() < total;
-            Ok(Json(PaginatedItems { items, total: total as i64, page, page_size: limit, has_more }))
-        }
-        Err(e) => {
-            error!("Error fetching items for store {}: {}", store_id, e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    let ItemPage { items, total, brands } = db
+        .get_store_items(store_id, q, &filter, page, limit)
+        .await
+        .with_context(|| format!("fetching items for store {}", store_id))?;
+    let has_more = (page - 1) * limit + items.len() < total;
+    Ok(Json(PaginatedItems { items, total: total as i64, page, page_size: limit, has_more, brands }))
 }
 
-/// Paginated browse across all stores with optional name + price filters.
+/// Paginated browse across all stores with optional name, price, category and brand filters.
+#[utoipa::path(
+    get,
+    path = "/items",
+    tag = "items",
+    params(
+        ("q" = Option<String>, Query, description = "Name filter"),
+        ("min_price" = Option<f64>, Query),
+        ("max_price" = Option<f64>, Query),
+        ("category" = Option<String>, Query, description = "Category slug; includes its subcategories"),
+        ("brand" = Option<i32>, Query, description = "Manufacturer ID (BrandFacet.id)"),
+        ("page" = Option<usize>, Query, description = "1-based page, default 1"),
+        ("limit" = Option<usize>, Query, description = "Page size 1-100, default 20"),
+    ),
+    responses(
+        (status = 200, description = "Cheapest price per distinct item name", body = PaginatedItems),
+        (status = 400, description = "Query too long or unknown category or malformed brand", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn search_items_handler(
     State(db): State<Arc<DatabaseManager>>,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<PaginatedItems>, StatusCode> {
+) -> Result<Json<PaginatedItems>, ApiError> {
     let q = params.get("q").map(|s| s.as_str()).unwrap_or("");
     if q.len() > MAX_TERM_LEN {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(ApiError::Validation(vec![FieldError::new(
+            "q",
+            "term_too_long",
+            format!("q may be at most {} bytes", MAX_TERM_LEN),
+        )]));
     }
     let min_price: Option<f64> = params.get("min_price").and_then(|p| p.parse().ok());
     let max_price: Option<f64> = params.get("max_price").and_then(|p| p.parse().ok());
+    let categories = category_filter(&db, &params).await?;
+    let filter = ItemFilter { categories: categories.as_deref(), brand: brand_filter(&params)? };
     let page: usize = params.get("page").and_then(|p| p.parse().ok()).unwrap_or(1).max(1);
     let limit: usize = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(20).clamp(1, 100);
 
-    match db.search_items_paginated(q, min_price, max_price, page, limit).await {
-        Ok((items, total)) => {
-            let has_more = (page - 1) * limit + items.len() < total;
-            Ok(Json(PaginatedItems { items, total: total as i64, page, page_size: limit, has_more }))
-        }
-        Err(e) => {
-            error!("Error searching items: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    let ItemPage { items, total, brands } = db
+        .search_items_paginated(q, min_price, max_price, &filter, page, limit)
+        .await
+        .context("searching items")?;
+    let has_more = (page - 1) * limit + items.len() < total;
+    Ok(Json(PaginatedItems { items, total: total as i64, page, page_size: limit, has_more, brands }))
+}
+
+/// Chains and stores ordered stalest first. `?stale_only=true` keeps only entries
+/// past the staleness threshold.
+#[utoipa::path(
+    get,
+    path = "/freshness",
+    tag = "freshness",
+    params(("stale_only" = Option<bool>, Query, description = "Only list stale chains and stores")),
+    responses((status = 200, description = "Chains and stores, stalest first", body = FreshnessResponse))
+)]
+pub async fn get_freshness_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<FreshnessResponse>, ApiError> {
+    let stale_only = params.get("stale_only").is_some_and(|v| v == "true" || v == "1");
+    let stale_after_hours = state.health.stale_after_hours;
+
+    let chains = state.db.chain_freshness().await.context("fetching chain freshness")?;
+    let stores = state.db.store_freshness().await.context("fetching store freshness")?;
+
+    let mut chains = chain_ages(chains, chain_local_now(), stale_after_hours);
+    chains.sort_by_key(|c| c.last_price_update);
+    let (chains, stores) = if stale_only {
+        (
+            chains.into_iter().filter(|c| c.is_stale).collect(),
+            stores.into_iter().filter(|s| s.is_stale).collect(),
+        )
+    } else {
+        (chains, stores)
+    };
+
+    Ok(Json(FreshnessResponse { stale_after_hours, chains, stores }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/chains",
+    tag = "chains",
+    responses((status = 200, description = "Every known chain with store and item counts and data age", body = [ChainSummary]))
+)]
+pub async fn get_chains_handler(
+    State(db): State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<ChainSummary>>, ApiError> {
+    let chains = db.get_chains().await.context("fetching chains")?;
+    Ok(Json(chains))
+}
+
+/// The product category tree, flattened: each parent followed by its children.
+#[utoipa::path(
+    get,
+    path = "/categories",
+    tag = "items",
+    responses((status = 200, description = "Every category; slugs are accepted by the items endpoints' `category` filter", body = [Category]))
+)]
+pub async fn get_categories_handler(
+    State(db): State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<Category>>, ApiError> {
+    let categories = db.categories().await.context("fetching categories")?;
+    Ok(Json(categories))
+}
+
+/// A brand: its `manufacturer_name` spellings and how its prices compare across chains.
+#[utoipa::path(
+    get,
+    path = "/brands/{id}",
+    tag = "items",
+    params(("id" = i32, Path, description = "Manufacturer ID (BrandFacet.id)")),
+    responses(
+        (status = 200, description = "The brand with per-chain price statistics", body = BrandDetail),
+        (status = 400, description = "Malformed brand id", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No such brand", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_brand_handler(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<i32>, PathRejection>,
+) -> Result<Json<BrandDetail>, ApiError> {
+    let Path(id) = id?;
+    let brand = db
+        .brand_detail(id)
+        .await
+        .with_context(|| format!("fetching brand {}", id))?
+        .ok_or_else(|| ApiError::not_found("brand_not_found", format!("no brand with id {}", id)))?;
+    Ok(Json(brand))
+}
+
+#[utoipa::path(
+    get,
+    path = "/chains/{chain_id}/stores",
+    tag = "chains",
+    params(("chain_id" = String, Path, description = "13-digit chain ID")),
+    responses(
+        (status = 200, description = "The chain's stores", body = [StoreInfo]),
+        (status = 404, description = "Unknown chain", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_chain_stores_handler(
+    State(db): State<Arc<DatabaseManager>>,
+    chain_id: Result<Path<String>, PathRejection>,
+) -> Result<Json<Vec<StoreInfo>>, ApiError> {
+    let Path(chain_id) = chain_id?;
+    let stores = db
+        .get_chain_stores(&chain_id)
+        .await
+        .with_context(|| format!("fetching stores for chain {}", chain_id))?
+        .ok_or_else(|| ApiError::not_found("chain_not_found", format!("no chain with id {}", chain_id)))?;
+    Ok(Json(stores))
+}
+
+async fn route_not_found(uri: axum::http::Uri) -> ApiError {
+    ApiError::not_found("route_not_found", format!("no route for {}", uri.path()))
+}
+
+/// v1 marks every response deprecated and points at its successor.
+async fn mark_deprecated(mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    headers.insert(header::LINK, HeaderValue::from_static("</api/v2>; rel=\"successor-version\""));
+    response
 }
 
-pub fn create_router(db_manager: Arc<DatabaseManager>) -> Router {
-    // /api/stores/nearby is registered before /api/stores/:id/items so Axum
-    // never tries to parse "nearby" as a store ID.
+/// Routes whose shape is the same in every API version. Each version adds its
+/// own compare endpoint on top.
+fn shared_routes() -> Router<AppState> {
+    // Static /stores/nearby takes priority over /stores/:id, so "nearby" is
+    // never parsed as a store ID.
     Router::new()
+        .route("/stores/nearby", get(get_nearby_stores))
+        .route("/stores", get(get_all_stores_handler))
+        .route("/stores/:id", get(get_store_handler))
+        .route("/stores/:id/items", get(get_store_items_handler))
+        .route("/items/search", get(search_items))
+        .route("/items/resolve", get(resolve_term_handler))
+        .route("/items", get(search_items_handler))
+        .route("/categories", get(get_categories_handler))
+        .route("/brands/:id", get(get_brand_handler))
+        .route("/products/:barcode", get(get_product_handler))
+        .route("/chains", get(get_chains_handler))
+        .route("/chains/:chain_id/stores", get(get_chain_stores_handler))
+        .route("/freshness", get(get_freshness_handler))
+        .merge(account_routes())
+        .merge(share_routes())
+}
+
+pub fn create_router(state: AppState) -> Router {
+    let v1 = shared_routes()
+        .route("/compare-prices", post(compare_prices))
+        .layer(middleware::map_response(mark_deprecated));
+    let v2 = shared_routes()
+        .route("/compare-prices", post(compare_prices_v2))
+        .route("/lists/:id/compare", post(compare_saved_list));
+
+    // Unversioned /api/... stays as an alias of v1 for PWA clients whose service
+    // worker cached an app shell from before versioning.
+    let mut router = Router::new()
         .route("/health", get(health_check))
-        .route("/api/stores/nearby", get(get_nearby_stores))
-        .route("/api/stores", get(get_all_stores_handler))
-        .route("/api/stores/:id/items", get(get_store_items_handler))
-        .route("/api/compare-prices", post(compare_prices))
-        .route("/api/items/search", get(search_items))
-        .route("/api/items", get(search_items_handler))
-        .with_state(db_manager)
+        .route("/health/pool", get(pool_stats))
+        .route("/health/details", get(health_details))
+        .route("/ready", get(ready))
+        .nest("/api/v1", v1.clone())
+        .nest("/api/v2", v2)
+        .nest("/api", v1)
+        .merge(docs_router());
+    if let Some(admin) = admin_router(&state) {
+        router = router.nest("/admin", admin);
+    }
+
+    router
+        .route_layer(middleware::from_fn(track_http))
+        .fallback(route_not_found)
+        .layer(middleware::from_fn(request_id))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EstimateMethod, ItemPrice, MissingEstimate, StoreComparison, SubChainRef};
+
+    fn codes(errors: &[FieldError]) -> Vec<&'static str> {
+        errors.iter().map(|e| e.code).collect()
+    }
+
+    #[test]
+    fn grocery_list_bounds() {
+        assert_eq!(codes(&grocery_list_errors(&[])), ["grocery_list_empty"]);
+        let long = vec!["x".to_string(); MAX_GROCERY_LIST_LEN + 1];
+        assert_eq!(codes(&grocery_list_errors(&long)), ["grocery_list_too_long"]);
+        assert!(grocery_list_errors(&["חלב".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn terms_reported_by_index() {
+        let errors = grocery_list_errors(&["ok".to_string(), String::new(), "x".repeat(MAX_TERM_LEN + 1)]);
+        assert_eq!(codes(&errors), ["term_empty", "term_too_long"]);
+        assert_eq!(errors[0].field, "grocery_list[1]");
+        assert_eq!(errors[1].field, "grocery_list[2]");
+    }
+
+    fn entry(barcode: Option<&str>, name: &str, quantity: u32) -> GroceryListEntry {
+        GroceryListEntry { barcode: barcode.map(str::to_string), cluster_id: None, name: name.to_string(), quantity }
+    }
+
+    fn store(id: i32) -> StoreInfo {
+        StoreInfo {
+            id,
+            chain_id: "7290027600007".to_string(),
+            sub_chain_id: 1,
+            store_id: id,
+            store_name: None,
+            address: None,
+            city: None,
+            latitude: None,
+            longitude: None,
+            distance_km: None,
+            last_price_update: None,
+        }
+    }
+
+    fn priced(term_index: usize, price: f64) -> ItemPrice {
+        ItemPrice {
+            item_code: format!("item-{}", term_index),
+            item_name: "x".to_string(),
+            price,
+            unit_of_measure: None,
+            manufacturer_name: None,
+            price_update_date: None,
+            is_stale: false,
+            match_confidence: None,
+            alternatives: vec![],
+            term_index,
+        }
+    }
+
+    fn comparison(id: i32, items: Vec<ItemPrice>) -> StoreComparison {
+        StoreComparison {
+            store: store(id),
+            total_price: items.iter().map(|i| i.price).sum(),
+            items_found: items.len(),
+            items_missing: vec![],
+            items,
+            estimated_total: None,
+            missing_estimates: vec![],
+            substitutions: vec![],
+            score: None,
+        }
+    }
+
+    #[test]
+    fn entry_term_prefers_valid_barcode() {
+        assert_eq!(entry_term(&entry(Some("7290000066769"), "milk", 1)), "7290000066769");
+        assert_eq!(entry_term(&entry(Some("123"), " חלב ", 1)), "חלב");
+        assert_eq!(entry_term(&entry(None, "חלב", 1)), "חלב");
+    }
+
+    #[test]
+    fn baskets_weight_by_quantity_and_rerank() {
+        let entries = vec![entry(None, "a", 3), entry(None, "b", 1)];
+        // Store 1 is cheaper per unit on "b" but "a" (×3) dominates.
+        let response = PriceComparisonResponse {
+            stores: vec![
+                comparison(1, vec![priced(0, 5.0), priced(1, 1.0)]),
+                comparison(2, vec![priced(1, 2.0), priced(0, 4.0)]),
+            ],
+            best_store: None,
+            requested_items: vec![],
+            total_stores: 2,
+            has_more: false,
+        };
+        let ranking = Ranking::from_options(&CompareOptions::default());
+        let v2 = basket_response(&entries, response, &ranking, 1, 10);
+        assert_eq!(v2.best_store_id, Some(2));
+        assert_eq!(v2.stores[0].total_price, 14.0);
+        assert_eq!(v2.stores[0].lines[0].line_total, Some(12.0));
+        assert_eq!(v2.stores[1].total_price, 16.0);
+    }
+
+    fn listing(id: i32, price: f64, distance_km: Option<f64>) -> ProductStorePrice {
+        ProductStorePrice {
+            store: StoreInfo { distance_km, ..store(id) },
+            price,
+            price_update_date: None,
+            is_stale: false,
+        }
+    }
+
+    #[test]
+    fn product_stores_sorted_by_price_or_distance() {
+        let mut stores = vec![listing(1, 9.9, Some(3.0)), listing(2, 7.5, None), listing(3, 9.9, Some(1.0))];
+        sort_product_stores(&mut stores, ProductSort::Price);
+        assert_eq!(stores.iter().map(|s| s.store.id).collect::<Vec<_>>(), [2, 3, 1]);
+        sort_product_stores(&mut stores, ProductSort::Distance);
+        assert_eq!(stores.iter().map(|s| s.store.id).collect::<Vec<_>>(), [3, 1, 2]);
+    }
+
+    #[test]
+    fn product_query_validation() {
+        let q = ProductQuery::default();
+        assert!(product_query_errors("7290000066768", &q).is_empty());
+        assert_eq!(codes(&product_query_errors("72900-abc", &q)), ["invalid_barcode"]);
+
+        let by_distance = ProductQuery { sort: Some(ProductSort::Distance), ..Default::default() };
+        assert_eq!(codes(&product_query_errors("7290000066768", &by_distance)), ["location_required"]);
+        let half = ProductQuery { latitude: Some(32.0), ..Default::default() };
+        assert_eq!(codes(&product_query_errors("7290000066768", &half)), ["incomplete_location"]);
+    }
+
+    #[test]
+    fn estimated_lines_weighted_by_quantity() {
+        let entries = vec![entry(None, "a", 2), entry(None, "b", 3)];
+        let mut partial = comparison(1, vec![priced(0, 5.0)]);
+        partial.estimated_total = Some(9.0);
+        partial.missing_estimates = vec![MissingEstimate {
+            term_index: 1,
+            term: "b".to_string(),
+            estimated_price: 4.0,
+            method: EstimateMethod::Median,
+            store_count: 3,
+        }];
+        let response = PriceComparisonResponse {
+            stores: vec![partial, comparison(2, vec![priced(0, 6.0), priced(1, 5.0)])],
+            best_store: None,
+            requested_items: vec![],
+            total_stores: 2,
+            has_more: false,
+        };
+        let options = CompareOptions {
+            estimate_missing: Some(EstimateMethod::Median),
+            missing_policy: MissingPolicy::EstimatedTotal,
+            ..Default::default()
+        };
+        let v2 = basket_response(&entries, response, &Ranking::from_options(&options), 1, 10);
+        // 10 found + 12 estimated beats a complete 27 basket under estimated_total.
+        assert_eq!(v2.best_store_id, Some(1));
+        assert_eq!(v2.stores[0].total_price, 10.0);
+        assert_eq!(v2.stores[0].estimated_total, Some(22.0));
+        assert_eq!(v2.stores[0].lines[1].estimated_line_total, Some(12.0));
+    }
+
+    #[test]
+    fn effective_cost_needs_location() {
+        let options = CompareOptions { ranking: RankingMode::EffectiveCost, cost_per_km: Some(-1.0), ..Default::default() };
+        assert_eq!(codes(&compare_options_errors(&options)), ["ranking_requires_location", "cost_out_of_range"]);
+        let options = CompareOptions { missing_policy: MissingPolicy::EstimatedTotal, ..Default::default() };
+        assert_eq!(codes(&compare_options_errors(&options)), ["policy_requires_estimates"]);
+        for hours in [0.0, 1e10, f64::NAN] {
+            let options = CompareOptions { max_data_age_hours: Some(hours), ..Default::default() };
+            assert_eq!(codes(&compare_options_errors(&options)), ["max_age_out_of_range"]);
+        }
+    }
+
+    #[test]
+    fn store_filter_validation() {
+        let chain = "7290027600007".to_string();
+        let filter = StoreFilter {
+            include_chains: vec![chain.clone(), "729".to_string()],
+            exclude_chains: vec![chain.clone()],
+            include_sub_chains: vec![SubChainRef { chain_id: chain.clone(), sub_chain_id: 1 }],
+            exclude_store_ids: vec![0],
+            ..Default::default()
+        };
+        let errors = store_filter_errors(&filter);
+        assert_eq!(codes(&errors), ["invalid_chain_id", "invalid_store_id", "conflicting_filter"]);
+        assert_eq!(errors[0].field, "include_chains[1]");
+        assert_eq!(errors[1].field, "exclude_store_ids[0]");
+
+        let too_many = StoreFilter { include_store_ids: (1..=MAX_FILTER_LEN as i32 + 1).collect(), ..Default::default() };
+        assert_eq!(codes(&store_filter_errors(&too_many)), ["filter_too_long"]);
+        assert!(store_filter_errors(&StoreFilter::default()).is_empty());
+    }
+
+    #[test]
+    fn resolve_query_validation() {
+        let query = |q: &str, limit| ResolveQuery { q: q.to_string(), limit };
+        assert_eq!(codes(&resolve_query_errors(&query(" ק ", None))), ["term_too_short"]);
+        assert_eq!(codes(&resolve_query_errors(&query("קוטג'", Some(31)))), ["limit_out_of_range"]);
+        assert!(resolve_query_errors(&query("קוטג'", Some(5))).is_empty());
+    }
+
+    #[test]
+    fn location_fields_prefixed() {
+        let location = LocationQuery { latitude: 95.0, longitude: 34.8, radius_km: Some(500.0) };
+        let errors = location_errors(&location, "user_location.");
+        assert_eq!(codes(&errors), ["radius_out_of_range", "latitude_out_of_range"]);
+        assert_eq!(errors[0].field, "user_location.radius_km");
+    }
 }
@@ -0,0 +1,90 @@
+//! Manufacturer identity across the free-text `manufacturer_name` spellings chains send.
+//!
+//! Every raw spelling is an alias (`manufacturer_aliases`) of one manufacturer. A
+//! spelling seen for the first time is resolved on its key — normalized, punctuation
+//! and company suffixes dropped: an existing manufacturer or alias with the same key
+//! wins, else one whose key it extends or that extends it word-wise ("תנובה מרכז" →
+//! "תנובה"), else it founds a new manufacturer. Cross-script spellings ("Tnuva") are
+//! operator-mapped aliases.
+
+use crate::hebrew;
+
+/// Manufacturer values chains use for "none" (keys).
+const PLACEHOLDERS: [&str; 4] = ["לא ידע", "כללי", "unknown", "-"];
+/// Company-form words that don't tell manufacturers apart (keys).
+const COMPANY_SUFFIXES: [&str; 6] = ["בעמ", "בע", "מ", "ltd", "inc", "co"];
+/// Keys shorter than this only match exactly: too little to extend.
+const MIN_PREFIX_KEY_LEN: usize = 3;
+
+/// The identity key of a manufacturer spelling, or None for blanks and placeholders.
+pub fn manufacturer_key(raw: &str) -> Option<String> {
+    let normalized = hebrew::normalize(raw);
+    if PLACEHOLDERS.contains(&normalized.as_str()) {
+        return None;
+    }
+    let key = normalized
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !COMPANY_SUFFIXES.contains(w))
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!key.is_empty()).then_some(key)
+}
+
+/// Whether `longer` is `shorter` followed by more words.
+fn extends(longer: &str, shorter: &str) -> bool {
+    shorter.chars().count() >= MIN_PREFIX_KEY_LEN
+        && longer.strip_prefix(shorter).is_some_and(|rest| rest.starts_with(' '))
+}
+
+/// The manufacturer a new key belongs to, given known (key, manufacturer ID) pairs:
+/// an exact key match, else the longest known key this one extends, else the
+/// shortest known key extending this one.
+pub fn resolve(key: &str, known: &[(String, i32)]) -> Option<i32> {
+    if let Some((_, id)) = known.iter().find(|(k, _)| k == key) {
+        return Some(*id);
+    }
+    let broader = known
+        .iter()
+        .filter(|(k, _)| extends(key, k))
+        .max_by_key(|(k, id)| (k.len(), std::cmp::Reverse(*id)));
+    let narrower = || {
+        known
+            .iter()
+            .filter(|(k, _)| extends(k, key))
+            .min_by_key(|(k, id)| (k.len(), *id))
+    };
+    broader.or_else(narrower).map(|(_, id)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_drop_punctuation_and_company_forms() {
+        assert_eq!(manufacturer_key("תנובה").as_deref(), Some("תנבה"));
+        assert_eq!(manufacturer_key(" תנובה בע\"מ ").as_deref(), Some("תנבה"));
+        assert_eq!(manufacturer_key("תנובה בע''מ").as_deref(), Some("תנבה"));
+        assert_eq!(manufacturer_key("Strauss Group Ltd.").as_deref(), Some("strauss group"));
+        assert_eq!(manufacturer_key("לא ידוע"), None);
+        assert_eq!(manufacturer_key("-"), None);
+        assert_eq!(manufacturer_key("  "), None);
+    }
+
+    #[test]
+    fn resolution_prefers_exact_then_broader_then_narrower() {
+        let known = vec![
+            ("תנבה".to_string(), 1),
+            ("שטראוס".to_string(), 2),
+            ("שטראוס מחלבות".to_string(), 3),
+            ("יוניליור ישראל".to_string(), 4),
+        ];
+        assert_eq!(resolve("תנבה", &known), Some(1));
+        assert_eq!(resolve("תנבה מרכז", &known), Some(1));
+        assert_eq!(resolve("שטראוס מחלבות צפון", &known), Some(3));
+        assert_eq!(resolve("יוניליור", &known), Some(4));
+        // Word-wise only: "תנבות" isn't "תנבה".
+        assert_eq!(resolve("תנבות", &known), None);
+        assert_eq!(resolve("אסמ", &known), None);
+    }
+}
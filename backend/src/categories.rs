@@ -0,0 +1,144 @@
+//! Rule-based product categorization.
+//!
+//! A keyword rule matches when its words appear, in order and as whole words, in
+//! the normalized item name; a manufacturer rule does the same against the
+//! manufacturer; a barcode prefix rule matches item codes starting with its prefix,
+//! or — written `from-to` — with a prefix in that range. The best matching rule
+//! names the category: highest priority, then keyword over barcode prefix over
+//! manufacturer, then the rule with more words / digits.
+
+use crate::hebrew;
+use crate::models::{CategoryRule, RuleKind};
+
+/// Whole words of a normalized text; punctuation separates words.
+fn words(text: &str) -> Vec<String> {
+    hebrew::normalize(text)
+        .split(|c: char| !c.is_alphanumeric() && c != '%')
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `needle` occurs as a contiguous run of `haystack`.
+fn contains_words(haystack: &[String], needle: &[String]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// A barcode prefix pattern as an inclusive range of equal-length digit prefixes.
+/// None if it isn't `digits` or `digits-digits` with `from <= to`.
+pub fn parse_prefix_range(pattern: &str) -> Option<(&str, &str)> {
+    let (from, to) = pattern.split_once('-').unwrap_or((pattern, pattern));
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (digits(from) && digits(to) && from.len() == to.len() && from <= to).then_some((from, to))
+}
+
+enum Matcher {
+    Name(Vec<String>),
+    Manufacturer(Vec<String>),
+    Prefix { from: String, to: String },
+}
+
+impl Matcher {
+    fn specificity(&self) -> usize {
+        match self {
+            Matcher::Name(words) | Matcher::Manufacturer(words) => words.len(),
+            Matcher::Prefix { from, .. } => from.len(),
+        }
+    }
+}
+
+/// Rules compiled once for classifying many items.
+pub struct Classifier {
+    rules: Vec<(Matcher, CategoryRule)>,
+}
+
+impl Classifier {
+    /// Rules with unusable patterns (no words, malformed ranges) are dropped.
+    pub fn new(rules: Vec<CategoryRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let matcher = match rule.kind {
+                    RuleKind::Keyword => Matcher::Name(words(&rule.pattern)),
+                    RuleKind::Manufacturer => Matcher::Manufacturer(words(&rule.pattern)),
+                    RuleKind::BarcodePrefix => {
+                        let (from, to) = parse_prefix_range(rule.pattern.trim())?;
+                        Matcher::Prefix { from: from.to_string(), to: to.to_string() }
+                    }
+                };
+                (matcher.specificity() > 0).then_some((matcher, rule))
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The category of the best rule matching an item, if any.
+    pub fn classify(&self, item_code: &str, item_name: &str, manufacturer: Option<&str>) -> Option<i32> {
+        let name_words = words(item_name);
+        let manufacturer_words = manufacturer.map(words).unwrap_or_default();
+        self.rules
+            .iter()
+            .filter(|(matcher, _)| match matcher {
+                Matcher::Name(needle) => contains_words(&name_words, needle),
+                Matcher::Manufacturer(needle) => contains_words(&manufacturer_words, needle),
+                Matcher::Prefix { from, to } => item_code
+                    .get(..from.len())
+                    .is_some_and(|prefix| from.as_str() <= prefix && prefix <= to.as_str()),
+            })
+            .max_by_key(|(matcher, rule)| {
+                let kind_rank = match rule.kind {
+                    RuleKind::Keyword => 2,
+                    RuleKind::BarcodePrefix => 1,
+                    RuleKind::Manufacturer => 0,
+                };
+                // Older rules win exact ties.
+                (rule.priority, kind_rank, matcher.specificity(), std::cmp::Reverse(rule.id))
+            })
+            .map(|(_, rule)| rule.category_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i32, category_id: i32, kind: RuleKind, pattern: &str, priority: i32) -> CategoryRule {
+        CategoryRule { id, category_id, kind, pattern: pattern.to_string(), priority }
+    }
+
+    #[test]
+    fn best_rule_wins() {
+        let classifier = Classifier::new(vec![
+            rule(1, 10, RuleKind::Keyword, "חלב", 0),
+            rule(2, 11, RuleKind::Keyword, "משקה", 0),
+            rule(3, 10, RuleKind::Keyword, "משקה חלב", 0),
+            rule(4, 12, RuleKind::Keyword, "שוקולד", 1),
+            rule(5, 13, RuleKind::Manufacturer, "תנובה", 0),
+            rule(6, 14, RuleKind::BarcodePrefix, "7290000066", 0),
+        ]);
+        assert_eq!(classifier.classify("1", "משקה חלב בטעם וניל", None), Some(10));
+        assert_eq!(classifier.classify("1", "שוקולד חלב 100 גרם", None), Some(12));
+        // Whole words only: "חלבה" (halva) isn't "חלב".
+        assert_eq!(classifier.classify("1", "חלבה וניל", None), None);
+        // Keyword over barcode prefix over manufacturer.
+        assert_eq!(classifier.classify("7290000066769", "חלב 3%", Some("תנובה")), Some(10));
+        assert_eq!(classifier.classify("7290000066769", "דני וניל", Some("תנובה")), Some(14));
+        assert_eq!(classifier.classify("7290000000001", "דני וניל", Some("תנובה מרכז")), Some(13));
+        assert_eq!(classifier.classify("7290000000001", "דני וניל", None), None);
+    }
+
+    #[test]
+    fn barcode_prefix_ranges() {
+        assert_eq!(parse_prefix_range("729"), Some(("729", "729")));
+        assert_eq!(parse_prefix_range("20-29"), Some(("20", "29")));
+        assert_eq!(parse_prefix_range("29-20"), None);
+        assert_eq!(parse_prefix_range("20-299"), None);
+        assert_eq!(parse_prefix_range("72a"), None);
+        assert_eq!(parse_prefix_range(""), None);
+
+        let classifier = Classifier::new(vec![rule(1, 3, RuleKind::BarcodePrefix, "20-29", 0)]);
+        assert_eq!(classifier.classify("2512345000006", "עגבניות שרי", None), Some(3));
+        assert_eq!(classifier.classify("3012345000006", "עגבניות שרי", None), None);
+        assert_eq!(classifier.classify("2", "עגבניות שרי", None), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Cross-store identity for items without a catalog barcode — store brands, produce,
+//! weighed goods — whose internal codes differ per chain.
+//!
+//! Items are grouped on their normalized name words (sizes stripped, order ignored)
+//! and pack size, then split by manufacturer; unbranded items join the group's only
+//! brand when there is exactly one. Groups spanning at least two chains become
+//! clusters. A member's confidence reflects how much of its identity was actually
+//! compared: the name always, size and manufacturer when known.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::brands;
+use crate::hebrew;
+use crate::units::{strip_size, Size};
+
+/// Grocery-list terms naming a cluster ("cluster:42") compare like barcodes.
+pub const CLUSTER_TERM_PREFIX: &str = "cluster:";
+
+const BASE_CONFIDENCE: f64 = 0.6;
+const SIZE_CONFIDENCE: f64 = 0.2;
+const MANUFACTURER_CONFIDENCE: f64 = 0.2;
+
+pub fn cluster_term(id: i32) -> String {
+    format!("{}{}", CLUSTER_TERM_PREFIX, id)
+}
+
+/// The cluster ID in a "cluster:<id>" term.
+pub fn parse_cluster_term(term: &str) -> Option<i32> {
+    term.strip_prefix(CLUSTER_TERM_PREFIX)?.parse().ok().filter(|id| *id > 0)
+}
+
+/// One chain's view of a non-catalog item.
+#[derive(Debug, Clone)]
+pub struct ChainItem {
+    pub chain_id: String,
+    pub item_code: String,
+    pub item_name: String,
+    pub manufacturer: Option<String>,
+    pub size: Option<Size>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedMember {
+    pub chain_id: String,
+    pub item_code: String,
+    pub item_name: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterProposal {
+    /// Stable across rebuilds while the identity holds, so cluster IDs are too.
+    pub key: String,
+    pub name: String,
+    pub manufacturer: Option<String>,
+    pub size: Option<Size>,
+    pub members: Vec<ProposedMember>,
+}
+
+fn name_key(name: &str) -> String {
+    let normalized = hebrew::normalize(&strip_size(name));
+    let mut words: Vec<&str> = normalized.split(' ').filter(|w| !w.is_empty()).collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+fn size_key(size: Option<Size>) -> String {
+    size.map(|s| format!("{:.1} {}", s.amount, s.unit.as_str())).unwrap_or_default()
+}
+
+fn manufacturer_key(manufacturer: Option<&str>) -> Option<String> {
+    brands::manufacturer_key(manufacturer?)
+}
+
+/// The most common of `values`, ties going to the longest (least truncated).
+fn most_common<'a>(values: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(a.0.chars().count().cmp(&b.0.chars().count())).then(b.0.cmp(a.0)))
+        .map(|(value, _)| value)
+}
+
+fn proposal(name: &str, size: &str, maker: Option<String>, items: Vec<ChainItem>) -> ClusterProposal {
+    let display_name = most_common(items.iter().map(|i| i.item_name.trim())).unwrap_or_default().to_string();
+    let manufacturer = most_common(
+        items
+            .iter()
+            .filter(|i| maker.is_some() && manufacturer_key(i.manufacturer.as_deref()) == maker)
+            .filter_map(|i| i.manufacturer.as_deref().map(str::trim)),
+    )
+    .map(str::to_string);
+    let cluster_size = items.iter().find_map(|i| i.size);
+    let members = items
+        .into_iter()
+        .map(|item| {
+            let mut confidence = BASE_CONFIDENCE;
+            if item.size.is_some() {
+                confidence += SIZE_CONFIDENCE;
+            }
+            if maker.is_some() && manufacturer_key(item.manufacturer.as_deref()) == maker {
+                confidence += MANUFACTURER_CONFIDENCE;
+            }
+            ProposedMember { chain_id: item.chain_id, item_code: item.item_code, item_name: item.item_name, confidence }
+        })
+        .collect();
+    ClusterProposal {
+        key: format!("{}|{}|{}", maker.unwrap_or_default(), name, size),
+        name: display_name,
+        manufacturer,
+        size: cluster_size,
+        members,
+    }
+}
+
+/// Clusters `items` (distinct per chain and code). Only groups spanning two or more
+/// chains are returned, ordered by key.
+pub fn cluster_items(items: Vec<ChainItem>) -> Vec<ClusterProposal> {
+    let mut groups: HashMap<(String, String), Vec<ChainItem>> = HashMap::new();
+    for item in items {
+        let name = name_key(&item.item_name);
+        if !name.is_empty() {
+            groups.entry((name, size_key(item.size))).or_default().push(item);
+        }
+    }
+
+    let mut proposals = Vec::new();
+    for ((name, size), items) in groups {
+        let mut by_maker: HashMap<Option<String>, Vec<ChainItem>> = HashMap::new();
+        for item in items {
+            by_maker.entry(manufacturer_key(item.manufacturer.as_deref())).or_default().push(item);
+        }
+        if by_maker.len() == 2
+            && let Some(unbranded) = by_maker.remove(&None)
+            && let Some(branded) = by_maker.values_mut().next()
+        {
+            branded.extend(unbranded);
+        }
+        for (maker, items) in by_maker {
+            let chains: HashSet<&str> = items.iter().map(|i| i.chain_id.as_str()).collect();
+            if chains.len() >= 2 {
+                proposals.push(proposal(&name, &size, maker, items));
+            }
+        }
+    }
+    proposals.sort_by(|a, b| a.key.cmp(&b.key));
+    proposals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::normalized_size;
+
+    fn item(chain: &str, code: &str, name: &str, manufacturer: Option<&str>, grams: Option<&str>) -> ChainItem {
+        ChainItem {
+            chain_id: chain.to_string(),
+            item_code: code.to_string(),
+            item_name: name.to_string(),
+            manufacturer: manufacturer.map(str::to_string),
+            size: normalized_size(grams, &[Some("גרם")]),
+        }
+    }
+
+    #[test]
+    fn cluster_terms() {
+        assert_eq!(parse_cluster_term(&cluster_term(42)), Some(42));
+        assert_eq!(parse_cluster_term("cluster:0"), None);
+        assert_eq!(parse_cluster_term("cluster:x"), None);
+        assert_eq!(parse_cluster_term("חלב"), None);
+    }
+
+    #[test]
+    fn store_brands_cluster_across_chains() {
+        let clusters = cluster_items(vec![
+            item("A", "101", "עגבניות שרי 250 גרם", Some("ירקות הגליל"), Some("250")),
+            item("B", "9001", "שרי עגבניות", Some("ירקות הגליל"), Some("250")),
+            item("C", "77", "עגבניות שרי", None, Some("250")),
+            // Same name, different size: its own (single-chain) group.
+            item("C", "78", "עגבניות שרי", None, Some("500")),
+            // Only one chain: not a cluster.
+            item("A", "555", "לחם אחיד", Some("ברמן"), None),
+            item("A", "556", "לחם אחיד", Some("ברמן"), None),
+        ]);
+        assert_eq!(clusters.len(), 1);
+        let cluster = &clusters[0];
+        assert_eq!(cluster.manufacturer.as_deref(), Some("ירקות הגליל"));
+        assert_eq!(cluster.size.map(|s| s.amount), Some(250.0));
+        let mut confidences: Vec<(&str, f64)> =
+            cluster.members.iter().map(|m| (m.item_code.as_str(), m.confidence)).collect();
+        confidences.sort_by(|a, b| a.0.cmp(b.0));
+        // The unbranded member matched on name and size only.
+        assert_eq!(confidences, [("101", 1.0), ("77", 0.8), ("9001", 1.0)]);
+    }
+
+    #[test]
+    fn conflicting_brands_stay_apart() {
+        let clusters = cluster_items(vec![
+            item("A", "1", "חומוס", Some("צבר"), Some("400")),
+            item("B", "2", "חומוס", Some("צבר"), Some("400")),
+            item("A", "3", "חומוס", Some("אחלה"), Some("400")),
+            item("B", "4", "חומוס", Some("אחלה"), Some("400")),
+            item("C", "5", "חומוס", Some("לא ידוע"), Some("400")),
+        ]);
+        // With two brands to pick from, the unbranded item joins neither (and alone spans one chain).
+        assert_eq!(clusters.len(), 2);
+        assert!(clusters.iter().all(|c| c.members.len() == 2 && c.manufacturer.is_some()));
+    }
+}
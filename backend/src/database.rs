@@ -1,7 +1,73 @@
-use sqlx::{PgPool, Executor, Row};
-use crate::models::{PriceComparisonRequest, PriceComparisonResponse, StoreInfo, ItemPrice, StoreComparison, StoreRecord, ProductSearchResult, StoreItemRow};
-use anyhow::Result;
-use std::collections::HashMap;
+use sqlx::{PgPool, Executor, Row, migrate::Migrator};
+use crate::models::{CompareOptions, PriceComparisonRequest, PriceComparisonResponse, StoreInfo, ItemPrice, StoreComparison, StoreRecord, ProductSearchResult, StoreItemRow, PoolStats, PoolStatsResponse, ChainFreshness, StoreFreshness, ProductDetail, ProductStorePrice, PriceStats, StoreDetail, ChainSummary, LedgerEntry, PurgeSummary, UserInfo, GroceryListEntry, SavedList, SavedListInput, SavedListSummary, SharedList, StoreFilter, MissingEstimate, EstimateMethod, Substitute, SubstituteReason, SubstitutionSuggestion, ProductVariant, SearchSynonym, SynonymInput, ClusterMember, ClusterRebuildSummary, MemberStatus, ProductCluster, NameElectionSummary, ProductNameVariant, ProductNames, Category, CategoryRule, CategoryRuleInput, ReclassifySummary, RuleKind, BrandFacet, BrandChainStats, BrandDetail, ManufacturerAlias};
+use crate::units::{normalized_size, size_ratio, BaseUnit, Size};
+use crate::categories::Classifier;
+use crate::clustering::{cluster_items, parse_cluster_term, ChainItem};
+use crate::brands;
+use crate::matching::{select_matches, CatalogHit};
+use crate::naming::{self, NameVariant};
+use crate::ranking::Ranking;
+use crate::hebrew;
+use crate::synonyms::{self, SynonymRule};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use uuid::Uuid;
+use std::{collections::{HashMap, HashSet}, time::Duration};
+
+/// Item-listing filter on the category subtree bound at `$n` (an `int4[]`, NULL for
+/// any category). Expects the `items` table unaliased. One fragment for every count,
+/// page and facet query so they cannot drift apart.
+macro_rules! category_filter_sql {
+    ($n:literal) => {
+        concat!(
+            "($", $n, "::int4[] IS NULL OR EXISTS ( \
+               SELECT 1 FROM item_categories ic JOIN stores cs ON cs.chain_id = ic.chain_id \
+               WHERE cs.id = items.store_pk AND ic.item_code = items.item_code \
+                 AND ic.category_id = ANY($", $n, ")))"
+        )
+    };
+}
+
+/// Item-listing filter on the manufacturer bound at `$n` (an `int4`, NULL for any
+/// brand), through its aliases; same conventions as [`category_filter_sql`].
+macro_rules! brand_filter_sql {
+    ($n:literal) => {
+        concat!(
+            "($", $n, "::int4 IS NULL OR EXISTS ( \
+               SELECT 1 FROM manufacturer_aliases ma \
+               WHERE ma.alias = items.manufacturer_name AND ma.manufacturer_id = $", $n, "))"
+        )
+    };
+}
+
+/// LIKE patterns for one term's synonym variants (`hebrew::like_patterns` of each).
+fn variant_patterns(variants: &[String]) -> Vec<String> {
+    let mut patterns: Vec<String> = Vec::new();
+    for pattern in variants.iter().flat_map(|v| hebrew::like_patterns(v)) {
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+    patterns
+}
+
+/// `variant_patterns` for several terms, flattened, each paired with its term's key —
+/// the normalized term, so repeated spellings of a term count once.
+fn name_term_patterns(term_variants: &[Vec<String>]) -> (Vec<String>, Vec<String>) {
+    let mut patterns = Vec::new();
+    let mut keys = Vec::new();
+    for variants in term_variants {
+        let term_patterns = variant_patterns(variants);
+        keys.extend(std::iter::repeat_n(variants[0].clone(), term_patterns.len()));
+        patterns.extend(term_patterns);
+    }
+    (patterns, keys)
+}
+
+/// Whether a grocery-list term is matched by name — neither a barcode nor a cluster.
+fn is_name_term(term: &str) -> bool {
+    !is_ean13(term) && parse_cluster_term(term).is_none()
+}
 
 /// Returns true if `code` is a valid EAN-13 barcode (13 digits + correct check digit).
 pub fn is_ean13(code: &str) -> bool {
@@ -21,31 +87,317 @@ pub fn is_ean13(code: &str) -> bool {
     check == digits[12]
 }
 
-#[derive(Clone)]
-pub struct DatabaseManager {
-    pub pool: PgPool,
+/// Min / mean / max over the prices listed, or None for an empty list.
+pub fn price_stats(prices: &[ProductStorePrice]) -> Option<PriceStats> {
+    if prices.is_empty() {
+        return None;
+    }
+    let (min, max, sum) = prices.iter().fold((f64::INFINITY, f64::NEG_INFINITY, 0.0), |(lo, hi, sum), p| {
+        (lo.min(p.price), hi.max(p.price), sum + p.price)
+    });
+    Some(PriceStats { min, avg: sum / prices.len() as f64, max, store_count: prices.len() })
 }
 
-impl DatabaseManager {
-    pub async fn new(database_url: &str) -> Result<Self> {
+/// Fewer shared items than this and a store's price-level index is noise.
+const MIN_PRICE_LEVEL_ITEMS: i64 = 20;
+
+/// `100 × store_total / city_total` over the items both sides list, or None when
+/// the overlap is below [`MIN_PRICE_LEVEL_ITEMS`].
+pub fn price_level_index(store_total: f64, city_total: f64, shared_items: i64) -> Option<f64> {
+    relative_index(store_total, city_total, shared_items, MIN_PRICE_LEVEL_ITEMS)
+}
+
+/// A brand's range is small and few of its products are in more than one chain, so
+/// a handful of shared products already says which chain sells it cheaper.
+const MIN_BRAND_PRICE_ITEMS: i64 = 3;
+
+/// `100 × chain_total / cross_total` over a brand's products the chain shares with
+/// other chains, or None when fewer than [`MIN_BRAND_PRICE_ITEMS`] are shared.
+pub fn brand_price_index(chain_total: f64, cross_total: f64, shared_items: i64) -> Option<f64> {
+    relative_index(chain_total, cross_total, shared_items, MIN_BRAND_PRICE_ITEMS)
+}
+
+fn relative_index(total: f64, reference_total: f64, shared_items: i64, min_items: i64) -> Option<f64> {
+    if shared_items < min_items || reference_total <= 0.0 {
+        return None;
+    }
+    Some((total / reference_total * 1000.0).round() / 10.0)
+}
+
+/// Connection pool tuning, read from the environment (ARCHITECTURE.md §3.2).
+///
+/// `DB_MAX_CONNECTIONS` / `DB_MIN_CONNECTIONS` size the API pool,
+/// `DB_ACQUIRE_TIMEOUT_SECS` bounds how long a request waits for a free connection,
+/// and `DB_STATEMENT_TIMEOUT_MS` (0 = off) caps any single API query server-side.
+/// XML ingestion gets its own pool of `DB_INGEST_MAX_CONNECTIONS` (default 2) so a
+/// large import can't starve API queries; its statements run under
+/// `DB_INGEST_STATEMENT_TIMEOUT_MS` (default 0 = off), as batch upserts of a big
+/// file legitimately outlast an API timeout. `DB_INGEST_MAX_CONNECTIONS=0` shares
+/// the API pool, and with it the API statement timeout.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub statement_timeout_ms: u64,
+    /// None shares the API pool.
+    pub ingest_max_connections: Option<u32>,
+    pub ingest_statement_timeout_ms: u64,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            statement_timeout_ms: 30_000,
+            ingest_max_connections: Some(2),
+            ingest_statement_timeout_ms: 0,
+        }
+    }
+}
+
+impl PoolConfig {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+        }
+        let defaults = Self::default();
+        let max_connections = var("DB_MAX_CONNECTIONS").unwrap_or(defaults.max_connections).max(1);
+        Self {
+            max_connections,
+            min_connections: var("DB_MIN_CONNECTIONS")
+                .unwrap_or(defaults.min_connections)
+                .min(max_connections),
+            acquire_timeout: var("DB_ACQUIRE_TIMEOUT_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.acquire_timeout),
+            statement_timeout_ms: var("DB_STATEMENT_TIMEOUT_MS").unwrap_or(defaults.statement_timeout_ms),
+            ingest_max_connections: var::<u32>("DB_INGEST_MAX_CONNECTIONS")
+                .map_or(defaults.ingest_max_connections, |n| (n > 0).then_some(n)),
+            ingest_statement_timeout_ms: var("DB_INGEST_STATEMENT_TIMEOUT_MS")
+                .unwrap_or(defaults.ingest_statement_timeout_ms),
+        }
+    }
+
+    async fn connect(&self, database_url: &str, max_connections: u32, statement_timeout_ms: u64) -> Result<PgPool> {
         // force_custom_plan: PostgreSQL evaluates the actual parameter value (e.g., '%חלב%')
         // each time, allowing the GIN trigram index to be used for LIKE '$1' queries.
         // Without this, after 5 executions PostgreSQL falls back to a generic plan that does
         // a sequential scan instead of using the trigram index.
         let pool = sqlx::postgres::PgPoolOptions::new()
-            .after_connect(|conn, _| Box::pin(async move {
+            .max_connections(max_connections)
+            .min_connections(self.min_connections.min(max_connections))
+            .acquire_timeout(self.acquire_timeout)
+            .after_connect(move |conn, _| Box::pin(async move {
                 conn.execute("SET plan_cache_mode = 'force_custom_plan'").await?;
+                conn.execute(format!("SET statement_timeout = {}", statement_timeout_ms).as_str()).await?;
                 Ok(())
             }))
             .connect(database_url)
             .await?;
+        Ok(pool)
+    }
+}
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+fn pool_stats_of(pool: &PgPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let max_connections = pool.options().get_max_connections();
+    let in_use = size.saturating_sub(idle);
+    PoolStats {
+        size,
+        idle,
+        in_use,
+        max_connections,
+        saturation: in_use as f64 / max_connections.max(1) as f64,
+    }
+}
+
+/// Chains publish price timestamps in Israel local time, stored naive.
+const CHAIN_TIMEZONE: chrono_tz::Tz = chrono_tz::Asia::Jerusalem;
+
+/// `at` on the chains' clock, comparable with stored price timestamps.
+fn chain_local(at: DateTime<Utc>) -> NaiveDateTime {
+    at.with_timezone(&CHAIN_TIMEZONE).naive_local()
+}
+
+pub fn chain_local_now() -> NaiveDateTime {
+    chain_local(Utc::now())
+}
+
+/// `hours` before the chain-local `now`; thresholds too large to represent flag nothing.
+fn stale_cutoff_at(now: NaiveDateTime, hours: f64) -> NaiveDateTime {
+    chrono::TimeDelta::try_seconds((hours * 3600.0) as i64)
+        .and_then(|age| now.checked_sub_signed(age))
+        .unwrap_or(NaiveDateTime::MIN)
+}
+
+/// Effective (page, page_size) for a compare request: 1-based page, 10 per page by default, at most 50.
+pub fn page_bounds(options: &CompareOptions) -> (usize, usize) {
+    (options.page.unwrap_or(1).max(1), options.page_size.unwrap_or(10).clamp(1, 50))
+}
+
+/// (store, term index, barcode) for each barcode term a store lacks, given the term
+/// indexes each store carries.
+fn missing_barcodes(
+    grocery_list: &[String],
+    found: impl IntoIterator<Item = (i32, HashSet<usize>)>,
+) -> Vec<(i32, usize, String)> {
+    found
+        .into_iter()
+        .flat_map(|(store_pk, found)| {
+            grocery_list
+                .iter()
+                .enumerate()
+                .filter(move |(idx, term)| is_ean13(term) && !found.contains(idx))
+                .map(move |(idx, term)| (store_pk, idx, term.clone()))
+        })
+        .collect()
+}
+
+/// Takes one store's substitutes out of `substitutes` as per-term suggestions.
+fn substitution_suggestions(
+    grocery_list: &[String],
+    store_pk: i32,
+    substitutes: &mut HashMap<(i32, usize), Vec<Substitute>>,
+) -> Vec<SubstitutionSuggestion> {
+    grocery_list
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, term)| {
+            let alternatives = substitutes.remove(&(store_pk, idx))?;
+            Some(SubstitutionSuggestion { term_index: idx, term: term.clone(), alternatives, applied: false })
+        })
+        .collect()
+}
+
+/// Name similarity (pg_trgm, 0–1) a substitute needs; same-manufacturer items need less.
+const SUBSTITUTE_MIN_SIMILARITY: f64 = 0.3;
+const SUBSTITUTE_MIN_SIMILARITY_SAME_MANUFACTURER: f64 = 0.15;
+/// Alternatives returned per missing term.
+const MAX_SUBSTITUTES: usize = 3;
+
+/// A possible substitute with its normalized pack size.
+pub type SubstituteCandidate = (Substitute, Option<Size>);
+
+/// Scores and orders substitute candidates for one missing product (`match_score`
+/// holds the raw name similarity on the way in). Sizes outside ½×–2× the original,
+/// or in a different unit, are dropped; unknown sizes are kept without a bonus.
+pub fn rank_substitutes(candidates: Vec<SubstituteCandidate>, original: Option<Size>) -> Vec<Substitute> {
+    let mut ranked: Vec<Substitute> = candidates
+        .into_iter()
+        .filter_map(|(mut sub, size)| {
+            let ratio = match (size, original) {
+                (Some(size), Some(original)) => Some(size_ratio(size, original)?),
+                _ => None,
+            };
+            let size_bonus = match ratio {
+                Some(r) if !(0.5..=2.0).contains(&r) => return None,
+                Some(r) => 0.25 * (1.0 - r.log2().abs()),
+                None => 0.0,
+            };
+            let manufacturer_bonus = if sub.reason == SubstituteReason::SameManufacturer { 0.25 } else { 0.0 };
+            sub.match_score += size_bonus + manufacturer_bonus;
+            sub.size_ratio = ratio;
+            Some(sub)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.match_score.partial_cmp(&a.match_score).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.item.price.partial_cmp(&b.item.price).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    ranked.truncate(MAX_SUBSTITUTES);
+    ranked
+}
+
+/// Brands listed in an item listing's brand facet.
+const MAX_BRAND_FACETS: i64 = 20;
+
+/// Optional filters of the item listings.
+#[derive(Debug, Default)]
+pub struct ItemFilter<'a> {
+    /// Category IDs (`category_subtree`); an item matches any of them.
+    pub categories: Option<&'a [i32]>,
+    /// Manufacturer ID.
+    pub brand: Option<i32>,
+}
+
+/// One page of an item listing: the items, the total, and the brand facet.
+#[derive(Debug)]
+pub struct ItemPage {
+    pub items: Vec<StoreItemRow>,
+    pub total: usize,
+    /// Brands of all matching items, ignoring the brand filter itself. Page 1 only.
+    pub brands: Vec<BrandFacet>,
+}
+
+#[derive(Clone)]
+pub struct DatabaseManager {
+    pub pool: PgPool,
+    /// Dedicated ingestion pool, unless `DB_INGEST_MAX_CONNECTIONS=0`.
+    ingest_pool: Option<PgPool>,
+    /// Prices older than this are flagged `is_stale` in API responses.
+    stale_after_hours: f64,
+    /// Chains whose product names win canonical name elections (`naming::elect`).
+    trusted_name_chains: Vec<String>,
+}
+
+impl DatabaseManager {
+    pub async fn new(database_url: &str, config: &PoolConfig) -> Result<Self> {
+        let pool = config.connect(database_url, config.max_connections, config.statement_timeout_ms).await?;
 
         // All schema management lives in backend/migrations/ (ARCHITECTURE.md §3.1).
         // Statements are IF NOT EXISTS-based so existing databases created by
         // older app versions migrate cleanly.
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        MIGRATOR.run(&pool).await?;
+
+        let ingest_pool = match config.ingest_max_connections {
+            Some(n) => Some(config.connect(database_url, n, config.ingest_statement_timeout_ms).await?),
+            None => None,
+        };
+
+        Ok(Self { pool, ingest_pool, stale_after_hours: 48.0, trusted_name_chains: Vec::new() })
+    }
+
+    pub fn with_stale_after_hours(mut self, hours: f64) -> Self {
+        self.stale_after_hours = hours;
+        self
+    }
+
+    pub fn with_trusted_name_chains(mut self, chain_ids: Vec<String>) -> Self {
+        self.trusted_name_chains = chain_ids;
+        self
+    }
+
+    fn is_stale(&self, price_update_date: Option<NaiveDateTime>) -> bool {
+        price_update_date.is_none_or(|ts| ts < self.stale_cutoff(self.stale_after_hours))
+    }
+
+    fn stale_cutoff(&self, hours: f64) -> NaiveDateTime {
+        stale_cutoff_at(chain_local_now(), hours)
+    }
+
+    /// A manager for the XML ingest path: identical, except its queries run on the
+    /// ingestion pool when one is configured. Never re-runs migrations.
+    pub fn for_ingest(&self) -> Self {
+        Self {
+            pool: self.ingest_pool.clone().unwrap_or_else(|| self.pool.clone()),
+            ingest_pool: self.ingest_pool.clone(),
+            stale_after_hours: self.stale_after_hours,
+            trusted_name_chains: self.trusted_name_chains.clone(),
+        }
+    }
 
-        Ok(Self { pool })
+    /// Current utilization of the API pool and, if separate, the ingestion pool.
+    pub fn pool_stats(&self) -> PoolStatsResponse {
+        PoolStatsResponse {
+            api: pool_stats_of(&self.pool),
+            ingest: self.ingest_pool.as_ref().map(pool_stats_of),
+        }
     }
 
     pub async fn get_nearby_stores(&self, lat: f64, lon: f64, radius_km: f64) -> Result<Vec<StoreInfo>> {
@@ -56,11 +408,15 @@ impl DatabaseManager {
                 COALESCE(s.store_name, cn.display_name) as store_name,
                 s.address, s.city,
                 s.latitude::float8 as latitude, s.longitude::float8 as longitude,
+                f.last_price_update,
                 6371 * acos(cos(radians($1)) * cos(radians(s.latitude))
                     * cos(radians(s.longitude) - radians($2))
                     + sin(radians($1)) * sin(radians(s.latitude))) as distance_km
             FROM stores s
             LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id
+            LEFT JOIN LATERAL (
+                SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id
+            ) f ON true
             WHERE s.latitude IS NOT NULL AND s.longitude IS NOT NULL
               AND 6371 * acos(cos(radians($1)) * cos(radians(s.latitude))
                   * cos(radians(s.longitude) - radians($2))
@@ -83,6 +439,7 @@ impl DatabaseManager {
             latitude: row.get("latitude"),
             longitude: row.get("longitude"),
             distance_km: row.get("distance_km"),
+            last_price_update: row.get("last_price_update"),
         }).collect())
     }
 
@@ -92,9 +449,12 @@ impl DatabaseManager {
             "SELECT s.id, s.chain_id, s.sub_chain_id, s.store_id, \
                     COALESCE(s.store_name, cn.display_name) as store_name, \
                     s.address, s.city, \
-                    s.latitude::float8, s.longitude::float8 \
+                    s.latitude::float8, s.longitude::float8, f.last_price_update \
              FROM stores s \
              LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id \
+             ) f ON true \
              ORDER BY s.city NULLS LAST, store_name NULLS LAST"
         )
         .fetch_all(&self.pool)
@@ -111,124 +471,267 @@ impl DatabaseManager {
             latitude: row.get("latitude"),
             longitude: row.get("longitude"),
             distance_km: None,
+            last_price_update: row.get("last_price_update"),
         }).collect())
     }
 
-    /// Returns paginated items for one store, optionally filtered by name query.
-    /// Uses DISTINCT ON item_code to return the cheapest price per distinct item.
+    /// One store with chain name, registry fields, item count and its price level
+    /// against the rest of its city. None if the ID is unknown.
+    pub async fn get_store_detail(&self, id: i32) -> Result<Option<StoreDetail>> {
+        let Some(row) = sqlx::query(
+            "SELECT s.id, s.chain_id, s.sub_chain_id, s.store_id, \
+                    COALESCE(s.store_name, cn.display_name) as store_name, \
+                    s.address, s.city, \
+                    s.latitude::float8 as latitude, s.longitude::float8 as longitude, \
+                    cn.display_name as chain_name, s.zip_code, s.bikoret_no, \
+                    f.last_price_update, f.item_count \
+             FROM stores s \
+             LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update, COUNT(*) AS item_count \
+                 FROM items WHERE store_pk = s.id \
+             ) f ON true \
+             WHERE s.id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await? else {
+            return Ok(None);
+        };
+
+        // Only barcoded items: internal item codes aren't comparable across chains.
+        let city: Option<String> = row.get("city");
+        let level = sqlx::query(
+            "WITH mine AS ( \
+                 SELECT i.item_code, i.item_price \
+                 FROM items i JOIN products p ON p.barcode = i.item_code \
+                 WHERE i.store_pk = $1 \
+             ), city AS ( \
+                 SELECT i.item_code, AVG(i.item_price) AS avg_price \
+                 FROM items i JOIN stores s ON s.id = i.store_pk \
+                 WHERE s.city = $2 AND s.id <> $1 \
+                   AND i.item_code IN (SELECT item_code FROM mine) \
+                 GROUP BY i.item_code \
+             ) \
+             SELECT COALESCE(SUM(m.item_price), 0)::float8 AS store_total, \
+                    COALESCE(SUM(c.avg_price), 0)::float8 AS city_total, \
+                    COUNT(*) AS shared_items \
+             FROM mine m JOIN city c USING (item_code)"
+        )
+        .bind(id)
+        .bind(&city)
+        .fetch_one(&self.pool)
+        .await?;
+        let shared_items: i64 = level.get("shared_items");
+
+        Ok(Some(StoreDetail {
+            store: StoreInfo {
+                id: row.get("id"),
+                chain_id: row.get("chain_id"),
+                sub_chain_id: row.get("sub_chain_id"),
+                store_id: row.get("store_id"),
+                store_name: row.get("store_name"),
+                address: row.get("address"),
+                city,
+                latitude: row.get("latitude"),
+                longitude: row.get("longitude"),
+                distance_km: None,
+                last_price_update: row.get("last_price_update"),
+            },
+            chain_name: row.get("chain_name"),
+            zip_code: row.get("zip_code"),
+            bikoret_no: row.get("bikoret_no"),
+            item_count: row.get("item_count"),
+            price_level_index: price_level_index(level.get("store_total"), level.get("city_total"), shared_items),
+            price_level_items: shared_items,
+        }))
+    }
+
+    /// Returns paginated items for one store, optionally filtered by name query,
+    /// categories and brand, plus the brand facet. Uses DISTINCT ON item_code to
+    /// return the cheapest price per distinct item.
     pub async fn get_store_items(
         &self,
         store_id: i32,
         query: Option<&str>,
+        filter: &ItemFilter<'_>,
         page: usize,
         limit: usize,
-    ) -> Result<(Vec<StoreItemRow>, usize)> {
+    ) -> Result<ItemPage> {
         let offset = ((page.saturating_sub(1)) * limit) as i64;
-        let pattern = match query {
-            Some(q) if !q.is_empty() => format!("%{}%", q.to_lowercase()),
-            _ => "%".to_string(),
-        };
+        let patterns = hebrew::like_patterns(query.unwrap_or_default());
 
         let total: i64 = sqlx::query_scalar(
-            "SELECT COUNT(DISTINCT item_code) FROM items \
-             WHERE store_pk = $1 AND LOWER(item_name) LIKE $2"
+            concat!("SELECT COUNT(DISTINCT item_code) FROM items \
+             WHERE store_pk = $1 AND item_name_normalized LIKE ANY($2) \
+               AND ", category_filter_sql!(3), " \
+               AND ", brand_filter_sql!(4))
         )
         .bind(store_id)
-        .bind(&pattern)
+        .bind(&patterns)
+        .bind(filter.categories)
+        .bind(filter.brand)
         .fetch_one(&self.pool)
         .await?;
 
         let rows = sqlx::query(
-            "SELECT DISTINCT ON (item_code) \
+            concat!("SELECT DISTINCT ON (item_code) \
                     item_code, item_name, manufacturer_name, \
-                    item_price::float8 as item_price, unit_of_measure, quantity \
+                    item_price::float8 as item_price, unit_of_measure, quantity, \
+                    price_update_date \
              FROM items \
-             WHERE store_pk = $1 AND LOWER(item_name) LIKE $2 \
+             WHERE store_pk = $1 AND item_name_normalized LIKE ANY($2) \
+               AND ", category_filter_sql!(3), " \
+               AND ", brand_filter_sql!(4), " \
              ORDER BY item_code, item_price ASC \
-             LIMIT $3 OFFSET $4"
+             LIMIT $5 OFFSET $6")
         )
         .bind(store_id)
-        .bind(&pattern)
+        .bind(&patterns)
+        .bind(filter.categories)
+        .bind(filter.brand)
         .bind(limit as i64)
         .bind(offset)
         .fetch_all(&self.pool)
         .await?;
 
-        let items = rows.into_iter().map(|row| StoreItemRow {
-            item_code: row.get("item_code"),
-            item_name: row.get("item_name"),
-            manufacturer_name: row.get("manufacturer_name"),
-            item_price: row.get("item_price"),
-            unit_of_measure: row.get("unit_of_measure"),
-            quantity: row.get("quantity"),
-        }).collect();
+        // Infinite scroll keeps page 1's facet; later pages skip the aggregate.
+        let brands = if page > 1 {
+            Vec::new()
+        } else {
+            sqlx::query(
+                concat!("SELECT m.id, m.name, COUNT(DISTINCT items.item_code) AS item_count \
+                 FROM items \
+                 JOIN manufacturer_aliases ma ON ma.alias = items.manufacturer_name \
+                 JOIN manufacturers m ON m.id = ma.manufacturer_id \
+                 WHERE items.store_pk = $1 AND items.item_name_normalized LIKE ANY($2) \
+                   AND ", category_filter_sql!(3), " \
+                 GROUP BY m.id, m.name \
+                 ORDER BY item_count DESC, m.name \
+                 LIMIT $4")
+            )
+            .bind(store_id)
+            .bind(&patterns)
+            .bind(filter.categories)
+            .bind(MAX_BRAND_FACETS)
+            .fetch_all(&self.pool)
+            .await?
+        };
 
-        Ok((items, total as usize))
+        Ok(ItemPage {
+            items: rows.into_iter().map(|row| self.store_item_from_row(row)).collect(),
+            total: total as usize,
+            brands: brands.into_iter().map(brand_facet_from_row).collect(),
+        })
     }
 
     /// Returns paginated items across all stores, DISTINCT by item name (cheapest price per name).
-    /// Supports optional query string and price range filters.
+    /// Supports optional query string, price range, category and brand filters, and
+    /// returns the brand facet.
     pub async fn search_items_paginated(
         &self,
         query: &str,
         min_price: Option<f64>,
         max_price: Option<f64>,
+        filter: &ItemFilter<'_>,
         page: usize,
         limit: usize,
-    ) -> Result<(Vec<StoreItemRow>, usize)> {
+    ) -> Result<ItemPage> {
         let offset = ((page.saturating_sub(1)) * limit) as i64;
-        let pattern = if query.is_empty() {
-            "%".to_string()
-        } else {
-            format!("%{}%", query.to_lowercase())
-        };
+        let patterns = self.search_patterns(query).await?;
 
         let total: i64 = sqlx::query_scalar(
-            "SELECT COUNT(DISTINCT LOWER(item_name)) FROM items \
-             WHERE LOWER(item_name) LIKE $1 \
+            concat!("SELECT COUNT(DISTINCT LOWER(item_name)) FROM items \
+             WHERE item_name_normalized LIKE ANY($1) \
                AND ($2::float8 IS NULL OR item_price::float8 >= $2) \
-               AND ($3::float8 IS NULL OR item_price::float8 <= $3)"
+               AND ($3::float8 IS NULL OR item_price::float8 <= $3) \
+               AND ", category_filter_sql!(4), " \
+               AND ", brand_filter_sql!(5))
         )
-        .bind(&pattern)
+        .bind(&patterns)
         .bind(min_price)
         .bind(max_price)
+        .bind(filter.categories)
+        .bind(filter.brand)
         .fetch_one(&self.pool)
         .await?;
 
         let rows = sqlx::query(
-            "SELECT DISTINCT ON (LOWER(item_name)) \
+            concat!("SELECT DISTINCT ON (LOWER(item_name)) \
                     item_code, item_name, manufacturer_name, \
-                    item_price::float8 as item_price, unit_of_measure, quantity \
+                    item_price::float8 as item_price, unit_of_measure, quantity, \
+                    price_update_date \
              FROM items \
-             WHERE LOWER(item_name) LIKE $1 \
+             WHERE item_name_normalized LIKE ANY($1) \
                AND ($2::float8 IS NULL OR item_price::float8 >= $2) \
                AND ($3::float8 IS NULL OR item_price::float8 <= $3) \
+               AND ", category_filter_sql!(4), " \
+               AND ", brand_filter_sql!(5), " \
              ORDER BY LOWER(item_name), item_price ASC \
-             LIMIT $4 OFFSET $5"
+             LIMIT $6 OFFSET $7")
         )
-        .bind(&pattern)
+        .bind(&patterns)
         .bind(min_price)
         .bind(max_price)
+        .bind(filter.categories)
+        .bind(filter.brand)
         .bind(limit as i64)
         .bind(offset)
         .fetch_all(&self.pool)
         .await?;
 
-        let items = rows.into_iter().map(|row| StoreItemRow {
+        let brands = if page > 1 {
+            Vec::new()
+        } else {
+            sqlx::query(
+                concat!("SELECT m.id, m.name, COUNT(DISTINCT LOWER(items.item_name)) AS item_count \
+                 FROM items \
+                 JOIN manufacturer_aliases ma ON ma.alias = items.manufacturer_name \
+                 JOIN manufacturers m ON m.id = ma.manufacturer_id \
+                 WHERE items.item_name_normalized LIKE ANY($1) \
+                   AND ($2::float8 IS NULL OR items.item_price::float8 >= $2) \
+                   AND ($3::float8 IS NULL OR items.item_price::float8 <= $3) \
+                   AND ", category_filter_sql!(4), " \
+                 GROUP BY m.id, m.name \
+                 ORDER BY item_count DESC, m.name \
+                 LIMIT $5")
+            )
+            .bind(&patterns)
+            .bind(min_price)
+            .bind(max_price)
+            .bind(filter.categories)
+            .bind(MAX_BRAND_FACETS)
+            .fetch_all(&self.pool)
+            .await?
+        };
+
+        Ok(ItemPage {
+            items: rows.into_iter().map(|row| self.store_item_from_row(row)).collect(),
+            total: total as usize,
+            brands: brands.into_iter().map(brand_facet_from_row).collect(),
+        })
+    }
+
+    fn store_item_from_row(&self, row: sqlx::postgres::PgRow) -> StoreItemRow {
+        StoreItemRow {
             item_code: row.get("item_code"),
             item_name: row.get("item_name"),
             manufacturer_name: row.get("manufacturer_name"),
             item_price: row.get("item_price"),
             unit_of_measure: row.get("unit_of_measure"),
             quantity: row.get("quantity"),
-        }).collect();
-
-        Ok((items, total as usize))
+            price_update_date: row.get("price_update_date"),
+            is_stale: self.is_stale(row.get("price_update_date")),
+        }
     }
 
-    /// Returns stores that carry at least one of the requested items, ordered by how many
-    /// list terms they cover (then store id). `candidate_ids` optionally restricts the
-    /// search to a pre-filtered set (nearby / city stores).
+    /// Returns every store that carries at least one of the requested items, ordered by
+    /// how many list terms they cover (then store id). `candidate_ids` optionally restricts
+    /// the search to a pre-filtered set (nearby / city stores). Unpaged: the caller ranks
+    /// the whole set on price before slicing a page.
+    ///
+    /// `filter` (chain / sub-chain / store include and exclude lists) is applied on top
+    /// of `candidate_ids`.
     ///
     /// One set-based query computes coverage for the whole grocery list: barcodes match
     /// `item_code = ANY(..)`, name terms match via `unnest`-joined LIKE patterns — no
@@ -237,23 +740,21 @@ impl DatabaseManager {
         &self,
         grocery_list: &[String],
         candidate_ids: Option<&[i32]>,
-        page: usize,
-        page_size: usize,
-    ) -> Result<(Vec<StoreInfo>, usize)> {
+        filter: &StoreFilter,
+    ) -> Result<Vec<StoreInfo>> {
         if grocery_list.is_empty() {
-            return Ok((vec![], 0));
+            return Ok(vec![]);
         }
+        let scoped = self.scoped_store_ids(candidate_ids, filter).await?;
+        let candidate_ids = scoped.as_deref();
         if matches!(candidate_ids, Some(ids) if ids.is_empty()) {
-            return Ok((vec![], 0));
+            return Ok(vec![]);
         }
 
-        let (barcodes, name_terms): (Vec<&String>, Vec<&String>) = grocery_list
-            .iter()
-            .partition(|s| is_ean13(s));
-        let barcode_vals: Vec<String> = barcodes.iter().map(|s| s.to_string()).collect();
-        let patterns: Vec<String> = name_terms.iter()
-            .map(|t| format!("%{}%", t.to_lowercase()))
-            .collect();
+        let barcode_vals: Vec<String> = grocery_list.iter().filter(|s| is_ean13(s)).cloned().collect();
+        let cluster_ids: Vec<i32> = grocery_list.iter().filter_map(|s| parse_cluster_term(s)).collect();
+        let name_terms: Vec<&String> = grocery_list.iter().filter(|s| is_name_term(s)).collect();
+        let (patterns, term_keys) = name_term_patterns(&self.term_variants(&name_terms).await?);
         let candidates: Option<Vec<i32>> = candidate_ids.map(|ids| ids.to_vec());
 
         let rows = sqlx::query(
@@ -263,64 +764,51 @@ impl DatabaseManager {
                 WHERE item_code = ANY($1) \
                   AND ($3::int4[] IS NULL OR store_pk = ANY($3)) \
                 UNION \
-                SELECT DISTINCT i.store_pk, 'n:' || p.pattern AS term_key \
+                SELECT DISTINCT i.store_pk, 'n:' || p.term_key AS term_key \
                 FROM items i \
-                JOIN unnest($2::text[]) AS p(pattern) \
-                  ON LOWER(i.item_name) LIKE p.pattern \
+                JOIN unnest($2::text[], $4::text[]) AS p(pattern, term_key) \
+                  ON i.item_name_normalized LIKE p.pattern \
                 WHERE ($3::int4[] IS NULL OR i.store_pk = ANY($3)) \
+                UNION \
+                SELECT DISTINCT i.store_pk, 'c:' || m.cluster_id AS term_key \
+                FROM product_cluster_members m \
+                JOIN stores s ON s.chain_id = m.chain_id \
+                JOIN items i ON i.store_pk = s.id AND i.item_code = m.item_code \
+                WHERE m.cluster_id = ANY($5) AND m.status <> 'rejected' \
+                  AND ($3::int4[] IS NULL OR i.store_pk = ANY($3)) \
              ) \
-             SELECT store_pk, COUNT(*) AS coverage, COUNT(*) OVER () AS total_stores \
+             SELECT store_pk, COUNT(*) AS coverage \
              FROM matched \
              GROUP BY store_pk \
-             ORDER BY coverage DESC, store_pk ASC \
-             LIMIT $4 OFFSET $5"
+             ORDER BY coverage DESC, store_pk ASC"
         )
         .bind(&barcode_vals)
         .bind(&patterns)
         .bind(&candidates)
-        .bind(page_size as i64)
-        .bind(((page - 1) * page_size) as i64)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let total = rows.first()
-            .map(|r| r.get::<i64, _>("total_stores") as usize)
-            .unwrap_or(0);
-        let page_ids: Vec<i32> = rows.iter().map(|r| r.get("store_pk")).collect();
-
-        if page_ids.is_empty() {
-            // Past the last page: recount so has_more stays correct.
-            let total: i64 = sqlx::query_scalar(
-                "WITH matched AS ( \
-                    SELECT DISTINCT store_pk FROM items \
-                    WHERE item_code = ANY($1) \
-                      AND ($3::int4[] IS NULL OR store_pk = ANY($3)) \
-                    UNION \
-                    SELECT DISTINCT i.store_pk \
-                    FROM items i \
-                    JOIN unnest($2::text[]) AS p(pattern) \
-                      ON LOWER(i.item_name) LIKE p.pattern \
-                    WHERE ($3::int4[] IS NULL OR i.store_pk = ANY($3)) \
-                 ) SELECT COUNT(*) FROM matched"
-            )
-            .bind(&barcode_vals)
-            .bind(&patterns)
-            .bind(&candidates)
-            .fetch_one(&self.pool)
-            .await?;
-            return Ok((vec![], total as usize));
+        .bind(&term_keys)
+        .bind(&cluster_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let store_ids: Vec<i32> = rows.iter().map(|r| r.get("store_pk")).collect();
+        if store_ids.is_empty() {
+            return Ok(vec![]);
         }
 
         let rows = sqlx::query(
             "SELECT s.id, s.chain_id, s.sub_chain_id, s.store_id, \
                     COALESCE(s.store_name, cn.display_name) as store_name, \
                     s.address, s.city, \
-                    s.latitude::float8 as latitude, s.longitude::float8 as longitude \
+                    s.latitude::float8 as latitude, s.longitude::float8 as longitude, \
+                    f.last_price_update \
              FROM stores s \
              LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id \
+             ) f ON true \
              WHERE s.id = ANY($1)"
         )
-        .bind(&page_ids)
+        .bind(&store_ids)
         .fetch_all(&self.pool)
         .await?;
 
@@ -337,31 +825,152 @@ impl DatabaseManager {
                 latitude: row.get("latitude"),
                 longitude: row.get("longitude"),
                 distance_km: None,
+                last_price_update: row.get("last_price_update"),
             })
         }).collect();
 
-        let stores: Vec<StoreInfo> = page_ids.iter()
-            .filter_map(|id| id_to_store.remove(id))
+        Ok(store_ids.iter().filter_map(|id| id_to_store.remove(id)).collect())
+    }
+
+    /// `candidate_ids` narrowed by `filter`; None when neither restricts anything.
+    async fn scoped_store_ids(&self, candidate_ids: Option<&[i32]>, filter: &StoreFilter) -> Result<Option<Vec<i32>>> {
+        if filter.is_empty() {
+            return Ok(candidate_ids.map(<[i32]>::to_vec));
+        }
+        let allowed = self.filtered_store_ids(filter).await?;
+        Ok(Some(match candidate_ids {
+            Some(ids) => ids.iter().copied().filter(|id| allowed.contains(id)).collect(),
+            None => allowed.into_iter().collect(),
+        }))
+    }
+
+    /// Per term index: (median, cheapest, store count) of the per-store prices among
+    /// `candidate_ids` (all stores when None). A store's price for a name term is its
+    /// cheapest containing item — rougher than the scored pick `find_items_for_stores`
+    /// makes, which is fine for a fill-in. Terms no store carries are absent.
+    async fn term_price_estimates(
+        &self,
+        grocery_list: &[String],
+        candidate_ids: Option<&[i32]>,
+    ) -> Result<HashMap<usize, (f64, f64, usize)>> {
+        let barcode_vals: Vec<String> = grocery_list.iter().filter(|t| is_ean13(t)).cloned().collect();
+        let cluster_ids: Vec<i32> = grocery_list.iter().filter_map(|t| parse_cluster_term(t)).collect();
+        let name_terms: Vec<&String> = grocery_list.iter().filter(|t| is_name_term(t)).collect();
+        let (patterns, pattern_keys) = name_term_patterns(&self.term_variants(&name_terms).await?);
+        let term_keys: Vec<String> = grocery_list
+            .iter()
+            .map(|term| match (is_ean13(term), parse_cluster_term(term)) {
+                (true, _) => format!("b:{}", term),
+                (false, Some(cluster_id)) => format!("c:{}", cluster_id),
+                (false, None) => format!("n:{}", hebrew::normalize(term)),
+            })
+            .collect();
+
+        let rows = sqlx::query(
+            "WITH per_store AS ( \
+                SELECT 'b:' || item_code AS term_key, store_pk, MIN(item_price) AS price \
+                FROM items \
+                WHERE item_code = ANY($1) \
+                  AND ($3::int4[] IS NULL OR store_pk = ANY($3)) \
+                GROUP BY 1, 2 \
+                UNION ALL \
+                SELECT 'n:' || p.term_key, i.store_pk, MIN(i.item_price) \
+                FROM items i \
+                JOIN unnest($2::text[], $4::text[]) AS p(pattern, term_key) \
+                  ON i.item_name_normalized LIKE p.pattern \
+                WHERE ($3::int4[] IS NULL OR i.store_pk = ANY($3)) \
+                GROUP BY 1, 2 \
+                UNION ALL \
+                SELECT 'c:' || m.cluster_id, i.store_pk, MIN(i.item_price) \
+                FROM product_cluster_members m \
+                JOIN stores s ON s.chain_id = m.chain_id \
+                JOIN items i ON i.store_pk = s.id AND i.item_code = m.item_code \
+                WHERE m.cluster_id = ANY($5) AND m.status <> 'rejected' \
+                  AND ($3::int4[] IS NULL OR i.store_pk = ANY($3)) \
+                GROUP BY 1, 2 \
+             ) \
+             SELECT term_key, \
+                    percentile_cont(0.5) WITHIN GROUP (ORDER BY price)::float8 AS median, \
+                    MIN(price)::float8 AS cheapest, \
+                    COUNT(*) AS store_count \
+             FROM per_store GROUP BY term_key"
+        )
+        .bind(&barcode_vals)
+        .bind(&patterns)
+        .bind(candidate_ids)
+        .bind(&pattern_keys)
+        .bind(&cluster_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let by_key: HashMap<String, (f64, f64, usize)> = rows
+            .into_iter()
+            .map(|row| {
+                let count: i64 = row.get("store_count");
+                (row.get("term_key"), (row.get("median"), row.get("cheapest"), count as usize))
+            })
             .collect();
+        Ok(term_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, key)| Some((idx, *by_key.get(key)?)))
+            .collect())
+    }
 
-        Ok((stores, total))
+    /// IDs of the stores `filter` lets through. An empty include list doesn't restrict;
+    /// sub-chains match on (chain_id, sub_chain_id) pairs.
+    async fn filtered_store_ids(&self, filter: &StoreFilter) -> Result<HashSet<i32>> {
+        let (include_sub_chain_ids, include_sub_ids): (Vec<String>, Vec<i32>) = filter
+            .include_sub_chains
+            .iter()
+            .map(|s| (s.chain_id.clone(), s.sub_chain_id))
+            .unzip();
+        let (exclude_sub_chain_ids, exclude_sub_ids): (Vec<String>, Vec<i32>) = filter
+            .exclude_sub_chains
+            .iter()
+            .map(|s| (s.chain_id.clone(), s.sub_chain_id))
+            .unzip();
+
+        let ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT s.id FROM stores s \
+             WHERE (cardinality($1::text[]) = 0 OR s.chain_id = ANY($1)) \
+               AND NOT (s.chain_id = ANY($2::text[])) \
+               AND (cardinality($3::text[]) = 0 OR EXISTS ( \
+                   SELECT 1 FROM unnest($3::text[], $4::int4[]) AS f(chain_id, sub_chain_id) \
+                   WHERE f.chain_id = s.chain_id AND f.sub_chain_id = s.sub_chain_id)) \
+               AND NOT EXISTS ( \
+                   SELECT 1 FROM unnest($5::text[], $6::int4[]) AS f(chain_id, sub_chain_id) \
+                   WHERE f.chain_id = s.chain_id AND f.sub_chain_id = s.sub_chain_id) \
+               AND (cardinality($7::int4[]) = 0 OR s.id = ANY($7)) \
+               AND NOT (s.id = ANY($8::int4[]))"
+        )
+        .bind(&filter.include_chains)
+        .bind(&filter.exclude_chains)
+        .bind(&include_sub_chain_ids)
+        .bind(&include_sub_ids)
+        .bind(&exclude_sub_chain_ids)
+        .bind(&exclude_sub_ids)
+        .bind(&filter.include_store_ids)
+        .bind(&filter.exclude_store_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids.into_iter().collect())
     }
 
     // Returns stores that carry at least one of the requested items, ordered by coverage.
     pub async fn get_stores_with_items(
         &self,
         grocery_list: &[String],
-        page: usize,
-        page_size: usize,
-    ) -> Result<(Vec<StoreInfo>, usize)> {
-        self.rank_stores_by_coverage(grocery_list, None, page, page_size).await
+        filter: &StoreFilter,
+    ) -> Result<Vec<StoreInfo>> {
+        self.rank_stores_by_coverage(grocery_list, None, filter).await
     }
 
     /// Search for items matching `query`. Returns results with a barcode when the item is a
     /// known EAN-13 product (so the frontend can send the barcode for exact comparison),
     /// or None for store-brand / non-standard items (fallback to name matching).
     pub async fn search_item_names(&self, query: &str, limit: i64) -> Result<Vec<ProductSearchResult>> {
-        let pattern = format!("%{}%", query.to_lowercase());
+        let patterns = self.search_patterns(query).await?;
         // Join items with products: barcode items get their barcode, others get NULL.
         // DISTINCT ON ensures each unique item name appears only once.
         let rows = sqlx::query(
@@ -371,12 +980,12 @@ impl DatabaseManager {
                 LOWER(i.item_name) as name
             FROM items i
             LEFT JOIN products p ON i.item_code = p.barcode
-            WHERE LOWER(i.item_name) LIKE $1
+            WHERE i.item_name_normalized LIKE ANY($1)
             ORDER BY LOWER(i.item_name), p.barcode NULLS LAST
             LIMIT $2
             "#,
         )
-        .bind(&pattern)
+        .bind(&patterns)
         .bind(limit)
         .fetch_all(&self.pool)
         .await?;
@@ -387,9 +996,128 @@ impl DatabaseManager {
         }).collect())
     }
 
-    /// Batch-fetch the cheapest matching item per grocery list term, per store.
+    /// Catalog products whose name contains `term` and that some store currently lists,
+    /// as (variant, manufacturer, name similarity), most similar first, at most `limit`.
+    pub async fn resolve_term_candidates(
+        &self,
+        term: &str,
+        limit: i64,
+    ) -> Result<Vec<CatalogHit>> {
+        let normalized = hebrew::normalize(term);
+        let rows = sqlx::query(
+            "SELECT p.barcode, p.canonical_name, p.manufacturer, p.quantity, p.unit_of_measure, \
+                    MIN(i.item_price)::float8 AS min_price, MAX(i.item_price)::float8 AS max_price, \
+                    COUNT(DISTINCT i.store_pk) AS store_count, \
+                    similarity(p.canonical_name_normalized, $1)::float8 AS name_similarity \
+             FROM products p \
+             JOIN items i ON i.item_code = p.barcode \
+             WHERE p.canonical_name_normalized LIKE ANY($2) \
+             GROUP BY p.barcode \
+             ORDER BY name_similarity DESC, store_count DESC \
+             LIMIT $3"
+        )
+        .bind(&normalized)
+        .bind(hebrew::like_patterns(term))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| {
+            let quantity: Option<String> = row.get("quantity");
+            let unit_of_measure: Option<String> = row.get("unit_of_measure");
+            let size = normalized_size(quantity.as_deref(), &[unit_of_measure.as_deref()]);
+            let store_count: i64 = row.get("store_count");
+            let variant = ProductVariant {
+                barcode: row.get("barcode"),
+                name: row.get("canonical_name"),
+                quantity,
+                unit_of_measure,
+                size_amount: size.map(|s| s.amount),
+                size_unit: size.map(|s| s.unit),
+                min_price: row.get("min_price"),
+                max_price: row.get("max_price"),
+                store_count: store_count as usize,
+            };
+            (variant, row.get("manufacturer"), row.get("name_similarity"))
+        }).collect())
+    }
+
+    /// Catalog entry for one barcode, with every store currently listing it (unsorted).
+    /// `origin` (lat, lon) fills in each store's `distance_km`. None if the barcode
+    /// isn't in the catalog.
+    pub async fn get_product(&self, barcode: &str, origin: Option<(f64, f64)>) -> Result<Option<ProductDetail>> {
+        let Some(row) = sqlx::query(
+            "SELECT barcode, canonical_name, manufacturer, quantity, unit_of_measure, first_seen_at \
+             FROM products WHERE barcode = $1"
+        )
+        .bind(barcode)
+        .fetch_optional(&self.pool)
+        .await? else {
+            return Ok(None);
+        };
+
+        let (lat, lon) = origin.unzip();
+        let rows = sqlx::query(
+            "SELECT s.id, s.chain_id, s.sub_chain_id, s.store_id, \
+                    COALESCE(s.store_name, cn.display_name) as store_name, \
+                    s.address, s.city, \
+                    s.latitude::float8 as latitude, s.longitude::float8 as longitude, \
+                    f.last_price_update, \
+                    i.item_price::float8 as price, i.price_update_date, \
+                    CASE WHEN $2::float8 IS NULL OR s.latitude IS NULL OR s.longitude IS NULL THEN NULL \
+                         ELSE 6371 * acos(LEAST(1.0, cos(radians($2)) * cos(radians(s.latitude)) \
+                              * cos(radians(s.longitude) - radians($3)) \
+                              + sin(radians($2)) * sin(radians(s.latitude)))) \
+                    END as distance_km \
+             FROM items i \
+             JOIN stores s ON s.id = i.store_pk \
+             LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id \
+             ) f ON true \
+             WHERE i.item_code = $1"
+        )
+        .bind(barcode)
+        .bind(lat)
+        .bind(lon)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let stores: Vec<ProductStorePrice> = rows.into_iter().map(|row| ProductStorePrice {
+            store: StoreInfo {
+                id: row.get("id"),
+                chain_id: row.get("chain_id"),
+                sub_chain_id: row.get("sub_chain_id"),
+                store_id: row.get("store_id"),
+                store_name: row.get("store_name"),
+                address: row.get("address"),
+                city: row.get("city"),
+                latitude: row.get("latitude"),
+                longitude: row.get("longitude"),
+                distance_km: row.get("distance_km"),
+                last_price_update: row.get("last_price_update"),
+            },
+            price: row.get("price"),
+            price_update_date: row.get("price_update_date"),
+            is_stale: self.is_stale(row.get("price_update_date")),
+        }).collect();
+
+        Ok(Some(ProductDetail {
+            barcode: row.get("barcode"),
+            canonical_name: row.get("canonical_name"),
+            manufacturer: row.get("manufacturer"),
+            quantity: row.get("quantity"),
+            unit_of_measure: row.get("unit_of_measure"),
+            first_seen_at: row.get("first_seen_at"),
+            price_stats: price_stats(&stores),
+            stores,
+        }))
+    }
+
+    /// Batch-fetch the matching item per grocery list term, per store.
     /// Returns map of store_pk -> (term_index -> ItemPrice).
-    /// Barcodes use exact item_code lookup; name terms use LIKE.
+    /// Barcodes use exact item_code lookup (cheapest), as do clusters over their members'
+    /// codes; name terms use LIKE, scored.
     pub async fn find_items_for_stores(
         &self,
         store_ids: &[i32],
@@ -405,10 +1133,16 @@ impl DatabaseManager {
             .filter(|(_, s)| is_ean13(s))
             .collect();
 
+        let clusters: Vec<(usize, i32)> = grocery_list
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, s)| Some((idx, parse_cluster_term(s)?)))
+            .collect();
+
         let name_terms: Vec<(usize, &String)> = grocery_list
             .iter()
             .enumerate()
-            .filter(|(_, s)| !is_ean13(s))
+            .filter(|(_, s)| is_name_term(s))
             .collect();
 
         // by_store[store_pk][term_index] = ItemPrice
@@ -419,7 +1153,8 @@ impl DatabaseManager {
             let barcode_vals: Vec<String> = barcodes.iter().map(|(_, s)| s.to_string()).collect();
             let rows = sqlx::query(
                 "SELECT store_pk, item_code, item_name, \
-                 MIN(item_price)::float8 as price, unit_of_measure, manufacturer_name \
+                 MIN(item_price)::float8 as price, unit_of_measure, manufacturer_name, \
+                 MAX(price_update_date) as price_update_date \
                  FROM items \
                  WHERE store_pk = ANY($1) AND item_code = ANY($2) \
                  GROUP BY store_pk, item_code, item_name, unit_of_measure, manufacturer_name"
@@ -439,53 +1174,114 @@ impl DatabaseManager {
                         price: row.get::<f64, _>("price"),
                         unit_of_measure: row.get("unit_of_measure"),
                         manufacturer_name: row.get("manufacturer_name"),
+                        price_update_date: row.get("price_update_date"),
+                        is_stale: self.is_stale(row.get("price_update_date")),
+                        match_confidence: Some(1.0),
+                        alternatives: vec![],
+                        term_index: idx,
                     });
                 }
             }
         }
 
-        // --- Name (LIKE) lookup: trigram index, cheapest per matching term per store ---
-        if !name_terms.is_empty() {
-            let like_patterns: Vec<String> = name_terms.iter()
-                .map(|(_, n)| format!("%{}%", n.to_lowercase()))
-                .collect();
-
-            // Build: LOWER(item_name) LIKE $2 OR LOWER(item_name) LIKE $3 ...
-            let or_clause: String = (1..=like_patterns.len())
-                .map(|i| format!("LOWER(item_name) LIKE ${}", i + 1))
-                .collect::<Vec<_>>()
-                .join(" OR ");
-
-            let sql = format!(
-                "SELECT store_pk, item_code, item_name, item_price::float8 as price, \
-                 unit_of_measure, manufacturer_name \
-                 FROM items \
-                 WHERE store_pk = ANY($1) AND ({}) \
-                 ORDER BY store_pk, item_price ASC",
-                or_clause
-            );
-
-            let mut q = sqlx::query(&sql).bind(store_ids);
-            for pat in &like_patterns {
-                q = q.bind(pat);
+        // --- Cluster lookup: cheapest member item per store; confidence is the member's ---
+        if !clusters.is_empty() {
+            let (indexes, cluster_ids): (Vec<i32>, Vec<i32>) = clusters.iter().map(|&(idx, id)| (idx as i32, id)).unzip();
+            let rows = sqlx::query(
+                "SELECT DISTINCT ON (i.store_pk, t.idx) \
+                        i.store_pk, t.idx, i.item_code, i.item_name, i.item_price::float8 as price, \
+                        i.unit_of_measure, i.manufacturer_name, i.price_update_date, m.confidence \
+                 FROM unnest($2::int4[], $3::int4[]) AS t(idx, cluster_id) \
+                 JOIN product_cluster_members m ON m.cluster_id = t.cluster_id AND m.status <> 'rejected' \
+                 JOIN stores s ON s.chain_id = m.chain_id \
+                 JOIN items i ON i.store_pk = s.id AND i.item_code = m.item_code \
+                 WHERE i.store_pk = ANY($1) \
+                 ORDER BY i.store_pk, t.idx, i.item_price ASC"
+            )
+            .bind(store_ids)
+            .bind(&indexes)
+            .bind(&cluster_ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for row in rows {
+                let idx = row.get::<i32, _>("idx") as usize;
+                by_store.entry(row.get("store_pk")).or_default().insert(idx, ItemPrice {
+                    item_code: row.get("item_code"),
+                    item_name: row.get("item_name"),
+                    price: row.get::<f64, _>("price"),
+                    unit_of_measure: row.get("unit_of_measure"),
+                    manufacturer_name: row.get("manufacturer_name"),
+                    price_update_date: row.get("price_update_date"),
+                    is_stale: self.is_stale(row.get("price_update_date")),
+                    match_confidence: Some(row.get("confidence")),
+                    alternatives: vec![],
+                    term_index: idx,
+                });
             }
-            let rows = q.fetch_all(&self.pool).await?;
+        }
+
+        // --- Name lookup: every containing item is a candidate; matching::select_matches
+        // scores them and picks one per store (not simply the cheapest) ---
+        if !name_terms.is_empty() {
+            let terms: Vec<&String> = name_terms.iter().map(|(_, n)| *n).collect();
+            let term_variants = self.term_variants(&terms).await?;
+            // One row per (pattern, term index, variant): an item is scored against the
+            // variant it resembles most.
+            let mut patterns = Vec::new();
+            let mut indexes = Vec::new();
+            let mut variant_terms = Vec::new();
+            for ((idx, _), variants) in name_terms.iter().zip(&term_variants) {
+                for variant in variants {
+                    let variant_patterns = hebrew::like_patterns(variant);
+                    indexes.extend(std::iter::repeat_n(*idx as i32, variant_patterns.len()));
+                    variant_terms.extend(std::iter::repeat_n(variant.clone(), variant_patterns.len()));
+                    patterns.extend(variant_patterns);
+                }
+            }
+
+            let rows = sqlx::query(
+                "SELECT DISTINCT ON (i.id, t.idx) \
+                        i.store_pk, t.idx, i.item_code, i.item_name, i.item_price::float8 as price, \
+                        i.unit_of_measure, i.manufacturer_name, i.price_update_date, \
+                        similarity(i.item_name_normalized, t.term)::float8 as name_similarity \
+                 FROM items i \
+                 JOIN unnest($2::text[], $3::int4[], $4::text[]) AS t(pattern, idx, term) \
+                   ON i.item_name_normalized LIKE t.pattern \
+                 WHERE i.store_pk = ANY($1) \
+                 ORDER BY i.id, t.idx, name_similarity DESC"
+            )
+            .bind(store_ids)
+            .bind(&patterns)
+            .bind(&indexes)
+            .bind(&variant_terms)
+            .fetch_all(&self.pool)
+            .await?;
 
+            let mut by_term: HashMap<usize, Vec<(i32, ItemPrice, f64)>> = HashMap::new();
             for row in rows {
-                let sid: i32 = row.get("store_pk");
-                let name_lower: String = row.get::<String, _>("item_name").to_lowercase();
-                let store_map = by_store.entry(sid).or_default();
-                for &(idx, term) in &name_terms {
-                    if store_map.contains_key(&idx) { continue; }
-                    if name_lower.contains(term.to_lowercase().as_str()) {
-                        store_map.insert(idx, ItemPrice {
-                            item_code: row.get("item_code"),
-                            item_name: row.get("item_name"),
-                            price: row.get::<f64, _>("price"),
-                            unit_of_measure: row.get("unit_of_measure"),
-                            manufacturer_name: row.get("manufacturer_name"),
-                        });
-                    }
+                let idx = row.get::<i32, _>("idx") as usize;
+                by_term.entry(idx).or_default().push((
+                    row.get("store_pk"),
+                    ItemPrice {
+                        item_code: row.get("item_code"),
+                        item_name: row.get("item_name"),
+                        price: row.get::<f64, _>("price"),
+                        unit_of_measure: row.get("unit_of_measure"),
+                        manufacturer_name: row.get("manufacturer_name"),
+                        price_update_date: row.get("price_update_date"),
+                        is_stale: self.is_stale(row.get("price_update_date")),
+                        match_confidence: None,
+                        alternatives: vec![],
+                        term_index: idx,
+                    },
+                    row.get("name_similarity"),
+                ));
+            }
+            for ((idx, _), variants) in name_terms.iter().zip(&term_variants) {
+                let candidates = by_term.remove(idx).unwrap_or_default();
+                for (sid, item) in select_matches(variants, candidates) {
+                    by_store.entry(sid).or_default().insert(*idx, item);
                 }
             }
         }
@@ -493,29 +1289,189 @@ impl DatabaseManager {
         Ok(by_store)
     }
 
+    /// In-store alternatives for missing barcode terms, keyed by (store_pk, term_index),
+    /// best first. Candidates are items whose name is trigram-similar to the catalog
+    /// name — more loosely when the manufacturer (`manufacturer_aliases`) matches, so
+    /// any spelling of the brand counts — and that aren't classified
+    /// under a different top-level category than the missing product; they are
+    /// ranked by `rank_substitutes`.
+    async fn find_substitutes(
+        &self,
+        missing: &[(i32, usize, String)],
+    ) -> Result<HashMap<(i32, usize), Vec<Substitute>>> {
+        if missing.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let store_pks: Vec<i32> = missing.iter().map(|m| m.0).collect();
+        let term_indexes: Vec<i32> = missing.iter().map(|m| m.1 as i32).collect();
+        let barcodes: Vec<String> = missing.iter().map(|m| m.2.clone()).collect();
+
+        // `%` is what lets the trigram index narrow the candidates; its threshold is the
+        // looser of the two, and the stricter one is applied per row.
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT set_config('pg_trgm.similarity_threshold', $1, true)")
+            .bind(SUBSTITUTE_MIN_SIMILARITY_SAME_MANUFACTURER.to_string())
+            .execute(&mut *tx)
+            .await?;
+        let rows = sqlx::query(
+            "SELECT m.store_pk, m.term_index, \
+                    i.item_code, i.item_name, i.item_price::float8 AS price, \
+                    i.unit_of_measure, i.unit_qty, i.quantity, i.manufacturer_name, i.price_update_date, \
+                    r.quantity AS ref_quantity, r.unit_of_measure AS ref_unit, \
+                    sim.name_similarity, sim.same_manufacturer \
+             FROM unnest($1::int4[], $2::int4[], $3::text[]) AS m(store_pk, term_index, barcode) \
+             JOIN products r ON r.barcode = m.barcode \
+             JOIN stores s ON s.id = m.store_pk \
+             JOIN items i ON i.store_pk = m.store_pk AND i.item_code <> m.barcode \
+                         AND i.item_name_normalized % r.canonical_name_normalized \
+             LEFT JOIN manufacturer_aliases ra ON ra.alias = r.manufacturer \
+             LEFT JOIN manufacturer_aliases ia ON ia.alias = i.manufacturer_name \
+             CROSS JOIN LATERAL ( \
+                 SELECT similarity(i.item_name_normalized, r.canonical_name_normalized)::float8 AS name_similarity, \
+                        COALESCE(ia.manufacturer_id = ra.manufacturer_id, false) AS same_manufacturer \
+             ) sim \
+             LEFT JOIN LATERAL ( \
+                 SELECT COALESCE(c.parent_id, c.id) AS root_id \
+                 FROM item_categories ic JOIN categories c ON c.id = ic.category_id \
+                 WHERE ic.item_code = m.barcode \
+                 ORDER BY ic.chain_id = s.chain_id DESC, ic.source = 'manual' DESC \
+                 LIMIT 1 \
+             ) wanted ON true \
+             LEFT JOIN LATERAL ( \
+                 SELECT COALESCE(c.parent_id, c.id) AS root_id \
+                 FROM item_categories ic JOIN categories c ON c.id = ic.category_id \
+                 WHERE ic.chain_id = s.chain_id AND ic.item_code = i.item_code \
+             ) found ON true \
+             WHERE (sim.name_similarity >= $4 OR sim.same_manufacturer) \
+               AND (wanted.root_id IS NULL OR found.root_id IS NULL OR wanted.root_id = found.root_id)"
+        )
+        .bind(&store_pks)
+        .bind(&term_indexes)
+        .bind(&barcodes)
+        .bind(SUBSTITUTE_MIN_SIMILARITY)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut candidates: HashMap<(i32, usize), Vec<SubstituteCandidate>> = HashMap::new();
+        let mut originals: HashMap<(i32, usize), Option<Size>> = HashMap::new();
+        for row in rows {
+            let key = (row.get::<i32, _>("store_pk"), row.get::<i32, _>("term_index") as usize);
+            originals.entry(key).or_insert_with(|| {
+                normalized_size(row.get("ref_quantity"), &[row.get("ref_unit")])
+            });
+            let same_manufacturer: bool = row.get("same_manufacturer");
+            let size = normalized_size(row.get("quantity"), &[row.get("unit_qty"), row.get("unit_of_measure")]);
+            candidates.entry(key).or_default().push((
+                Substitute {
+                    item: ItemPrice {
+                        item_code: row.get("item_code"),
+                        item_name: row.get("item_name"),
+                        price: row.get("price"),
+                        unit_of_measure: row.get("unit_of_measure"),
+                        manufacturer_name: row.get("manufacturer_name"),
+                        price_update_date: row.get("price_update_date"),
+                        is_stale: self.is_stale(row.get("price_update_date")),
+                        match_confidence: None,
+                        alternatives: vec![],
+                        term_index: key.1,
+                    },
+                    reason: if same_manufacturer { SubstituteReason::SameManufacturer } else { SubstituteReason::SimilarName },
+                    match_score: row.get("name_similarity"),
+                    size_ratio: None,
+                },
+                size,
+            ));
+        }
+
+        Ok(candidates
+            .into_iter()
+            .map(|(key, found)| {
+                let original = originals.get(&key).copied().flatten();
+                (key, rank_substitutes(found, original))
+            })
+            .filter(|(_, ranked)| !ranked.is_empty())
+            .collect())
+    }
+
+    /// IDs of stores with at least one price newer than `max_age_hours`.
+    async fn fresh_store_ids(&self, max_age_hours: f64) -> Result<HashSet<i32>> {
+        let ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT s.id FROM stores s \
+             WHERE EXISTS ( \
+                 SELECT 1 FROM items i WHERE i.store_pk = s.id AND i.price_update_date >= $1 \
+             )"
+        )
+        .bind(self.stale_cutoff(max_age_hours))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(ids.into_iter().collect())
+    }
+
+    /// Every store with its newest price timestamp, oldest data first (never-updated stores lead).
+    pub async fn store_freshness(&self) -> Result<Vec<StoreFreshness>> {
+        let rows = sqlx::query(
+            "SELECT s.id, s.chain_id, s.store_id, \
+                    COALESCE(s.store_name, cn.display_name) as store_name, s.city, \
+                    f.last_price_update \
+             FROM stores s \
+             LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id \
+             ) f ON true \
+             ORDER BY f.last_price_update ASC NULLS FIRST, s.id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = chain_local_now();
+        Ok(rows.into_iter().map(|row| {
+            let last_price_update: Option<NaiveDateTime> = row.get("last_price_update");
+            StoreFreshness {
+                id: row.get("id"),
+                chain_id: row.get("chain_id"),
+                store_id: row.get("store_id"),
+                store_name: row.get("store_name"),
+                city: row.get("city"),
+                last_price_update,
+                age_hours: last_price_update.map(|ts| (now - ts).num_seconds() as f64 / 3600.0),
+                is_stale: self.is_stale(last_price_update),
+            }
+        }).collect())
+    }
+
     /// Like get_stores_with_items but pre-filtered to a set of candidate store IDs.
     /// Used when a location or city pre-filter has already determined the candidate set.
     async fn get_stores_with_items_from_set(
         &self,
         grocery_list: &[String],
         candidate_ids: &[i32],
-        page: usize,
-        page_size: usize,
-    ) -> Result<(Vec<StoreInfo>, usize)> {
-        self.rank_stores_by_coverage(grocery_list, Some(candidate_ids), page, page_size).await
+        filter: &StoreFilter,
+    ) -> Result<Vec<StoreInfo>> {
+        self.rank_stores_by_coverage(grocery_list, Some(candidate_ids), filter).await
     }
 
-    pub async fn compare_prices(&self, request: PriceComparisonRequest) -> Result<PriceComparisonResponse> {
-        let page = request.page.unwrap_or(1).max(1);
-        let page_size = request.page_size.unwrap_or(10).max(1).min(50);
-
-        let (stores, total_stores) = if let Some(ref loc) = request.user_location {
+    /// Compares the list across every store in scope, ranks them, then returns the
+    /// requested page. `quantities` (per term index, 1 when absent) weight the totals
+    /// stores are ranked on, so v2 baskets page in their weighted order.
+    pub async fn compare_prices(
+        &self,
+        request: PriceComparisonRequest,
+        quantities: &[u32],
+    ) -> Result<PriceComparisonResponse> {
+        let options = &request.options;
+        let (page, page_size) = page_bounds(options);
+
+        let ranking = Ranking::from_options(options);
+        // Store id -> km from the user; only filled in the location branch.
+        let mut distances: HashMap<i32, f64> = HashMap::new();
+        let mut candidate_ids: Option<Vec<i32>> = if let Some(ref loc) = options.user_location {
             // Get IDs of all stores within radius, then intersect with item-carrying stores
             let radius_km = loc.radius_km.unwrap_or(10.0);
             let nearby = self.get_nearby_stores(loc.latitude, loc.longitude, radius_km).await?;
-            let nearby_ids: Vec<i32> = nearby.iter().map(|s| s.id).collect();
-            self.get_stores_with_items_from_set(&request.grocery_list, &nearby_ids, page, page_size).await?
-        } else if let Some(ref city) = request.city {
+            distances = nearby.iter().filter_map(|s| Some((s.id, s.distance_km?))).collect();
+            Some(nearby.iter().map(|s| s.id).collect())
+        } else if let Some(ref city) = options.city {
             // Get IDs of all stores in that city, then intersect with item-carrying stores
             let city_ids: Vec<i32> = sqlx::query_scalar(
                 "SELECT id FROM stores WHERE LOWER(city) LIKE $1"
@@ -523,21 +1479,68 @@ impl DatabaseManager {
             .bind(format!("%{}%", city.to_lowercase()))
             .fetch_all(&self.pool)
             .await?;
-            self.get_stores_with_items_from_set(&request.grocery_list, &city_ids, page, page_size).await?
+            Some(city_ids)
         } else {
-            self.get_stores_with_items(&request.grocery_list, page, page_size).await?
+            None
+        };
+
+        // Drop stores whose newest price is older than the caller's max_data_age_hours.
+        if let Some(hours) = options.max_data_age_hours {
+            let fresh = self.fresh_store_ids(hours).await?;
+            candidate_ids = Some(match candidate_ids {
+                Some(ids) => ids.into_iter().filter(|id| fresh.contains(id)).collect(),
+                None => fresh.into_iter().collect(),
+            });
+        }
+
+        // Every store in scope is priced and ranked before the page is cut: a cheaper or
+        // closer store must not be stuck behind the page boundary.
+        let stores = match candidate_ids {
+            Some(ref ids) => {
+                self.get_stores_with_items_from_set(&request.grocery_list, ids, &options.store_filter).await?
+            }
+            None => self.get_stores_with_items(&request.grocery_list, &options.store_filter).await?,
         };
+        let total_stores = stores.len();
 
-        let page_store_ids: Vec<i32> = stores.iter().map(|s| s.id).collect();
+        let store_ids: Vec<i32> = stores.iter().map(|s| s.id).collect();
         let mut items_by_store = self
-            .find_items_for_stores(&page_store_ids, &request.grocery_list)
+            .find_items_for_stores(&store_ids, &request.grocery_list)
             .await?;
 
+        // Auto-substitution changes totals, so every store's alternatives are needed to
+        // rank; suggestions alone are looked up for the page's stores only.
+        let mut substitutes = if options.auto_substitute {
+            let found = store_ids.iter().map(|id| {
+                (*id, items_by_store.get(id).map(|f| f.keys().copied().collect()).unwrap_or_default())
+            });
+            self.find_substitutes(&missing_barcodes(&request.grocery_list, found)).await?
+        } else {
+            HashMap::new()
+        };
+
+        // Fill-in prices for missing terms, drawn from every store in scope.
+        let estimates = match options.estimate_missing {
+            Some(_) if !stores.is_empty() => {
+                let scoped = self.scoped_store_ids(candidate_ids.as_deref(), &options.store_filter).await?;
+                self.term_price_estimates(&request.grocery_list, scoped.as_deref()).await?
+            }
+            _ => HashMap::new(),
+        };
+
         let mut store_comparisons = Vec::new();
 
-        for store in stores {
+        for mut store in stores {
+            store.distance_km = distances.get(&store.id).copied();
             // term_map: term_index -> ItemPrice (one entry per grocery list term, if found)
-            let term_map = items_by_store.remove(&store.id).unwrap_or_default();
+            let mut term_map = items_by_store.remove(&store.id).unwrap_or_default();
+            let mut substitutions = substitution_suggestions(&request.grocery_list, store.id, &mut substitutes);
+            if options.auto_substitute {
+                for suggestion in &mut substitutions {
+                    term_map.insert(suggestion.term_index, suggestion.alternatives[0].item.clone());
+                    suggestion.applied = true;
+                }
+            }
             let total_price: f64 = term_map.values().map(|item| item.price).sum();
 
             // A term is missing if its index has no entry in term_map
@@ -549,26 +1552,67 @@ impl DatabaseManager {
                 .collect();
 
             let items_found = request.grocery_list.len() - missing_items.len();
-            let items: Vec<ItemPrice> = term_map.into_values().collect();
-
+            let mut items: Vec<ItemPrice> = term_map.into_values().collect();
+            items.sort_by_key(|item| item.term_index);
+
+            let missing_estimates: Vec<MissingEstimate> = options
+                .estimate_missing
+                .map(|method| {
+                    request.grocery_list
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| !items.iter().any(|i| i.term_index == *idx))
+                        .filter_map(|(idx, term)| {
+                            let &(median, cheapest, store_count) = estimates.get(&idx)?;
+                            Some(MissingEstimate {
+                                term_index: idx,
+                                term: term.clone(),
+                                estimated_price: match method {
+                                    EstimateMethod::Median => median,
+                                    EstimateMethod::CheapestNearby => cheapest,
+                                },
+                                method,
+                                store_count,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let estimated_total = options
+                .estimate_missing
+                .map(|_| total_price + missing_estimates.iter().map(|e| e.estimated_price).sum::<f64>());
+
+            let score = ranking.score(ranking.basket_total(total_price, estimated_total), store.distance_km);
             store_comparisons.push(StoreComparison {
                 store,
                 items,
                 total_price,
                 items_found,
                 items_missing: missing_items,
+                estimated_total,
+                missing_estimates,
+                substitutions,
+                score,
             });
         }
 
-        // Sort: most items found first, then cheapest total
-        store_comparisons.sort_by(|a, b| {
-            b.items_found.cmp(&a.items_found)
-                .then_with(|| a.total_price.partial_cmp(&b.total_price).unwrap_or(std::cmp::Ordering::Equal))
-        });
+        // Most items found first, then cheapest total (or effective cost / estimated
+        // full basket, per the request's ranking options) — across all stores, then paged.
+        ranking.rank(&mut store_comparisons, quantities);
+        let best_store = if page == 1 { store_comparisons.first().cloned() } else { None };
+        let mut store_comparisons: Vec<StoreComparison> =
+            store_comparisons.into_iter().skip((page - 1) * page_size).take(page_size).collect();
+
+        if options.suggest_substitutes && !options.auto_substitute {
+            let found = store_comparisons.iter().map(|c| (c.store.id, c.items.iter().map(|i| i.term_index).collect()));
+            let mut substitutes = self.find_substitutes(&missing_barcodes(&request.grocery_list, found)).await?;
+            for comparison in &mut store_comparisons {
+                comparison.substitutions = substitution_suggestions(&request.grocery_list, comparison.store.id, &mut substitutes);
+            }
+        }
 
         let delivered = (page - 1) * page_size + store_comparisons.len();
         let has_more = delivered < total_stores;
-        let best_store = if page == 1 { store_comparisons.first().cloned() } else { None };
 
         Ok(PriceComparisonResponse {
             stores: store_comparisons,
@@ -579,6 +1623,142 @@ impl DatabaseManager {
         })
     }
 
+    /// Round-trips a trivial query — the readiness probe's connectivity check.
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// (latest applied migration, latest migration compiled into this binary).
+    pub async fn migration_versions(&self) -> Result<(Option<i64>, i64)> {
+        let applied: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(version) FROM _sqlx_migrations WHERE success"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let expected = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+        Ok((applied, expected))
+    }
+
+    /// Newest price_update_date per chain. Uses a per-store LATERAL max so each
+    /// store costs one index probe on idx_items_store_price_date.
+    pub async fn chain_freshness(&self) -> Result<Vec<ChainFreshness>> {
+        let rows = sqlx::query(
+            "SELECT s.chain_id, cn.display_name, MAX(m.last_update) AS last_price_update \
+             FROM stores s \
+             LEFT JOIN chain_names cn ON cn.chain_id = s.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_update FROM items WHERE store_pk = s.id \
+             ) m ON true \
+             GROUP BY s.chain_id, cn.display_name \
+             ORDER BY s.chain_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| ChainFreshness {
+            chain_id: row.get("chain_id"),
+            display_name: row.get("display_name"),
+            last_price_update: row.get("last_price_update"),
+        }).collect())
+    }
+
+    /// Every chain known from stores or `chain_names`, with store and item counts
+    /// and its newest price.
+    pub async fn get_chains(&self) -> Result<Vec<ChainSummary>> {
+        let rows = sqlx::query(
+            "WITH per_store AS ( \
+                 SELECT s.id, s.chain_id, f.item_count, f.last_price_update \
+                 FROM stores s \
+                 LEFT JOIN LATERAL ( \
+                     SELECT COUNT(*) AS item_count, MAX(price_update_date) AS last_price_update \
+                     FROM items WHERE store_pk = s.id \
+                 ) f ON true \
+             ) \
+             SELECT c.chain_id, cn.display_name, \
+                    COUNT(ps.id) AS store_count, \
+                    COALESCE(SUM(ps.item_count), 0)::int8 AS item_count, \
+                    MAX(ps.last_price_update) AS last_price_update \
+             FROM (SELECT chain_id FROM stores UNION SELECT chain_id FROM chain_names) c \
+             LEFT JOIN chain_names cn ON cn.chain_id = c.chain_id \
+             LEFT JOIN per_store ps ON ps.chain_id = c.chain_id \
+             GROUP BY c.chain_id, cn.display_name \
+             ORDER BY c.chain_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = chain_local_now();
+        Ok(rows.into_iter().map(|row| {
+            let last_price_update: Option<NaiveDateTime> = row.get("last_price_update");
+            ChainSummary {
+                chain_id: row.get("chain_id"),
+                display_name: row.get("display_name"),
+                store_count: row.get("store_count"),
+                item_count: row.get("item_count"),
+                last_price_update,
+                age_hours: last_price_update.map(|ts| (now - ts).num_seconds() as f64 / 3600.0),
+                is_stale: self.is_stale(last_price_update),
+            }
+        }).collect())
+    }
+
+    /// All stores of one chain, ordered like [`get_all_stores`](Self::get_all_stores).
+    /// None when the chain has neither stores nor a display name.
+    pub async fn get_chain_stores(&self, chain_id: &str) -> Result<Option<Vec<StoreInfo>>> {
+        let rows = sqlx::query(
+            "SELECT s.id, s.chain_id, s.sub_chain_id, s.store_id, \
+                    COALESCE(s.store_name, cn.display_name) as store_name, \
+                    s.address, s.city, \
+                    s.latitude::float8, s.longitude::float8, f.last_price_update \
+             FROM stores s \
+             LEFT JOIN chain_names cn ON s.chain_id = cn.chain_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT MAX(price_update_date) AS last_price_update FROM items WHERE store_pk = s.id \
+             ) f ON true \
+             WHERE s.chain_id = $1 \
+             ORDER BY s.city NULLS LAST, store_name NULLS LAST"
+        )
+        .bind(chain_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.is_empty() {
+            let named: Option<i32> = sqlx::query_scalar("SELECT 1 FROM chain_names WHERE chain_id = $1")
+                .bind(chain_id)
+                .fetch_optional(&self.pool)
+                .await?;
+            return Ok(named.map(|_| vec![]));
+        }
+
+        Ok(Some(rows.into_iter().map(|row| StoreInfo {
+            id: row.get("id"),
+            chain_id: row.get("chain_id"),
+            sub_chain_id: row.get("sub_chain_id"),
+            store_id: row.get("store_id"),
+            store_name: row.get("store_name"),
+            address: row.get("address"),
+            city: row.get("city"),
+            latitude: row.get("latitude"),
+            longitude: row.get("longitude"),
+            distance_km: None,
+            last_price_update: row.get("last_price_update"),
+        }).collect()))
+    }
+
+    /// Adds or renames a chain's display name.
+    pub async fn upsert_chain_name(&self, chain_id: &str, display_name: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO chain_names (chain_id, display_name) VALUES ($1, $2) \
+             ON CONFLICT (chain_id) DO UPDATE SET display_name = EXCLUDED.display_name"
+        )
+        .bind(chain_id)
+        .bind(display_name)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn update_store_from_stores_full(
         &self,
         chain_id: &str,
@@ -612,7 +1792,8 @@ impl DatabaseManager {
 
     /// Upsert a product into the canonical products catalog.
     /// Called during XML ingest for every item with a valid EAN-13 barcode.
-    /// ON CONFLICT DO NOTHING keeps the first-seen canonical name.
+    /// ON CONFLICT DO NOTHING: the canonical name is left to `elect_canonical_names`
+    /// and later sizes to `record_product_variants`.
     pub async fn upsert_product(&self, item: &crate::models::Item) -> Result<()> {
         sqlx::query(
             r#"
@@ -628,7 +1809,85 @@ impl DatabaseManager {
         .bind(&item.unit_of_measure)
         .execute(&self.pool)
         .await?;
+        Ok(())
+    }
+
+    /// Records one file's catalog items: each (barcode, name) counts once as a name
+    /// variant of the chain, however many rows list it. A parsed pack size replaces
+    /// the catalog's when the chain is trusted (`trusted_name_chains`) or the stored
+    /// size doesn't parse, so chains don't overwrite each other on every scan.
+    pub async fn record_product_variants(&self, chain_id: &str, items: &[crate::models::Item]) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut sizes: HashMap<&str, (&str, &str)> = HashMap::new();
+        let (mut barcodes, mut names) = (Vec::new(), Vec::new());
+        for item in items.iter().filter(|item| is_ean13(&item.item_code)) {
+            let name = item.item_name.trim();
+            if seen.insert((item.item_code.as_str(), name)) {
+                barcodes.push(item.item_code.clone());
+                names.push(name.to_string());
+            }
+            // Keep the unit field the size parsed from.
+            if let Some(quantity) = item.quantity.as_deref()
+                && let Some(unit) = [item.unit_qty.as_deref(), item.unit_of_measure.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .find(|unit| normalized_size(Some(quantity), &[Some(unit)]).is_some())
+            {
+                sizes.entry(&item.item_code).or_insert((quantity, unit));
+            }
+        }
+
+        sqlx::query(
+            "INSERT INTO product_name_variants (barcode, chain_id, name) \
+             SELECT n.barcode, $1, n.name FROM unnest($2::text[], $3::text[]) AS n(barcode, name) \
+             JOIN products p ON p.barcode = n.barcode \
+             ON CONFLICT (barcode, chain_id, name) DO UPDATE SET \
+                 seen_count = product_name_variants.seen_count + 1, \
+                 last_seen_at = NOW()"
+        )
+        .bind(chain_id)
+        .bind(&barcodes)
+        .bind(&names)
+        .execute(&self.pool)
+        .await?;
 
+        if sizes.is_empty() {
+            return Ok(());
+        }
+        let trusted = self.trusted_name_chains.iter().any(|c| c == chain_id);
+        let sized_barcodes: Vec<&str> = sizes.keys().copied().collect();
+        let stored = sqlx::query(
+            "SELECT barcode, quantity, unit_of_measure FROM products WHERE barcode = ANY($1)"
+        )
+        .bind(&sized_barcodes)
+        .fetch_all(&self.pool)
+        .await?;
+        let (mut update_barcodes, mut quantities, mut units) = (Vec::new(), Vec::new(), Vec::new());
+        for row in stored {
+            let barcode: String = row.get("barcode");
+            let quantity: Option<String> = row.get("quantity");
+            let unit: Option<String> = row.get("unit_of_measure");
+            let Some(&(new_quantity, new_unit)) = sizes.get(barcode.as_str()) else {
+                continue;
+            };
+            let unchanged = quantity.as_deref() == Some(new_quantity) && unit.as_deref() == Some(new_unit);
+            let stored_parses = normalized_size(quantity.as_deref(), &[unit.as_deref()]).is_some();
+            if !unchanged && (trusted || !stored_parses) {
+                update_barcodes.push(barcode);
+                quantities.push(new_quantity);
+                units.push(new_unit);
+            }
+        }
+        sqlx::query(
+            "UPDATE products p SET quantity = n.quantity, unit_of_measure = n.unit \
+             FROM unnest($1::text[], $2::text[], $3::text[]) AS n(barcode, quantity, unit) \
+             WHERE p.barcode = n.barcode"
+        )
+        .bind(&update_barcodes)
+        .bind(&quantities)
+        .bind(&units)
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
 }
@@ -646,6 +1905,110 @@ impl DatabaseManager {
         Ok(exists.is_some())
     }
 
+    /// All ledger entries as filename -> size, for diffing against the watch directory.
+    pub async fn processed_file_sizes(&self) -> Result<HashMap<String, i64>> {
+        let rows = sqlx::query("SELECT filename, file_size FROM processed_files")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|r| (r.get("filename"), r.get("file_size"))).collect())
+    }
+
+    /// Drops a file's ledger row so the next scan ingests it again. False if it had none.
+    pub async fn forget_processed_file(&self, filename: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM processed_files WHERE filename = $1")
+            .bind(filename)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// One page of the ingest ledger, newest first, optionally filtered by filename substring.
+    pub async fn processed_files_page(
+        &self,
+        query: Option<&str>,
+        page: usize,
+        limit: usize,
+    ) -> Result<(Vec<LedgerEntry>, usize)> {
+        let pattern = match query {
+            Some(q) if !q.is_empty() => format!("%{}%", q.to_lowercase()),
+            _ => "%".to_string(),
+        };
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM processed_files WHERE LOWER(filename) LIKE $1"
+        )
+        .bind(&pattern)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            "SELECT filename, file_size, processed_at FROM processed_files \
+             WHERE LOWER(filename) LIKE $1 \
+             ORDER BY processed_at DESC NULLS LAST, filename \
+             LIMIT $2 OFFSET $3"
+        )
+        .bind(&pattern)
+        .bind(limit as i64)
+        .bind(((page.saturating_sub(1)) * limit) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows.into_iter().map(|r| LedgerEntry {
+            filename: r.get("filename"),
+            file_size: r.get("file_size"),
+            processed_at: r.get("processed_at"),
+        }).collect();
+        Ok((entries, total as usize))
+    }
+
+    /// Deletes every store of a chain with its items and price history. The chain's
+    /// display name and the ingest ledger are kept.
+    pub async fn purge_chain(&self, chain_id: &str) -> Result<PurgeSummary> {
+        let mut tx = self.pool.begin().await?;
+        let price_history = sqlx::query(
+            "DELETE FROM price_history WHERE store_pk IN (SELECT id FROM stores WHERE chain_id = $1)"
+        )
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let items = sqlx::query(
+            "DELETE FROM items WHERE store_pk IN (SELECT id FROM stores WHERE chain_id = $1)"
+        )
+        .bind(chain_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let stores = sqlx::query("DELETE FROM stores WHERE chain_id = $1")
+            .bind(chain_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(PurgeSummary { stores, items, price_history })
+    }
+
+    /// Deletes one store with its items and price history.
+    pub async fn purge_store(&self, id: i32) -> Result<PurgeSummary> {
+        let mut tx = self.pool.begin().await?;
+        let price_history = sqlx::query("DELETE FROM price_history WHERE store_pk = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let items = sqlx::query("DELETE FROM items WHERE store_pk = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let stores = sqlx::query("DELETE FROM stores WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(PurgeSummary { stores, items, price_history })
+    }
+
     /// Marks a file as processed so it won't be re-scanned on next startup.
     pub async fn mark_file_processed(&self, filename: &str, file_size: i64) -> Result<()> {
         sqlx::query(
@@ -665,35 +2028,1339 @@ impl DatabaseManager {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::is_ean13;
+/// Accounts: users, sessions and saved grocery lists.
+impl DatabaseManager {
+    /// Creates a user; None if the email is already registered.
+    pub async fn create_user(&self, email: &str, password_hash: &str) -> Result<Option<UserInfo>> {
+        let row = sqlx::query(
+            "INSERT INTO users (email, password_hash) VALUES ($1, $2) \
+             ON CONFLICT (email) DO NOTHING \
+             RETURNING id, email, created_at"
+        )
+        .bind(email)
+        .bind(password_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| UserInfo { id: r.get("id"), email: r.get("email"), created_at: r.get("created_at") }))
+    }
 
-    #[test]
-    fn valid_ean13_barcodes() {
-        // EAN-13 codes with correct check digits
-        assert!(is_ean13("7290000066769"));
-        assert!(is_ean13("7290027600007"));
-        assert!(is_ean13("4006381333931"));
+    /// The user with this email and their stored password hash.
+    pub async fn user_credentials(&self, email: &str) -> Result<Option<(UserInfo, String)>> {
+        let row = sqlx::query("SELECT id, email, created_at, password_hash FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| {
+            (
+                UserInfo { id: r.get("id"), email: r.get("email"), created_at: r.get("created_at") },
+                r.get("password_hash"),
+            )
+        }))
     }
 
-    #[test]
-    fn wrong_check_digit_rejected() {
-        assert!(!is_ean13("7290000066768"));
-        assert!(!is_ean13("4006381333930"));
+    /// Stores a new session and clears the user's expired ones.
+    pub async fn create_session(&self, user_id: i64, token_hash: &[u8], expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND expires_at <= NOW()")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("INSERT INTO sessions (token_hash, user_id, expires_at) VALUES ($1, $2, $3)")
+            .bind(token_hash)
+            .bind(user_id)
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
-    #[test]
-    fn wrong_length_rejected() {
-        assert!(!is_ean13(""));
-        assert!(!is_ean13("729000006676"));    // 12 digits
-        assert!(!is_ean13("72900000667680"));  // 14 digits
+    /// The user owning an unexpired session.
+    pub async fn session_user(&self, token_hash: &[u8]) -> Result<Option<UserInfo>> {
+        let row = sqlx::query(
+            "SELECT u.id, u.email, u.created_at FROM sessions s \
+             JOIN users u ON u.id = s.user_id \
+             WHERE s.token_hash = $1 AND s.expires_at > NOW()"
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| UserInfo { id: r.get("id"), email: r.get("email"), created_at: r.get("created_at") }))
     }
 
-    #[test]
-    fn non_digits_rejected() {
-        assert!(!is_ean13("729000006676a"));
-        assert!(!is_ean13("חלב תנובה 1 ל"));
-        assert!(!is_ean13("7290-00006676"));
+    pub async fn delete_session(&self, token_hash: &[u8]) -> Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE token_hash = $1")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The subset of `barcodes` present in the product catalog.
+    pub async fn known_barcodes(&self, barcodes: &[String]) -> Result<HashSet<String>> {
+        if barcodes.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let found: Vec<String> = sqlx::query_scalar("SELECT barcode FROM products WHERE barcode = ANY($1)")
+            .bind(barcodes)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(found.into_iter().collect())
+    }
+
+    pub async fn saved_list_count(&self, user_id: i64) -> Result<i64> {
+        Ok(sqlx::query_scalar("SELECT COUNT(*) FROM grocery_lists WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?)
+    }
+
+    /// The user's lists, most recently updated first.
+    pub async fn saved_lists(&self, user_id: i64) -> Result<Vec<SavedListSummary>> {
+        let rows = sqlx::query(
+            "SELECT l.id, l.name, l.updated_at, \
+                    (SELECT COUNT(*) FROM grocery_list_items i WHERE i.list_id = l.id) AS item_count \
+             FROM grocery_lists l \
+             WHERE l.user_id = $1 \
+             ORDER BY l.updated_at DESC, l.name"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| SavedListSummary {
+            id: r.get("id"),
+            name: r.get("name"),
+            item_count: r.get("item_count"),
+            updated_at: r.get("updated_at"),
+        }).collect())
+    }
+
+    /// One of the user's lists with its items in order; None if it doesn't exist or isn't theirs.
+    pub async fn saved_list(&self, user_id: i64, id: Uuid) -> Result<Option<SavedList>> {
+        let Some(row) = sqlx::query(
+            "SELECT id, name, created_at, updated_at FROM grocery_lists WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await? else {
+            return Ok(None);
+        };
+
+        let grocery_list = sqlx::query(
+            "SELECT barcode, cluster_id, name, quantity FROM grocery_list_items WHERE list_id = $1 ORDER BY position"
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|r| GroceryListEntry {
+            barcode: r.get("barcode"),
+            cluster_id: r.get("cluster_id"),
+            name: r.get("name"),
+            quantity: r.get::<i32, _>("quantity") as u32,
+        })
+        .collect();
+
+        Ok(Some(SavedList {
+            id: row.get("id"),
+            name: row.get("name"),
+            grocery_list,
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    pub async fn create_saved_list(&self, user_id: i64, list: &SavedListInput) -> Result<SavedList> {
+        let mut tx = self.pool.begin().await?;
+        let id: Uuid = sqlx::query_scalar("INSERT INTO grocery_lists (user_id, name) VALUES ($1, $2) RETURNING id")
+            .bind(user_id)
+            .bind(&list.name)
+            .fetch_one(&mut *tx)
+            .await?;
+        insert_list_items(&mut tx, id, &list.grocery_list).await?;
+        tx.commit().await?;
+        self.saved_list(user_id, id).await?.context("saved list vanished after insert")
+    }
+
+    /// Replaces a list's name and items; None if it doesn't exist or isn't the user's.
+    pub async fn replace_saved_list(&self, user_id: i64, id: Uuid, list: &SavedListInput) -> Result<Option<SavedList>> {
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query(
+            "UPDATE grocery_lists SET name = $3, updated_at = NOW() WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&list.name)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(None);
+        }
+        sqlx::query("DELETE FROM grocery_list_items WHERE list_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        insert_list_items(&mut tx, id, &list.grocery_list).await?;
+        tx.commit().await?;
+        self.saved_list(user_id, id).await
+    }
+
+    /// False if the list doesn't exist or isn't the user's.
+    pub async fn delete_saved_list(&self, user_id: i64, id: Uuid) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM grocery_lists WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+}
+
+/// Short-link shares.
+impl DatabaseManager {
+    /// Stores a new share under `id`; None if that ID is already taken.
+    pub async fn create_share(
+        &self,
+        id: &str,
+        name: Option<&str>,
+        grocery_list: &[GroceryListEntry],
+        editable: bool,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<SharedList>> {
+        let row = sqlx::query(
+            "INSERT INTO shared_lists (id, name, grocery_list, editable, expires_at) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (id) DO NOTHING \
+             RETURNING id, name, grocery_list, editable, created_at, updated_at, expires_at"
+        )
+        .bind(id)
+        .bind(name)
+        .bind(sqlx::types::Json(grocery_list))
+        .bind(editable)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(shared_list_from_row))
+    }
+
+    /// A share by ID, expired or not.
+    pub async fn get_share(&self, id: &str) -> Result<Option<SharedList>> {
+        let row = sqlx::query(
+            "SELECT id, name, grocery_list, editable, created_at, updated_at, expires_at \
+             FROM shared_lists WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(shared_list_from_row))
+    }
+
+    /// Overwrites an editable, unexpired share; None if there's no such share.
+    pub async fn update_share(
+        &self,
+        id: &str,
+        name: Option<&str>,
+        grocery_list: &[GroceryListEntry],
+    ) -> Result<Option<SharedList>> {
+        let row = sqlx::query(
+            "UPDATE shared_lists SET name = $2, grocery_list = $3, updated_at = NOW() \
+             WHERE id = $1 AND editable AND (expires_at IS NULL OR expires_at > NOW()) \
+             RETURNING id, name, grocery_list, editable, created_at, updated_at, expires_at"
+        )
+        .bind(id)
+        .bind(name)
+        .bind(sqlx::types::Json(grocery_list))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(shared_list_from_row))
+    }
+
+    /// Deletes shares that expired more than `grace` ago (recently expired ones
+    /// still answer "expired" rather than "not found").
+    pub async fn purge_expired_shares(&self, grace: chrono::Duration) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM shared_lists WHERE expires_at < $1")
+            .bind(Utc::now() - grace)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn shared_list_from_row(row: sqlx::postgres::PgRow) -> SharedList {
+    let sqlx::types::Json(grocery_list) = row.get("grocery_list");
+    SharedList {
+        id: row.get("id"),
+        name: row.get("name"),
+        grocery_list,
+        editable: row.get("editable"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        expires_at: row.get("expires_at"),
+    }
+}
+
+async fn insert_list_items(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    list_id: Uuid,
+    items: &[GroceryListEntry],
+) -> Result<()> {
+    let positions: Vec<i32> = (0..items.len() as i32).collect();
+    let barcodes: Vec<Option<String>> = items.iter().map(|i| i.barcode.clone()).collect();
+    let cluster_ids: Vec<Option<i32>> = items.iter().map(|i| i.cluster_id).collect();
+    let names: Vec<String> = items.iter().map(|i| i.name.clone()).collect();
+    let quantities: Vec<i32> = items.iter().map(|i| i.quantity as i32).collect();
+    sqlx::query(
+        "INSERT INTO grocery_list_items (list_id, position, barcode, cluster_id, name, quantity) \
+         SELECT $1, * FROM unnest($2::int4[], $3::text[], $4::int4[], $5::text[], $6::int4[])"
+    )
+    .bind(list_id)
+    .bind(&positions)
+    .bind(&barcodes)
+    .bind(&cluster_ids)
+    .bind(&names)
+    .bind(&quantities)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Search synonyms.
+impl DatabaseManager {
+    /// Each term's normalized form followed by its synonym expansions.
+    async fn term_variants(&self, terms: &[&String]) -> Result<Vec<Vec<String>>> {
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+        let normalized: Vec<String> = terms.iter().map(|t| hebrew::normalize(t)).collect();
+        // Rules whose side appears in some term as whole words; `synonyms::expand`
+        // does the exact matching.
+        let rows = sqlx::query(
+            "SELECT term_normalized AS from_words, alias_normalized AS to_words \
+             FROM search_synonyms s \
+             WHERE EXISTS (SELECT 1 FROM unnest($1::text[]) AS t(term) \
+                           WHERE ' ' || t.term || ' ' LIKE '% ' || s.term_normalized || ' %') \
+             UNION \
+             SELECT alias_normalized, term_normalized \
+             FROM search_synonyms s \
+             WHERE s.bidirectional \
+               AND EXISTS (SELECT 1 FROM unnest($1::text[]) AS t(term) \
+                           WHERE ' ' || t.term || ' ' LIKE '% ' || s.alias_normalized || ' %')"
+        )
+        .bind(&normalized)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut rules: Vec<SynonymRule> = rows.into_iter().map(|r| (r.get("from_words"), r.get("to_words"))).collect();
+        // Longest phrases first: they're the most specific, so they go before the variant cap.
+        rules.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.cmp(b)));
+        Ok(terms.iter().map(|t| synonyms::expand(t, &rules)).collect())
+    }
+
+    /// LIKE patterns for a free-text query and its synonym expansions.
+    async fn search_patterns(&self, query: &str) -> Result<Vec<String>> {
+        let query = query.to_string();
+        let variants = self.term_variants(&[&query]).await?;
+        Ok(variant_patterns(&variants[0]))
+    }
+
+    pub async fn list_synonyms(&self) -> Result<Vec<SearchSynonym>> {
+        let rows = sqlx::query(
+            "SELECT id, term, alias, bidirectional, created_at FROM search_synonyms ORDER BY term, alias"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(synonym_from_row).collect())
+    }
+
+    /// Adds a rule; None if the same (normalized) rule exists.
+    pub async fn create_synonym(&self, input: &SynonymInput) -> Result<Option<SearchSynonym>> {
+        let row = sqlx::query(
+            "INSERT INTO search_synonyms (term, alias, bidirectional) VALUES ($1, $2, $3) \
+             ON CONFLICT (term_normalized, alias_normalized) DO NOTHING \
+             RETURNING id, term, alias, bidirectional, created_at"
+        )
+        .bind(&input.term)
+        .bind(&input.alias)
+        .bind(input.bidirectional)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(synonym_from_row))
+    }
+
+    /// Whether a rule other than `except_id` already normalizes to the same term and alias.
+    pub async fn synonym_taken(&self, input: &SynonymInput, except_id: Option<i32>) -> Result<bool> {
+        Ok(sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM search_synonyms \
+                            WHERE term_normalized = normalize_hebrew($1) \
+                              AND alias_normalized = normalize_hebrew($2) \
+                              AND ($3::int4 IS NULL OR id <> $3))"
+        )
+        .bind(&input.term)
+        .bind(&input.alias)
+        .bind(except_id)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Rewrites a rule; None if there's no such rule.
+    pub async fn update_synonym(&self, id: i32, input: &SynonymInput) -> Result<Option<SearchSynonym>> {
+        let row = sqlx::query(
+            "UPDATE search_synonyms SET term = $2, alias = $3, bidirectional = $4 WHERE id = $1 \
+             RETURNING id, term, alias, bidirectional, created_at"
+        )
+        .bind(id)
+        .bind(&input.term)
+        .bind(&input.alias)
+        .bind(input.bidirectional)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(synonym_from_row))
+    }
+
+    pub async fn delete_synonym(&self, id: i32) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM search_synonyms WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+}
+
+fn synonym_from_row(row: sqlx::postgres::PgRow) -> SearchSynonym {
+    SearchSynonym {
+        id: row.get("id"),
+        term: row.get("term"),
+        alias: row.get("alias"),
+        bidirectional: row.get("bidirectional"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Product clusters: cross-chain identity for non-catalog items.
+impl DatabaseManager {
+    /// Re-clusters every non-catalog item (see `clustering`). Cluster IDs are kept for
+    /// unchanged identities; automatic members are rewritten, overrides kept — an item
+    /// confirmed elsewhere or rejected from a cluster isn't re-added to it.
+    pub async fn rebuild_product_clusters(&self) -> Result<ClusterRebuildSummary> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT ON (s.chain_id, i.item_code) \
+                    s.chain_id, i.item_code, i.item_name, i.manufacturer_name, \
+                    i.quantity, i.unit_qty, i.unit_of_measure \
+             FROM items i \
+             JOIN stores s ON s.id = i.store_pk \
+             WHERE NOT EXISTS (SELECT 1 FROM products p WHERE p.barcode = i.item_code) \
+             ORDER BY s.chain_id, i.item_code, i.price_update_date DESC NULLS LAST"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let items: Vec<ChainItem> = rows
+            .into_iter()
+            .map(|row| ChainItem {
+                chain_id: row.get("chain_id"),
+                item_code: row.get("item_code"),
+                item_name: row.get("item_name"),
+                manufacturer: row.get("manufacturer_name"),
+                size: normalized_size(row.get("quantity"), &[row.get("unit_qty"), row.get("unit_of_measure")]),
+            })
+            .collect();
+        let proposals = cluster_items(items);
+
+        let mut keys = Vec::with_capacity(proposals.len());
+        let mut names = Vec::with_capacity(proposals.len());
+        let mut manufacturers = Vec::with_capacity(proposals.len());
+        let mut size_amounts = Vec::with_capacity(proposals.len());
+        let mut size_units = Vec::with_capacity(proposals.len());
+        let (mut member_keys, mut chain_ids, mut item_codes, mut item_names, mut confidences) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for proposal in proposals {
+            for member in proposal.members {
+                member_keys.push(proposal.key.clone());
+                chain_ids.push(member.chain_id);
+                item_codes.push(member.item_code);
+                item_names.push(member.item_name);
+                confidences.push(member.confidence);
+            }
+            keys.push(proposal.key);
+            names.push(proposal.name);
+            manufacturers.push(proposal.manufacturer);
+            size_amounts.push(proposal.size.map(|s| s.amount));
+            size_units.push(proposal.size.map(|s| s.unit.as_str()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO product_clusters (cluster_key, name, manufacturer, size_amount, size_unit) \
+             SELECT * FROM unnest($1::text[], $2::text[], $3::text[], $4::float8[], $5::text[]) \
+             ON CONFLICT (cluster_key) DO UPDATE SET \
+                 name = EXCLUDED.name, manufacturer = EXCLUDED.manufacturer, \
+                 size_amount = EXCLUDED.size_amount, size_unit = EXCLUDED.size_unit, updated_at = NOW()"
+        )
+        .bind(&keys)
+        .bind(&names)
+        .bind(&manufacturers)
+        .bind(&size_amounts)
+        .bind(&size_units)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM product_cluster_members WHERE status = 'auto'")
+            .execute(&mut *tx)
+            .await?;
+        // Conflicts are the overrides: the item confirmed in some cluster, or rejected from this one.
+        let auto_members = sqlx::query(
+            "INSERT INTO product_cluster_members (cluster_id, chain_id, item_code, item_name, confidence) \
+             SELECT c.id, m.chain_id, m.item_code, m.item_name, m.confidence \
+             FROM unnest($1::text[], $2::text[], $3::text[], $4::text[], $5::float8[]) \
+                  AS m(cluster_key, chain_id, item_code, item_name, confidence) \
+             JOIN product_clusters c ON c.cluster_key = m.cluster_key \
+             ON CONFLICT DO NOTHING"
+        )
+        .bind(&member_keys)
+        .bind(&chain_ids)
+        .bind(&item_codes)
+        .bind(&item_names)
+        .bind(&confidences)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query(
+            "DELETE FROM product_clusters c \
+             WHERE NOT EXISTS (SELECT 1 FROM product_cluster_members m WHERE m.cluster_id = c.id)"
+        )
+        .execute(&mut *tx)
+        .await?;
+        let clusters: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM product_clusters")
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(ClusterRebuildSummary { clusters: clusters as usize, auto_members: auto_members as usize })
+    }
+
+    /// A page of clusters, most members first, optionally filtered by name.
+    pub async fn product_clusters_page(&self, query: Option<&str>, page: usize, limit: usize) -> Result<(Vec<ProductCluster>, usize)> {
+        let patterns = hebrew::like_patterns(query.unwrap_or_default());
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM product_clusters WHERE normalize_hebrew(name) LIKE ANY($1)"
+        )
+        .bind(&patterns)
+        .fetch_one(&self.pool)
+        .await?;
+        let ids: Vec<i32> = sqlx::query_scalar(
+            "SELECT c.id FROM product_clusters c \
+             WHERE normalize_hebrew(c.name) LIKE ANY($1) \
+             ORDER BY (SELECT COUNT(*) FROM product_cluster_members m \
+                       WHERE m.cluster_id = c.id AND m.status <> 'rejected') DESC, c.id \
+             LIMIT $2 OFFSET $3"
+        )
+        .bind(&patterns)
+        .bind(limit as i64)
+        .bind(((page.saturating_sub(1)) * limit) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok((self.product_clusters(&ids).await?, total as usize))
+    }
+
+    pub async fn product_cluster(&self, id: i32) -> Result<Option<ProductCluster>> {
+        Ok(self.product_clusters(&[id]).await?.pop())
+    }
+
+    /// Clusters by ID with their members, in the order given; unknown IDs are skipped.
+    async fn product_clusters(&self, ids: &[i32]) -> Result<Vec<ProductCluster>> {
+        let rows = sqlx::query(
+            "SELECT id, name, manufacturer, size_amount, size_unit FROM product_clusters WHERE id = ANY($1)"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut members: HashMap<i32, Vec<ClusterMember>> = HashMap::new();
+        for row in sqlx::query(
+            "SELECT cluster_id, chain_id, item_code, item_name, confidence, status \
+             FROM product_cluster_members WHERE cluster_id = ANY($1) \
+             ORDER BY status = 'rejected', confidence DESC, chain_id, item_code"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            members.entry(row.get("cluster_id")).or_default().push(cluster_member_from_row(row));
+        }
+
+        let mut by_id: HashMap<i32, ProductCluster> = rows
+            .into_iter()
+            .map(|row| {
+                let id: i32 = row.get("id");
+                let size_unit: Option<String> = row.get("size_unit");
+                (id, ProductCluster {
+                    id,
+                    name: row.get("name"),
+                    manufacturer: row.get("manufacturer"),
+                    size_amount: row.get("size_amount"),
+                    size_unit: size_unit.as_deref().and_then(BaseUnit::from_name),
+                    members: members.remove(&id).unwrap_or_default(),
+                })
+            })
+            .collect();
+        Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+    }
+
+    /// Records an operator override. Confirming moves the item out of any other
+    /// cluster. None if the cluster doesn't exist or the chain doesn't carry the item.
+    pub async fn set_cluster_member_status(
+        &self,
+        cluster_id: i32,
+        chain_id: &str,
+        item_code: &str,
+        status: MemberStatus,
+    ) -> Result<Option<ClusterMember>> {
+        let mut tx = self.pool.begin().await?;
+        let item_name: Option<String> = sqlx::query_scalar(
+            "SELECT i.item_name FROM items i JOIN stores s ON s.id = i.store_pk \
+             WHERE s.chain_id = $1 AND i.item_code = $2 \
+               AND EXISTS (SELECT 1 FROM product_clusters WHERE id = $3) \
+             ORDER BY i.price_update_date DESC NULLS LAST LIMIT 1"
+        )
+        .bind(chain_id)
+        .bind(item_code)
+        .bind(cluster_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(item_name) = item_name else {
+            return Ok(None);
+        };
+
+        if status == MemberStatus::Confirmed {
+            sqlx::query(
+                "DELETE FROM product_cluster_members \
+                 WHERE chain_id = $1 AND item_code = $2 AND cluster_id <> $3 AND status <> 'rejected'"
+            )
+            .bind(chain_id)
+            .bind(item_code)
+            .bind(cluster_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        let row = sqlx::query(
+            "INSERT INTO product_cluster_members (cluster_id, chain_id, item_code, item_name, confidence, status) \
+             VALUES ($1, $2, $3, $4, CASE WHEN $5 = 'confirmed' THEN 1.0 ELSE 0.0 END, $5) \
+             ON CONFLICT (cluster_id, chain_id, item_code) DO UPDATE SET \
+                 status = EXCLUDED.status, updated_at = NOW(), \
+                 confidence = CASE WHEN EXCLUDED.status = 'confirmed' THEN 1.0 \
+                                   ELSE product_cluster_members.confidence END \
+             RETURNING chain_id, item_code, item_name, confidence, status"
+        )
+        .bind(cluster_id)
+        .bind(chain_id)
+        .bind(item_code)
+        .bind(&item_name)
+        .bind(status.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(cluster_member_from_row(row)))
+    }
+
+    /// Drops a member row, override or not; the next rebuild decides afresh.
+    pub async fn clear_cluster_member(&self, cluster_id: i32, chain_id: &str, item_code: &str) -> Result<bool> {
+        let deleted = sqlx::query(
+            "DELETE FROM product_cluster_members WHERE cluster_id = $1 AND chain_id = $2 AND item_code = $3"
+        )
+        .bind(cluster_id)
+        .bind(chain_id)
+        .bind(item_code)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// The subset of `ids` that are existing clusters.
+    pub async fn known_clusters(&self, ids: &[i32]) -> Result<HashSet<i32>> {
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let found: Vec<i32> = sqlx::query_scalar("SELECT id FROM product_clusters WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(found.into_iter().collect())
+    }
+}
+
+fn cluster_member_from_row(row: sqlx::postgres::PgRow) -> ClusterMember {
+    ClusterMember {
+        chain_id: row.get("chain_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        confidence: row.get("confidence"),
+        status: MemberStatus::from_db(row.get("status")),
+    }
+}
+
+/// Product categories.
+impl DatabaseManager {
+    /// The whole category tree, each parent followed by its children.
+    pub async fn categories(&self) -> Result<Vec<Category>> {
+        let rows = sqlx::query(
+            "SELECT id, slug, name, parent_id FROM categories \
+             ORDER BY COALESCE(parent_id, id), parent_id IS NOT NULL, name"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Category {
+                id: row.get("id"),
+                slug: row.get("slug"),
+                name: row.get("name"),
+                parent_id: row.get("parent_id"),
+            })
+            .collect())
+    }
+
+    /// A category's ID and all its descendants', or None for an unknown slug.
+    pub async fn category_subtree(&self, slug: &str) -> Result<Option<Vec<i32>>> {
+        let ids: Vec<i32> = sqlx::query_scalar(
+            "WITH RECURSIVE tree AS ( \
+                SELECT id FROM categories WHERE slug = $1 \
+                UNION \
+                SELECT c.id FROM categories c JOIN tree t ON c.parent_id = t.id \
+             ) \
+             SELECT id FROM tree"
+        )
+        .bind(slug)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok((!ids.is_empty()).then_some(ids))
+    }
+
+    pub async fn category_exists(&self, id: i32) -> Result<bool> {
+        Ok(sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM categories WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?)
+    }
+
+    pub async fn category_rules(&self) -> Result<Vec<CategoryRule>> {
+        let rows = sqlx::query(
+            "SELECT id, category_id, kind, pattern, priority FROM category_rules ORDER BY category_id, kind, pattern"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(category_rule_from_row).collect())
+    }
+
+    /// Adds a rule; None if a rule of the same kind already has this (normalized) pattern.
+    /// Existing items are only affected by the next reclassification.
+    pub async fn create_category_rule(&self, input: &CategoryRuleInput) -> Result<Option<CategoryRule>> {
+        let row = sqlx::query(
+            "INSERT INTO category_rules (category_id, kind, pattern, priority) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (kind, pattern_normalized) DO NOTHING \
+             RETURNING id, category_id, kind, pattern, priority"
+        )
+        .bind(input.category_id)
+        .bind(input.kind.as_str())
+        .bind(input.pattern.trim())
+        .bind(input.priority)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(category_rule_from_row))
+    }
+
+    pub async fn delete_category_rule(&self, id: i32) -> Result<bool> {
+        let deleted = sqlx::query("DELETE FROM category_rules WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    async fn classifier(&self) -> Result<Classifier> {
+        Ok(Classifier::new(self.category_rules().await?))
+    }
+
+    /// Classifies a chain's freshly ingested items by rule. Items that already have a
+    /// category — by rule or pinned — keep it. Returns how many were classified.
+    pub async fn classify_new_items(&self, chain_id: &str, items: &[crate::models::Item]) -> Result<usize> {
+        let classifier = self.classifier().await?;
+        let (mut item_codes, mut category_ids) = (Vec::new(), Vec::new());
+        for item in items {
+            if let Some(category_id) =
+                classifier.classify(&item.item_code, &item.item_name, item.manufacturer_name.as_deref())
+            {
+                item_codes.push(item.item_code.clone());
+                category_ids.push(category_id);
+            }
+        }
+        let inserted = sqlx::query(
+            "INSERT INTO item_categories (chain_id, item_code, category_id) \
+             SELECT $1, * FROM unnest($2::text[], $3::int4[]) \
+             ON CONFLICT (chain_id, item_code) DO NOTHING"
+        )
+        .bind(chain_id)
+        .bind(&item_codes)
+        .bind(&category_ids)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(inserted as usize)
+    }
+
+    /// Re-runs the rules over every item that isn't pinned, replacing earlier rule
+    /// results — for after rules change. `item_code` (and `chain_id`) narrow it down.
+    pub async fn reclassify_items(&self, chain_id: Option<&str>, item_code: Option<&str>) -> Result<ReclassifySummary> {
+        let classifier = self.classifier().await?;
+        let rows = sqlx::query(
+            "SELECT DISTINCT ON (s.chain_id, i.item_code) \
+                    s.chain_id, i.item_code, i.item_name, i.manufacturer_name \
+             FROM items i \
+             JOIN stores s ON s.id = i.store_pk \
+             WHERE ($1::text IS NULL OR s.chain_id = $1) \
+               AND ($2::text IS NULL OR i.item_code = $2) \
+               AND NOT EXISTS (SELECT 1 FROM item_categories ic \
+                               WHERE ic.chain_id = s.chain_id AND ic.item_code = i.item_code \
+                                 AND ic.source = 'manual') \
+             ORDER BY s.chain_id, i.item_code, i.price_update_date DESC NULLS LAST"
+        )
+        .bind(chain_id)
+        .bind(item_code)
+        .fetch_all(&self.pool)
+        .await?;
+        let total = rows.len();
+        let (mut chain_ids, mut item_codes, mut category_ids) = (Vec::new(), Vec::new(), Vec::new());
+        for row in rows {
+            let code: String = row.get("item_code");
+            let name: String = row.get("item_name");
+            let manufacturer: Option<String> = row.get("manufacturer_name");
+            if let Some(category_id) = classifier.classify(&code, &name, manufacturer.as_deref()) {
+                chain_ids.push(row.get::<String, _>("chain_id"));
+                item_codes.push(code);
+                category_ids.push(category_id);
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "DELETE FROM item_categories WHERE source = 'rule' \
+               AND ($1::text IS NULL OR chain_id = $1) AND ($2::text IS NULL OR item_code = $2)"
+        )
+        .bind(chain_id)
+        .bind(item_code)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO item_categories (chain_id, item_code, category_id) \
+             SELECT * FROM unnest($1::text[], $2::text[], $3::int4[]) \
+             ON CONFLICT (chain_id, item_code) DO NOTHING"
+        )
+        .bind(&chain_ids)
+        .bind(&item_codes)
+        .bind(&category_ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(ReclassifySummary { classified: category_ids.len(), unclassified: total - category_ids.len() })
+    }
+
+    /// Pins an item to a category in one chain, or in every chain carrying it when
+    /// `chain_id` is None. Returns the number of chain items pinned — 0 if no chain
+    /// (in scope) carries the code.
+    pub async fn pin_item_category(&self, item_code: &str, chain_id: Option<&str>, category_id: i32) -> Result<usize> {
+        let pinned = sqlx::query(
+            "INSERT INTO item_categories (chain_id, item_code, category_id, source) \
+             SELECT DISTINCT s.chain_id, i.item_code, $3, 'manual' \
+             FROM items i JOIN stores s ON s.id = i.store_pk \
+             WHERE i.item_code = $1 AND ($2::text IS NULL OR s.chain_id = $2) \
+             ON CONFLICT (chain_id, item_code) DO UPDATE SET \
+                 category_id = EXCLUDED.category_id, source = 'manual', updated_at = NOW()"
+        )
+        .bind(item_code)
+        .bind(chain_id)
+        .bind(category_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(pinned as usize)
+    }
+
+    /// Unpins an item (in one chain or all) and lets the rules classify it again.
+    /// False if it wasn't pinned.
+    pub async fn unpin_item_category(&self, item_code: &str, chain_id: Option<&str>) -> Result<bool> {
+        let unpinned = sqlx::query(
+            "DELETE FROM item_categories \
+             WHERE item_code = $1 AND ($2::text IS NULL OR chain_id = $2) AND source = 'manual'"
+        )
+        .bind(item_code)
+        .bind(chain_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if unpinned == 0 {
+            return Ok(false);
+        }
+        self.reclassify_items(chain_id, Some(item_code)).await?;
+        Ok(true)
+    }
+}
+
+fn category_rule_from_row(row: sqlx::postgres::PgRow) -> CategoryRule {
+    CategoryRule {
+        id: row.get("id"),
+        category_id: row.get("category_id"),
+        kind: RuleKind::from_db(row.get("kind")),
+        pattern: row.get("pattern"),
+        priority: row.get("priority"),
+    }
+}
+
+/// Canonical product names.
+impl DatabaseManager {
+    /// Re-elects the canonical name of every product with more than one name variant
+    /// (see `naming::elect`), updating only those whose winner changed.
+    pub async fn elect_canonical_names(&self) -> Result<NameElectionSummary> {
+        let rows = sqlx::query(
+            "SELECT v.barcode, v.chain_id, v.name, v.seen_count::int8 AS seen_count, p.canonical_name \
+             FROM product_name_variants v \
+             JOIN products p ON p.barcode = v.barcode \
+             WHERE v.barcode IN (SELECT barcode FROM product_name_variants \
+                                 GROUP BY barcode HAVING COUNT(*) > 1) \
+             ORDER BY v.barcode"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut products: Vec<(String, String, Vec<NameVariant>)> = Vec::new();
+        for row in rows {
+            let barcode: String = row.get("barcode");
+            if products.last().is_none_or(|(last, _, _)| *last != barcode) {
+                products.push((barcode, row.get("canonical_name"), Vec::new()));
+            }
+            if let Some((_, _, variants)) = products.last_mut() {
+                variants.push(NameVariant {
+                    chain_id: row.get("chain_id"),
+                    name: row.get("name"),
+                    seen_count: row.get("seen_count"),
+                });
+            }
+        }
+
+        let (mut barcodes, mut names) = (Vec::new(), Vec::new());
+        for (barcode, current, variants) in &products {
+            if let Some(elected) = naming::elect(variants, &self.trusted_name_chains)
+                && elected != *current
+            {
+                barcodes.push(barcode.clone());
+                names.push(elected);
+            }
+        }
+        let renamed = sqlx::query(
+            "UPDATE products p SET canonical_name = n.name \
+             FROM unnest($1::text[], $2::text[]) AS n(barcode, name) \
+             WHERE p.barcode = n.barcode"
+        )
+        .bind(&barcodes)
+        .bind(&names)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(NameElectionSummary { products: products.len(), renamed: renamed as usize })
+    }
+
+    /// A product's canonical name and every chain's name for it, or None if the
+    /// barcode isn't in the catalog.
+    pub async fn product_names(&self, barcode: &str) -> Result<Option<ProductNames>> {
+        let canonical_name: Option<String> =
+            sqlx::query_scalar("SELECT canonical_name FROM products WHERE barcode = $1")
+                .bind(barcode)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some(canonical_name) = canonical_name else {
+            return Ok(None);
+        };
+        let variants = sqlx::query(
+            "SELECT v.chain_id, cn.display_name AS chain_name, v.name, \
+                    v.seen_count::int8 AS seen_count, v.last_seen_at \
+             FROM product_name_variants v \
+             LEFT JOIN chain_names cn ON cn.chain_id = v.chain_id \
+             WHERE v.barcode = $1 \
+             ORDER BY v.seen_count DESC, v.chain_id, v.name"
+        )
+        .bind(barcode)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| ProductNameVariant {
+            chain_id: row.get("chain_id"),
+            chain_name: row.get("chain_name"),
+            name: row.get("name"),
+            seen_count: row.get("seen_count"),
+            last_seen_at: row.get("last_seen_at"),
+        })
+        .collect();
+        Ok(Some(ProductNames { barcode: barcode.to_string(), canonical_name, variants }))
+    }
+}
+
+/// Manufacturers and brand statistics.
+impl DatabaseManager {
+    /// Maps `manufacturer_name` spellings without an alias to a manufacturer (see
+    /// `brands`), founding manufacturers for spellings that resolve to none.
+    /// Returns how many spellings were new.
+    pub async fn register_manufacturers(&self, raw_names: &[String]) -> Result<usize> {
+        let missing: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT n FROM unnest($1::text[]) AS n \
+             WHERE NOT EXISTS (SELECT 1 FROM manufacturer_aliases ma WHERE ma.alias = n)"
+        )
+        .bind(raw_names)
+        .fetch_all(&self.pool)
+        .await?;
+        if missing.is_empty() {
+            return Ok(0);
+        }
+
+        let mut known: Vec<(String, i32)> = sqlx::query("SELECT name_key, id FROM manufacturers")
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| (row.get("name_key"), row.get("id")))
+            .collect();
+        let aliases = sqlx::query(
+            "SELECT alias, manufacturer_id FROM manufacturer_aliases WHERE manufacturer_id IS NOT NULL"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in aliases {
+            if let Some(key) = brands::manufacturer_key(row.get("alias")) {
+                known.push((key, row.get("manufacturer_id")));
+            }
+        }
+
+        let mut manufacturer_ids: Vec<Option<i32>> = Vec::with_capacity(missing.len());
+        for raw in &missing {
+            let Some(key) = brands::manufacturer_key(raw) else {
+                manufacturer_ids.push(None);
+                continue;
+            };
+            let id = match brands::resolve(&key, &known) {
+                Some(id) => id,
+                None => {
+                    let name: String = raw.trim().chars().take(100).collect();
+                    // A concurrent ingest may have founded it meanwhile.
+                    sqlx::query_scalar(
+                        "INSERT INTO manufacturers (name, name_key) VALUES ($1, $2) \
+                         ON CONFLICT (name_key) DO UPDATE SET name_key = EXCLUDED.name_key \
+                         RETURNING id"
+                    )
+                    .bind(&name)
+                    .bind(&key)
+                    .fetch_one(&self.pool)
+                    .await?
+                }
+            };
+            known.push((key, id));
+            manufacturer_ids.push(Some(id));
+        }
+
+        let inserted = sqlx::query(
+            "INSERT INTO manufacturer_aliases (alias, manufacturer_id) \
+             SELECT * FROM unnest($1::text[], $2::int4[]) \
+             ON CONFLICT (alias) DO NOTHING"
+        )
+        .bind(&missing)
+        .bind(&manufacturer_ids)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(inserted as usize)
+    }
+
+    /// Registers every `manufacturer_name` in `items` that has no alias yet — catches
+    /// up after files ingested before the manufacturer table existed.
+    pub async fn sync_manufacturers(&self) -> Result<usize> {
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT i.manufacturer_name FROM items i \
+             WHERE i.manufacturer_name IS NOT NULL \
+               AND NOT EXISTS (SELECT 1 FROM manufacturer_aliases ma WHERE ma.alias = i.manufacturer_name)"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        self.register_manufacturers(&names).await
+    }
+
+    pub async fn manufacturer_exists(&self, id: i32) -> Result<bool> {
+        Ok(sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM manufacturers WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?)
+    }
+
+    /// Maps a spelling to a manufacturer (or to none), overriding what ingest resolved.
+    pub async fn set_manufacturer_alias(&self, alias: &str, manufacturer_id: Option<i32>) -> Result<ManufacturerAlias> {
+        let row = sqlx::query(
+            "INSERT INTO manufacturer_aliases (alias, manufacturer_id, source) VALUES ($1, $2, 'manual') \
+             ON CONFLICT (alias) DO UPDATE SET manufacturer_id = EXCLUDED.manufacturer_id, source = 'manual' \
+             RETURNING alias, manufacturer_id, source"
+        )
+        .bind(alias)
+        .bind(manufacturer_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(ManufacturerAlias {
+            alias: row.get("alias"),
+            manufacturer_id: row.get("manufacturer_id"),
+            source: row.get("source"),
+        })
+    }
+
+    /// Moves every alias of manufacturer `from` to `into` and deletes `from`.
+    /// None if either manufacturer doesn't exist.
+    pub async fn merge_manufacturers(&self, from: i32, into: i32) -> Result<Option<usize>> {
+        let mut tx = self.pool.begin().await?;
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM manufacturers WHERE id IN ($1, $2)")
+            .bind(from)
+            .bind(into)
+            .fetch_one(&mut *tx)
+            .await?;
+        if found < 2 {
+            return Ok(None);
+        }
+        let moved = sqlx::query("UPDATE manufacturer_aliases SET manufacturer_id = $2 WHERE manufacturer_id = $1")
+            .bind(from)
+            .bind(into)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        // Keep the merged name resolving to the survivor for future spellings.
+        sqlx::query(
+            "INSERT INTO manufacturer_aliases (alias, manufacturer_id, source) \
+             SELECT name, $2, 'manual' FROM manufacturers WHERE id = $1 \
+             ON CONFLICT (alias) DO NOTHING"
+        )
+        .bind(from)
+        .bind(into)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM manufacturers WHERE id = $1")
+            .bind(from)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(moved as usize))
+    }
+
+    /// A brand with its spellings and per-chain price statistics, or None for an
+    /// unknown ID. Each chain's price for a product is its average over the chain's
+    /// stores; the price index compares chains over barcoded products at least two
+    /// chains carry.
+    pub async fn brand_detail(&self, id: i32) -> Result<Option<BrandDetail>> {
+        let name: Option<String> = sqlx::query_scalar("SELECT name FROM manufacturers WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        let aliases: Vec<String> = sqlx::query_scalar(
+            "SELECT alias FROM manufacturer_aliases WHERE manufacturer_id = $1 ORDER BY alias"
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            "WITH brand_items AS ( \
+                 SELECT s.chain_id, i.item_code, AVG(i.item_price)::float8 AS price \
+                 FROM items i \
+                 JOIN stores s ON s.id = i.store_pk \
+                 JOIN manufacturer_aliases ma ON ma.alias = i.manufacturer_name \
+                 WHERE ma.manufacturer_id = $1 \
+                 GROUP BY s.chain_id, i.item_code \
+             ), cross_chain AS ( \
+                 SELECT b.item_code, AVG(b.price) AS avg_price \
+                 FROM brand_items b JOIN products p ON p.barcode = b.item_code \
+                 GROUP BY b.item_code \
+                 HAVING COUNT(*) > 1 \
+             ) \
+             SELECT b.chain_id, cn.display_name AS chain_name, COUNT(*) AS item_count, \
+                    AVG(b.price)::float8 AS avg_price, MIN(b.price)::float8 AS min_price, \
+                    MAX(b.price)::float8 AS max_price, COUNT(c.item_code) AS shared_items, \
+                    COALESCE(SUM(b.price) FILTER (WHERE c.item_code IS NOT NULL), 0)::float8 AS chain_total, \
+                    COALESCE(SUM(c.avg_price), 0)::float8 AS cross_total \
+             FROM brand_items b \
+             LEFT JOIN cross_chain c ON c.item_code = b.item_code \
+             LEFT JOIN chain_names cn ON cn.chain_id = b.chain_id \
+             GROUP BY b.chain_id, cn.display_name"
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut chains: Vec<BrandChainStats> = rows
+            .into_iter()
+            .map(|row| {
+                let shared_items: i64 = row.get("shared_items");
+                BrandChainStats {
+                    chain_id: row.get("chain_id"),
+                    chain_name: row.get("chain_name"),
+                    item_count: row.get("item_count"),
+                    avg_price: row.get("avg_price"),
+                    min_price: row.get("min_price"),
+                    max_price: row.get("max_price"),
+                    shared_items,
+                    price_index: brand_price_index(row.get("chain_total"), row.get("cross_total"), shared_items),
+                }
+            })
+            .collect();
+        // Indexed chains cheapest first, then the rest by how much of the brand they carry.
+        chains.sort_by(|a, b| match (a.price_index, b.price_index) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => b.item_count.cmp(&a.item_count),
+        });
+
+        Ok(Some(BrandDetail { id, name, aliases, chains }))
+    }
+}
+
+fn brand_facet_from_row(row: sqlx::postgres::PgRow) -> BrandFacet {
+    BrandFacet {
+        id: row.get("id"),
+        name: row.get("name"),
+        item_count: row.get("item_count"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brand_price_index, chain_local, is_ean13, price_level_index, price_stats, rank_substitutes, stale_cutoff_at, SubstituteCandidate};
+    use chrono::{NaiveDateTime, TimeZone, Utc};
+    use crate::models::{ItemPrice, ProductStorePrice, StoreInfo, Substitute, SubstituteReason};
+    use crate::units::{BaseUnit, Size};
+
+    #[test]
+    fn stale_cutoff_on_chain_clock() {
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        // IDT (UTC+3) in summer, IST (UTC+2) in winter.
+        let summer = chain_local(Utc.with_ymd_and_hms(2026, 7, 1, 9, 0, 0).unwrap());
+        assert_eq!(summer, local("2026-07-01 12:00"));
+        assert_eq!(chain_local(Utc.with_ymd_and_hms(2026, 1, 15, 9, 0, 0).unwrap()), local("2026-01-15 11:00"));
+        // A price stamped 11:30 local is half an hour old: fresh against a 1 h threshold.
+        assert_eq!(stale_cutoff_at(summer, 1.0), local("2026-07-01 11:00"));
+        assert!(local("2026-07-01 11:30") > stale_cutoff_at(summer, 1.0));
+        assert_eq!(stale_cutoff_at(summer, 1e12), NaiveDateTime::MIN);
+    }
+
+    #[test]
+    fn valid_ean13_barcodes() {
+        // EAN-13 codes with correct check digits
+        assert!(is_ean13("7290000066769"));
+        assert!(is_ean13("7290027600007"));
+        assert!(is_ean13("4006381333931"));
+    }
+
+    #[test]
+    fn wrong_check_digit_rejected() {
+        assert!(!is_ean13("7290000066768"));
+        assert!(!is_ean13("4006381333930"));
+    }
+
+    #[test]
+    fn wrong_length_rejected() {
+        assert!(!is_ean13(""));
+        assert!(!is_ean13("729000006676"));    // 12 digits
+        assert!(!is_ean13("72900000667680"));  // 14 digits
+    }
+
+    #[test]
+    fn non_digits_rejected() {
+        assert!(!is_ean13("729000006676a"));
+        assert!(!is_ean13("חלב תנובה 1 ל"));
+        assert!(!is_ean13("7290-00006676"));
+    }
+
+    fn listing(price: f64) -> ProductStorePrice {
+        ProductStorePrice {
+            store: StoreInfo {
+                id: 1,
+                chain_id: "7290027600007".to_string(),
+                sub_chain_id: 1,
+                store_id: 1,
+                store_name: None,
+                address: None,
+                city: None,
+                latitude: None,
+                longitude: None,
+                distance_km: None,
+                last_price_update: None,
+            },
+            price,
+            price_update_date: None,
+            is_stale: false,
+        }
+    }
+
+    #[test]
+    fn price_stats_span_listings() {
+        assert_eq!(price_stats(&[]), None);
+        let stats = price_stats(&[listing(6.0), listing(9.0), listing(4.5)]).unwrap();
+        assert_eq!((stats.min, stats.avg, stats.max, stats.store_count), (4.5, 6.5, 9.0, 3));
+    }
+
+    #[test]
+    fn price_level_needs_overlap() {
+        assert_eq!(price_level_index(90.0, 100.0, 19), None);
+        assert_eq!(price_level_index(90.0, 100.0, 20), Some(90.0));
+        assert_eq!(price_level_index(104.44, 100.0, 50), Some(104.4));
+        assert_eq!(price_level_index(10.0, 0.0, 50), None);
+    }
+
+    #[test]
+    fn brand_index_needs_a_few_shared_products() {
+        // Far below the store index's overlap, which a brand almost never reaches.
+        assert_eq!(brand_price_index(19.0, 20.0, 2), None);
+        assert_eq!(brand_price_index(19.0, 20.0, 3), Some(95.0));
+        assert_eq!(brand_price_index(19.0, 0.0, 5), None);
+    }
+
+    fn grams(amount: f64) -> Option<Size> {
+        Some(Size { amount, unit: BaseUnit::Gram })
+    }
+
+    fn candidate(code: &str, price: f64, similarity: f64, reason: SubstituteReason, size: Option<Size>) -> SubstituteCandidate {
+        let item = ItemPrice {
+            item_code: code.to_string(),
+            item_name: code.to_string(),
+            price,
+            unit_of_measure: None,
+            manufacturer_name: None,
+            price_update_date: None,
+            is_stale: false,
+            match_confidence: None,
+            alternatives: vec![],
+            term_index: 0,
+        };
+        (Substitute { item, reason, match_score: similarity, size_ratio: None }, size)
+    }
+
+    #[test]
+    fn substitutes_ranked_by_name_manufacturer_and_size() {
+        let millilitres = Some(Size { amount: 500.0, unit: BaseUnit::Milliliter });
+        let ranked = rank_substitutes(
+            vec![
+                candidate("similar", 10.0, 0.6, SubstituteReason::SimilarName, grams(500.0)),
+                candidate("same-brand", 12.0, 0.4, SubstituteReason::SameManufacturer, grams(500.0)),
+                candidate("too-big", 8.0, 0.9, SubstituteReason::SimilarName, grams(1500.0)),
+                candidate("unsized", 9.0, 0.5, SubstituteReason::SimilarName, None),
+                candidate("liquid", 9.0, 0.9, SubstituteReason::SimilarName, millilitres),
+            ],
+            grams(500.0),
+        );
+        let codes: Vec<&str> = ranked.iter().map(|s| s.item.item_code.as_str()).collect();
+        // 0.6 + 0.25 size; 0.4 + 0.25 size + 0.25 brand; 0.5 with no size bonus.
+        assert_eq!(codes, ["same-brand", "similar", "unsized"]);
+        assert_eq!(ranked[0].size_ratio, Some(1.0));
+        assert_eq!(ranked[2].size_ratio, None);
     }
 }
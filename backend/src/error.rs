@@ -0,0 +1,191 @@
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        Request,
+    },
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use tracing::error;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Middleware: tags every request with an ID (the caller's `X-Request-Id` if it
+/// sent a sane one, otherwise a fresh UUID), echoes it on the response, and makes
+/// it available to error bodies and logs for correlation.
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 64 && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// One invalid input field, as reported in a validation problem.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> Self {
+        Self { field: field.into(), code, message: message.into() }
+    }
+}
+
+/// Every failure an API handler can return. Serialized as RFC 7807
+/// `application/problem+json` with a machine-readable `code`.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400 — one or more input fields failed validation.
+    Validation(Vec<FieldError>),
+    /// 400 — the request couldn't be decoded at all (bad JSON, bad query string, bad path).
+    BadRequest { code: &'static str, message: String },
+    /// 401 — missing or wrong credentials on a protected route.
+    Unauthorized { code: &'static str, message: String },
+    /// 403 — authenticated (or anonymous) but not allowed to do this.
+    Forbidden { code: &'static str, message: String },
+    /// 404
+    NotFound { code: &'static str, message: String },
+    /// 409 — the request clashes with work already in progress.
+    Conflict { code: &'static str, message: String },
+    /// 500 — logged with the request ID; the client only sees a generic message.
+    Internal(anyhow::Error),
+}
+
+/// RFC 7807 problem details — the body of every API error response.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct Problem {
+    #[serde(rename = "type")]
+    type_uri: &'static str,
+    title: &'static str,
+    status: u16,
+    code: &'static str,
+    detail: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::NotFound { code, message: message.into() }
+    }
+
+    pub fn forbidden(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Forbidden { code, message: message.into() }
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Conflict { code, message: message.into() }
+    }
+
+    pub fn unauthorized(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Unauthorized { code, message: message.into() }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Validation(_) | Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            Self::Forbidden { .. } => StatusCode::FORBIDDEN,
+            Self::NotFound { .. } => StatusCode::NOT_FOUND,
+            Self::Conflict { .. } => StatusCode::CONFLICT,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let challenge = matches!(self, Self::Unauthorized { .. });
+        let request_id = current_request_id();
+        let (code, detail, errors) = match self {
+            Self::Validation(errors) => {
+                let detail = errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; ");
+                ("validation_failed", detail, errors)
+            }
+            Self::BadRequest { code, message }
+            | Self::Unauthorized { code, message }
+            | Self::Forbidden { code, message }
+            | Self::NotFound { code, message }
+            | Self::Conflict { code, message } => (code, message, vec![]),
+            Self::Internal(e) => {
+                error!(request_id = request_id.as_deref().unwrap_or("-"), "{:#}", e);
+                ("internal_error", "An internal error occurred".to_string(), vec![])
+            }
+        };
+
+        let body = Problem {
+            // Problems are told apart by `code`, so `type` stays the RFC 7807 default.
+            type_uri: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            code,
+            detail,
+            errors,
+            request_id,
+        };
+        let mut response = (status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+        if challenge {
+            response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::BadRequest { code: "invalid_json", message: rejection.body_text() }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::BadRequest { code: "invalid_query", message: rejection.body_text() }
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::BadRequest { code: "invalid_path", message: rejection.body_text() }
+    }
+}
+
+/// Turns a list of field errors into `Err(ApiError::Validation)` when non-empty.
+pub fn validate(errors: Vec<FieldError>) -> Result<(), ApiError> {
+    if errors.is_empty() { Ok(()) } else { Err(ApiError::Validation(errors)) }
+}
@@ -0,0 +1,308 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use chrono::{NaiveDateTime, Utc};
+use std::{env, sync::Arc, time::{Duration, Instant}};
+use tokio::sync::Mutex;
+use tracing::error;
+
+use crate::api::AppState;
+use crate::database::{chain_local_now, DatabaseManager};
+use crate::models::{
+    ChainDataAge, ChainFreshness, CheckStatus, HealthCheck, HealthDetails, PoolStatsResponse, ReadinessResponse,
+};
+use crate::xml_processor::{ingest_paused, pending_file_count, watcher_heartbeat};
+
+/// The watcher loop wakes every second; this much silence means the thread is gone.
+const WATCHER_MAX_SILENCE_SECS: i64 = 30;
+
+/// How long the freshness and backlog checks are reused. Both endpoints are public
+/// and those checks aggregate every store and list the watch directory, so probes
+/// share one run per interval instead of each paying for it.
+const DATA_CHECKS_TTL: Duration = Duration::from_secs(15);
+
+/// Last freshness and backlog results: (taken at, checks, per-chain ages).
+type DataChecks = (Instant, Vec<HealthCheck>, Vec<ChainDataAge>);
+
+static DATA_CHECKS: Mutex<Option<DataChecks>> = Mutex::const_new(None);
+
+/// Thresholds for readiness, read from the environment.
+///
+/// `DATA_STALE_AFTER_HOURS` (default 48) marks a chain stale; `/ready` fails only
+/// when *every* chain is stale. `READY_MAX_BACKLOG_FILES` (default 50) is how many
+/// unprocessed XML files may sit in the watch directory before health degrades.
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    pub watch_directory: String,
+    pub stale_after_hours: f64,
+    pub max_backlog_files: usize,
+}
+
+impl HealthConfig {
+    pub fn from_env(watch_directory: String) -> Self {
+        Self {
+            watch_directory,
+            stale_after_hours: env::var("DATA_STALE_AFTER_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|h: &f64| *h > 0.0)
+                .unwrap_or(48.0),
+            max_backlog_files: env::var("READY_MAX_BACKLOG_FILES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Liveness — the process is up"))
+)]
+pub async fn health_check() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "service": "shop-saver-api"
+    }))
+}
+
+/// Connection pool utilization, for spotting ingestion starving the API.
+#[utoipa::path(
+    get,
+    path = "/health/pool",
+    tag = "health",
+    responses((status = 200, body = PoolStatsResponse))
+)]
+pub async fn pool_stats(State(db): State<Arc<DatabaseManager>>) -> Json<PoolStatsResponse> {
+    Json(db.pool_stats())
+}
+
+/// GET /ready — 503 when the service can't serve meaningful comparisons
+/// (database unreachable, schema behind the binary, or no chain has fresh prices).
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready (possibly degraded)", body = ReadinessResponse),
+        (status = 503, description = "Not ready", body = ReadinessResponse),
+    )
+)]
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let (checks, _) = run_checks(&state).await;
+    let status = overall(&checks);
+    (status_code(status), Json(ReadinessResponse { status, checks }))
+}
+
+/// GET /health/details — every check plus per-chain data age and pool stats.
+#[utoipa::path(
+    get,
+    path = "/health/details",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready (possibly degraded)", body = HealthDetails),
+        (status = 503, description = "Not ready", body = HealthDetails),
+    )
+)]
+pub async fn health_details(State(state): State<AppState>) -> (StatusCode, Json<HealthDetails>) {
+    let (checks, chains) = run_checks(&state).await;
+    let status = overall(&checks);
+    (
+        status_code(status),
+        Json(HealthDetails {
+            status,
+            timestamp: Utc::now().to_rfc3339(),
+            checks,
+            chains,
+            pool: state.db.pool_stats(),
+        }),
+    )
+}
+
+/// Check details are public, so failures say what failed and the error itself only
+/// goes to the log. Connectivity, migrations and the watcher are checked live; the
+/// costlier data checks come from [`data_checks`].
+async fn run_checks(state: &AppState) -> (Vec<HealthCheck>, Vec<ChainDataAge>) {
+    let db = &state.db;
+    let mut checks = Vec::new();
+
+    if let Err(e) = db.ping().await {
+        error!("Health check: database unreachable: {}", e);
+        checks.push(check("database", CheckStatus::Failed, "unreachable".to_string()));
+        return (checks, vec![]);
+    }
+    checks.push(check("database", CheckStatus::Ok, "connected".to_string()));
+
+    checks.push(match db.migration_versions().await {
+        Ok((Some(applied), expected)) if applied == expected => {
+            check("migrations", CheckStatus::Ok, format!("at version {}", applied))
+        }
+        Ok((Some(applied), expected)) if applied > expected => check(
+            "migrations",
+            CheckStatus::Degraded,
+            format!("database at {}, binary expects {}", applied, expected),
+        ),
+        Ok((applied, expected)) => check(
+            "migrations",
+            CheckStatus::Failed,
+            format!("database at {:?}, binary expects {}", applied, expected),
+        ),
+        Err(e) => {
+            error!("Health check: cannot read migration ledger: {}", e);
+            check("migrations", CheckStatus::Failed, "cannot read ledger".to_string())
+        }
+    });
+
+    checks.push(match watcher_heartbeat() {
+        Some(beat) if (Utc::now() - beat).num_seconds() <= WATCHER_MAX_SILENCE_SECS => {
+            check("watcher", CheckStatus::Ok, format!("last heartbeat {}", beat.to_rfc3339()))
+        }
+        Some(beat) => check("watcher", CheckStatus::Degraded, format!("silent since {}", beat.to_rfc3339())),
+        None => check("watcher", CheckStatus::Degraded, "not running".to_string()),
+    });
+
+    let (data, chains) = data_checks(state).await;
+    checks.extend(data);
+
+    (checks, chains)
+}
+
+/// Data freshness and ingest backlog, rerun at most once per [`DATA_CHECKS_TTL`];
+/// concurrent callers wait for the one run in flight.
+async fn data_checks(state: &AppState) -> (Vec<HealthCheck>, Vec<ChainDataAge>) {
+    let mut cached = DATA_CHECKS.lock().await;
+    if let Some((taken, checks, chains)) = cached.as_ref()
+        && taken.elapsed() < DATA_CHECKS_TTL
+    {
+        return (checks.clone(), chains.clone());
+    }
+
+    let db = &state.db;
+    let config = &state.health;
+    let mut checks = Vec::new();
+
+    let chains = match db.chain_freshness().await {
+        Ok(freshness) => {
+            let chains = chain_ages(freshness, chain_local_now(), config.stale_after_hours);
+            checks.push(data_check(&chains, config.stale_after_hours));
+            chains
+        }
+        Err(e) => {
+            error!("Health check: freshness query failed: {}", e);
+            checks.push(check("data_freshness", CheckStatus::Failed, "query failed".to_string()));
+            vec![]
+        }
+    };
+
+    checks.push(match pending_file_count(db, &config.watch_directory).await {
+        Ok(n) if ingest_paused() => {
+            check("ingest_backlog", CheckStatus::Degraded, format!("ingestion paused, {} files pending", n))
+        }
+        Ok(n) if n <= config.max_backlog_files => {
+            check("ingest_backlog", CheckStatus::Ok, format!("{} files pending", n))
+        }
+        Ok(n) => check(
+            "ingest_backlog",
+            CheckStatus::Degraded,
+            format!("{} files pending (limit {})", n, config.max_backlog_files),
+        ),
+        Err(e) => {
+            error!("Health check: cannot scan watch directory: {}", e);
+            check("ingest_backlog", CheckStatus::Degraded, "cannot scan watch directory".to_string())
+        }
+    });
+
+    *cached = Some((Instant::now(), checks.clone(), chains.clone()));
+    (checks, chains)
+}
+
+/// Judges each chain's newest price against `stale_after_hours`. Chains with no
+/// prices at all count as stale.
+pub(crate) fn chain_ages(freshness: Vec<ChainFreshness>, now: NaiveDateTime, stale_after_hours: f64) -> Vec<ChainDataAge> {
+    freshness
+        .into_iter()
+        .map(|c| {
+            let age_hours = c
+                .last_price_update
+                .map(|ts| (now - ts).num_seconds() as f64 / 3600.0);
+            ChainDataAge {
+                chain_id: c.chain_id,
+                display_name: c.display_name,
+                last_price_update: c.last_price_update,
+                age_hours,
+                is_stale: age_hours.is_none_or(|h| h > stale_after_hours),
+            }
+        })
+        .collect()
+}
+
+fn data_check(chains: &[ChainDataAge], stale_after_hours: f64) -> HealthCheck {
+    let stale: Vec<&str> = chains.iter().filter(|c| c.is_stale).map(|c| c.chain_id.as_str()).collect();
+    if chains.is_empty() || stale.len() == chains.len() {
+        check(
+            "data_freshness",
+            CheckStatus::Failed,
+            format!("no chain has prices newer than {}h", stale_after_hours),
+        )
+    } else if !stale.is_empty() {
+        check(
+            "data_freshness",
+            CheckStatus::Degraded,
+            format!("stale (> {}h): {}", stale_after_hours, stale.join(", ")),
+        )
+    } else {
+        check("data_freshness", CheckStatus::Ok, format!("{} chains fresh", chains.len()))
+    }
+}
+
+fn check(name: &'static str, status: CheckStatus, detail: String) -> HealthCheck {
+    HealthCheck { name, status, detail }
+}
+
+fn overall(checks: &[HealthCheck]) -> CheckStatus {
+    checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Ok)
+}
+
+fn status_code(status: CheckStatus) -> StatusCode {
+    match status {
+        CheckStatus::Failed => StatusCode::SERVICE_UNAVAILABLE,
+        CheckStatus::Ok | CheckStatus::Degraded => StatusCode::OK,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn chain(id: &str, last: Option<&str>) -> ChainFreshness {
+        ChainFreshness { chain_id: id.to_string(), display_name: None, last_price_update: last.map(at) }
+    }
+
+    #[test]
+    fn stale_chains_flagged_against_threshold() {
+        let now = at("2025-06-05 12:00:00");
+        let ages = chain_ages(
+            vec![chain("a", Some("2025-06-05 06:00:00")), chain("b", Some("2025-06-02 12:00:00")), chain("c", None)],
+            now,
+            48.0,
+        );
+        assert_eq!(ages[0].age_hours, Some(6.0));
+        assert!(!ages[0].is_stale);
+        assert!(ages[1].is_stale);
+        assert!(ages[2].is_stale);
+        assert_eq!(data_check(&ages, 48.0).status, CheckStatus::Degraded);
+    }
+
+    #[test]
+    fn all_stale_fails_readiness() {
+        let now = at("2025-06-05 12:00:00");
+        let ages = chain_ages(vec![chain("a", Some("2025-06-01 00:00:00"))], now, 48.0);
+        assert_eq!(data_check(&ages, 48.0).status, CheckStatus::Failed);
+        assert_eq!(data_check(&[], 48.0).status, CheckStatus::Failed);
+    }
+}
@@ -0,0 +1,99 @@
+//! Hebrew-aware normalization for product search.
+//!
+//! `normalize` mirrors the `normalize_hebrew` SQL function in
+//! migrations/0006_normalized_names.sql, which fills the stored
+//! `item_name_normalized` / `canonical_name_normalized` columns at ingest. Search
+//! terms go through the same rules here, so the two must change together.
+
+/// Prefix letters ("the", "and", "in", "to") stripped from search words.
+const SEARCH_PREFIXES: [char; 4] = ['ה', 'ו', 'ב', 'ל'];
+/// Words shorter than this keep their first letter: in "לחם" or "בשר" it isn't a prefix.
+const MIN_PREFIXED_LEN: usize = 4;
+
+fn is_hebrew_letter(c: char) -> bool {
+    ('א'..='ת').contains(&c)
+}
+
+/// Lowercases; drops niqqud, cantillation and geresh/gershayim/quote marks; maps
+/// final letters to their regular forms and maqaf to a space; drops ו and י between
+/// two letters (so full and defective spellings meet); collapses whitespace.
+pub fn normalize(text: &str) -> String {
+    let chars: Vec<char> = text
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            '\u{0591}'..='\u{05BD}' | '\u{05BF}' | '\u{05C1}' | '\u{05C2}' | '\u{05C4}' | '\u{05C5}' | '\u{05C7}' => None,
+            '\'' | '"' | '`' | '׳' | '״' | '‘' | '’' | '“' | '”' => None,
+            'ך' => Some('כ'),
+            'ם' => Some('מ'),
+            'ן' => Some('נ'),
+            'ף' => Some('פ'),
+            'ץ' => Some('צ'),
+            '־' => Some(' '),
+            c => Some(c),
+        })
+        .collect();
+
+    let kept: String = chars
+        .iter()
+        .enumerate()
+        .filter(|&(i, &c)| {
+            let inner = i > 0 && is_hebrew_letter(chars[i - 1]) && chars.get(i + 1).is_some_and(|&n| is_hebrew_letter(n));
+            !(matches!(c, 'ו' | 'י') && inner)
+        })
+        .map(|(_, &c)| c)
+        .collect();
+    kept.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Substring LIKE patterns for a search term against the normalized columns: the
+/// normalized term and, when it differs, the same with prefix letters stripped from
+/// its words ("החלב" also finds "חלב"). Meant for `LIKE ANY(..)`.
+pub fn like_patterns(term: &str) -> Vec<String> {
+    let normalized = normalize(term);
+    let stripped = normalized
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) if SEARCH_PREFIXES.contains(&first) && word.chars().count() >= MIN_PREFIXED_LEN => chars.as_str(),
+                _ => word,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut patterns = vec![format!("%{}%", normalized)];
+    if stripped != normalized {
+        patterns.push(format!("%{}%", stripped));
+    }
+    patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spelling_variants_meet() {
+        assert_eq!(normalize("קוטג'"), normalize("קוטג׳"));
+        assert_eq!(normalize("קוטג'"), normalize("קוטג"));
+        assert_eq!(normalize("שוקולד"), normalize("שקולד"));
+        assert_eq!(normalize("גבינה"), normalize("גבנה"));
+        assert_eq!(normalize("חָלָב"), "חלב");
+        assert_eq!(normalize("עוף  טרי"), "עפ טרי");
+        assert_eq!(normalize("Milk 3%"), "milk 3%");
+        // A leading or trailing ו/י is kept: "חלבי" (dairy) isn't "חלב".
+        assert_ne!(normalize("חלבי"), normalize("חלב"));
+        assert_eq!(normalize("יין"), "ינ");
+    }
+
+    #[test]
+    fn prefixes_stripped_from_search_words() {
+        assert_eq!(like_patterns("החלב"), ["%החלב%", "%חלב%"]);
+        assert_eq!(like_patterns("ולחם"), ["%ולחמ%", "%לחמ%"]);
+        // Too short for the first letter to be a prefix.
+        assert_eq!(like_patterns("לחם"), ["%לחמ%"]);
+        assert_eq!(like_patterns(" Milk "), ["%milk%"]);
+        assert_eq!(like_patterns(""), ["%%"]);
+    }
+}
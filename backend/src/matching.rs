@@ -0,0 +1,287 @@
+//! Picks which item answers a free-text grocery term in each store.
+//!
+//! Every item whose name contains the term is a candidate; they're scored on whole-word
+//! token hits, whether the term leads the name (Hebrew puts the head noun first, so
+//! "חלב 3%" is milk and "שוקולד חלב" is chocolate) and trigram similarity, then checked
+//! against the term's typical price.
+
+use std::collections::HashMap;
+
+use crate::hebrew;
+use crate::models::{ItemPrice, MatchAlternative, ProductFamily, ProductVariant};
+use crate::units::strip_size;
+
+/// One-letter prefixes Hebrew attaches to words (the, and, in, to, from, that, as).
+const HEBREW_PREFIXES: [char; 7] = ['ה', 'ו', 'ב', 'ל', 'מ', 'ש', 'כ'];
+/// Candidates scoring at least this set the term's reference price.
+const STRONG_MATCH: f64 = 0.75;
+/// Prices this many times off the reference look like a different product.
+const PRICE_SANITY_FACTOR: f64 = 3.0;
+const PRICE_OUTLIER_PENALTY: f64 = 0.7;
+/// Within this much of a store's best score, the cheaper item wins.
+const SCORE_TOLERANCE: f64 = 0.1;
+const MAX_ALTERNATIVES: usize = 3;
+
+/// Words of `text` after Hebrew normalization, so "קוטג'" and "קוטג׳" are one token.
+fn tokens(text: &str) -> Vec<String> {
+    hebrew::normalize(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 1.0 for the same word, 0.8 for the word behind a Hebrew prefix letter ("החלב").
+fn token_hit(term_token: &str, name_token: &str) -> f64 {
+    if name_token == term_token {
+        return 1.0;
+    }
+    let mut chars = name_token.chars();
+    match chars.next() {
+        Some(first) if HEBREW_PREFIXES.contains(&first) && chars.as_str() == term_token => 0.8,
+        _ => 0.0,
+    }
+}
+
+/// Relevance of `item_name` to `term`, 0–1. `similarity` is pg_trgm's for the pair.
+pub fn match_score(term: &str, item_name: &str, similarity: f64) -> f64 {
+    let term_tokens = tokens(term);
+    let name_tokens = tokens(item_name);
+    if term_tokens.is_empty() || name_tokens.is_empty() {
+        return 0.2 * similarity;
+    }
+    let token_score = term_tokens
+        .iter()
+        .map(|t| name_tokens.iter().map(|n| token_hit(t, n)).fold(0.0, f64::max))
+        .sum::<f64>()
+        / term_tokens.len() as f64;
+    let lead = token_hit(&term_tokens[0], &name_tokens[0]);
+    0.5 * token_score + 0.3 * lead + 0.2 * similarity
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+/// Chooses one item per store for a term from `candidates` (store_pk, item, trigram
+/// similarity). `variants` are the term and its synonym expansions; an item scores
+/// against the one it fits best. The pick carries its `match_confidence` and the runners-up.
+pub fn select_matches(variants: &[String], candidates: Vec<(i32, ItemPrice, f64)>) -> HashMap<i32, ItemPrice> {
+    let mut scored: Vec<(i32, ItemPrice, f64)> = candidates
+        .into_iter()
+        .map(|(store, item, similarity)| {
+            let score = variants
+                .iter()
+                .map(|term| match_score(term, &item.item_name, similarity))
+                .fold(0.0, f64::max);
+            (store, item, score)
+        })
+        .collect();
+
+    // Price sanity: the typical price of strong matches (or of everything, if none are).
+    let strong: Vec<f64> = scored.iter().filter(|c| c.2 >= STRONG_MATCH).map(|c| c.1.price).collect();
+    let reference = if strong.is_empty() { median(scored.iter().map(|c| c.1.price).collect()) } else { median(strong) };
+    if let Some(reference) = reference.filter(|r| *r > 0.0) {
+        for (_, item, score) in &mut scored {
+            let ratio = item.price / reference;
+            if !(1.0 / PRICE_SANITY_FACTOR..=PRICE_SANITY_FACTOR).contains(&ratio) {
+                *score *= PRICE_OUTLIER_PENALTY;
+            }
+        }
+    }
+
+    let mut by_store: HashMap<i32, Vec<(ItemPrice, f64)>> = HashMap::new();
+    for (store, item, score) in scored {
+        by_store.entry(store).or_default().push((item, score));
+    }
+
+    by_store
+        .into_iter()
+        .map(|(store, mut items)| {
+            items.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            let best_score = items[0].1;
+            let pick = items
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, score))| best_score - score <= SCORE_TOLERANCE)
+                .min_by(|a, b| a.1.0.price.partial_cmp(&b.1.0.price).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(pos, _)| pos)
+                .unwrap_or(0);
+            let (mut chosen, score) = items.remove(pick);
+            chosen.match_confidence = Some(score);
+            chosen.alternatives = items
+                .into_iter()
+                .take(MAX_ALTERNATIVES)
+                .map(|(item, score)| MatchAlternative {
+                    item_code: item.item_code,
+                    item_name: item.item_name,
+                    price: item.price,
+                    match_confidence: score,
+                })
+                .collect();
+            (store, chosen)
+        })
+        .collect()
+}
+
+/// A catalog product matching a term: (variant, manufacturer, trigram similarity of
+/// its name to the term).
+pub type CatalogHit = (ProductVariant, Option<String>, f64);
+
+/// Groups catalog hits for `term` into families — same manufacturer, same name with
+/// sizes stripped — most relevant first, then most widely stocked.
+pub fn product_families(term: &str, rows: Vec<CatalogHit>, limit: usize) -> Vec<ProductFamily> {
+    let mut groups: HashMap<(String, String), Vec<CatalogHit>> = HashMap::new();
+    for row in rows {
+        let manufacturer_key = row.1.as_deref().map(|m| m.trim().to_lowercase()).unwrap_or_default();
+        groups.entry((manufacturer_key, strip_size(&row.0.name))).or_default().push(row);
+    }
+
+    let mut families: Vec<ProductFamily> = groups
+        .into_values()
+        .map(|members| {
+            let match_confidence = members
+                .iter()
+                .map(|(variant, _, similarity)| match_score(term, &variant.name, *similarity))
+                .fold(0.0, f64::max);
+            let lead = members
+                .iter()
+                .max_by_key(|(variant, _, _)| variant.store_count)
+                .expect("families are never empty");
+            let (name, manufacturer, store_count) = (lead.0.name.clone(), lead.1.clone(), lead.0.store_count);
+            let mut variants: Vec<ProductVariant> = members.into_iter().map(|(variant, _, _)| variant).collect();
+            variants.sort_by(|a, b| {
+                let size = |v: &ProductVariant| v.size_amount.unwrap_or(f64::INFINITY);
+                size(a).partial_cmp(&size(b)).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            ProductFamily {
+                name,
+                manufacturer,
+                match_confidence,
+                min_price: variants.iter().map(|v| v.min_price).fold(f64::INFINITY, f64::min),
+                max_price: variants.iter().map(|v| v.max_price).fold(0.0, f64::max),
+                store_count,
+                variants,
+            }
+        })
+        .collect();
+
+    families.sort_by(|a, b| {
+        b.match_confidence.partial_cmp(&a.match_confidence).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.store_count.cmp(&a.store_count))
+    });
+    families.truncate(limit);
+    families
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(code: &str, name: &str, price: f64) -> ItemPrice {
+        ItemPrice {
+            item_code: code.to_string(),
+            item_name: name.to_string(),
+            price,
+            unit_of_measure: None,
+            manufacturer_name: None,
+            price_update_date: None,
+            is_stale: false,
+            match_confidence: None,
+            alternatives: vec![],
+            term_index: 0,
+        }
+    }
+
+    #[test]
+    fn head_noun_outscores_modifier() {
+        let milk = match_score("חלב", "חלב תנובה 3% 1 ליטר", 0.3);
+        let chocolate = match_score("חלב", "שוקולד חלב", 0.4);
+        let protein = match_score("חלב", "חלבון מי גבינה", 0.3);
+        assert!(milk > chocolate, "{} <= {}", milk, chocolate);
+        assert!(chocolate > protein);
+        assert_eq!(token_hit("חלב", "החלב"), 0.8);
+    }
+
+    #[test]
+    fn picks_milk_over_cheaper_chocolate() {
+        let picked = select_matches(
+            &["חלב".to_string()],
+            vec![
+                (1, item("1", "שוקולד חלב", 3.0), 0.4),
+                (1, item("2", "חלב תנובה 3%", 6.9), 0.3),
+                (1, item("3", "חלב טרה 3%", 6.5), 0.3),
+                (2, item("4", "חלב יטבתה 1%", 7.2), 0.3),
+            ],
+        );
+        let store1 = &picked[&1];
+        // Two milks score alike; the cheaper one wins and chocolate is only an alternative.
+        assert_eq!(store1.item_code, "3");
+        assert!(store1.match_confidence.unwrap() > 0.8);
+        let alternatives: Vec<&str> = store1.alternatives.iter().map(|a| a.item_code.as_str()).collect();
+        assert_eq!(alternatives, ["2", "1"]);
+        assert_eq!(picked[&2].item_code, "4");
+    }
+
+    fn variant(barcode: &str, name: &str, size_amount: f64, price: f64, store_count: usize) -> ProductVariant {
+        ProductVariant {
+            barcode: barcode.to_string(),
+            name: name.to_string(),
+            quantity: None,
+            unit_of_measure: None,
+            size_amount: Some(size_amount),
+            size_unit: None,
+            min_price: price,
+            max_price: price + 1.0,
+            store_count,
+        }
+    }
+
+    #[test]
+    fn sizes_grouped_into_families() {
+        let tnuva = Some("תנובה".to_string());
+        let families = product_families(
+            "קוטג'",
+            vec![
+                (variant("1", "קוטג' 5% 500 גרם", 500.0, 9.9, 40), tnuva.clone(), 0.4),
+                (variant("2", "קוטג' 5% 250 גרם", 250.0, 5.5, 120), tnuva.clone(), 0.5),
+                (variant("3", "קוטג' 9% 250 גרם", 250.0, 6.2, 80), tnuva, 0.5),
+                (variant("4", "מאפה קוטג' 250 גרם", 250.0, 12.0, 10), None, 0.3),
+            ],
+            10,
+        );
+        assert_eq!(families.len(), 3);
+        assert_eq!(families[0].name, "קוטג' 5% 250 גרם");
+        let barcodes: Vec<&str> = families[0].variants.iter().map(|v| v.barcode.as_str()).collect();
+        assert_eq!(barcodes, ["2", "1"]);
+        assert_eq!((families[0].min_price, families[0].max_price), (5.5, 10.9));
+        assert_eq!(families[2].name, "מאפה קוטג' 250 גרם");
+        assert_eq!(product_families("קוטג'", vec![], 10).len(), 0);
+    }
+
+    #[test]
+    fn synonym_variants_scored() {
+        let candidates = || vec![(1, item("1", "שמנת להקצפה 38%", 12.9), 0.3)];
+        let plain = select_matches(&["שמנת מתוקה".to_string()], candidates());
+        let expanded = select_matches(&["שמנת מתוקה".to_string(), "שמנת להקצפה".to_string()], candidates());
+        assert!(expanded[&1].match_confidence.unwrap() > plain[&1].match_confidence.unwrap());
+    }
+
+    #[test]
+    fn price_outliers_penalized() {
+        let picked = select_matches(
+            &["קוטג'".to_string()],
+            vec![
+                (1, item("1", "קוטג' 5% 250 גרם", 5.5), 0.5),
+                (2, item("2", "קוטג' 5% 250 גרם", 5.9), 0.5),
+                (3, item("3", "קוטג' מארז 6 יחידות", 29.0), 0.5),
+            ],
+        );
+        assert!(picked[&3].match_confidence.unwrap() < picked[&1].match_confidence.unwrap());
+    }
+}
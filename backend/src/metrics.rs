@@ -0,0 +1,134 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::{sync::{Arc, OnceLock}, time::Instant};
+use tracing::error;
+
+use crate::database::DatabaseManager;
+use crate::models::PoolStats;
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Latency buckets (seconds) for HTTP handlers — compare can take a few seconds
+/// on a long list, everything else should sit in the low milliseconds.
+const HTTP_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Installs the global Prometheus recorder. Call once, before serving requests.
+pub fn install() -> anyhow::Result<()> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full("http_request_duration_seconds".to_string()),
+            HTTP_BUCKETS,
+        )?
+        .install_recorder()?;
+    HANDLE
+        .set(handle)
+        .map_err(|_| anyhow::anyhow!("metrics recorder already installed"))
+}
+
+/// Middleware: per-route request count and latency. The route label is the Axum
+/// path template (`/api/stores/:id/items`), never the raw URI, to keep cardinality bounded.
+pub async fn track_http(req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    counter!("http_requests_total", "route" => route.clone(), "method" => method.clone(), "status" => status)
+        .increment(1);
+    histogram!("http_request_duration_seconds", "route" => route, "method" => method)
+        .record(start.elapsed().as_secs_f64());
+    response
+}
+
+/// The metrics listener's routes. Served on `METRICS_PORT`, apart from the public
+/// API, so only the internal network (the Prometheus scraper) can reach it.
+pub fn metrics_router(db: Arc<DatabaseManager>) -> Router {
+    Router::new().route("/metrics", get(metrics_handler)).with_state(db)
+}
+
+/// GET /metrics — Prometheus text exposition. Pool and freshness gauges are
+/// sampled at scrape time; counters are updated as events happen.
+async fn metrics_handler(State(db): State<Arc<DatabaseManager>>) -> Response {
+    let Some(handle) = HANDLE.get() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "metrics recorder not installed").into_response();
+    };
+
+    let pools = db.pool_stats();
+    record_pool("api", &pools.api);
+    if let Some(ingest) = &pools.ingest {
+        record_pool("ingest", ingest);
+    }
+
+    match db.chain_freshness().await {
+        Ok(chains) => {
+            for chain in chains {
+                if let Some(ts) = chain.last_price_update {
+                    gauge!("data_last_price_update_timestamp_seconds", "chain" => chain.chain_id)
+                        .set(ts.and_utc().timestamp() as f64);
+                }
+            }
+        }
+        Err(e) => error!("Error sampling chain freshness for metrics: {}", e),
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        handle.render(),
+    )
+        .into_response()
+}
+
+fn record_pool(pool: &'static str, stats: &PoolStats) {
+    gauge!("db_pool_connections", "pool" => pool, "state" => "idle").set(stats.idle as f64);
+    gauge!("db_pool_connections", "pool" => pool, "state" => "in_use").set(stats.in_use as f64);
+    gauge!("db_pool_max_connections", "pool" => pool).set(stats.max_connections as f64);
+    gauge!("db_pool_saturation", "pool" => pool).set(stats.saturation);
+}
+
+/// Ingest outcome for one XML file. `chain` is the chain ID from the file (or its
+/// filename prefix when the file didn't parse).
+pub fn record_ingest_file(chain: &str, outcome: &'static str) {
+    counter!("ingest_files_total", "chain" => chain.to_string(), "outcome" => outcome).increment(1);
+}
+
+pub fn record_ingest_items(chain: &str, inserted: u64, errors: u64) {
+    counter!("ingest_items_total", "chain" => chain.to_string()).increment(inserted);
+    if errors > 0 {
+        counter!("ingest_errors_total", "chain" => chain.to_string(), "stage" => "item").increment(errors);
+    }
+}
+
+pub fn record_ingest_error(chain: &str, stage: &'static str) {
+    counter!("ingest_errors_total", "chain" => chain.to_string(), "stage" => stage).increment(1);
+}
+
+/// One file in `ingest_queue_depth` — picked up by the watcher or listed by a scan,
+/// not yet processed — until dropped.
+pub struct QueuedFile(());
+
+impl QueuedFile {
+    pub fn enqueue() -> Self {
+        gauge!("ingest_queue_depth").increment(1.0);
+        QueuedFile(())
+    }
+}
+
+impl Drop for QueuedFile {
+    fn drop(&mut self) {
+        gauge!("ingest_queue_depth").decrement(1.0);
+    }
+}
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::units::BaseUnit;
 
 // XML Data Structures (for parsing price files)
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -101,30 +104,181 @@ pub struct StoreRecord {
 }
 
 // Product catalog entry returned by item search autocomplete
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ProductSearchResult {
     pub barcode: Option<String>,
     pub name: String,
 }
 
 // API Request/Response Structures
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, ToSchema, IntoParams)]
 pub struct LocationQuery {
     pub latitude: f64,
     pub longitude: f64,
     pub radius_km: Option<f64>, // Default to 10km if not provided
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PriceComparisonRequest {
+/// Compare options shared by every API version; flattened into the request body.
+#[derive(Debug, Deserialize, Default, Clone, ToSchema)]
+pub struct CompareOptions {
     pub user_location: Option<LocationQuery>, // Optional — if absent, search all stores
-    pub grocery_list: Vec<String>,
     pub page: Option<usize>,
     pub page_size: Option<usize>,
     pub city: Option<String>,  // new — filter to stores in this city
+    pub max_data_age_hours: Option<f64>, // skip stores whose newest price is older than this
+    #[serde(flatten)]
+    pub store_filter: StoreFilter,
+    #[serde(default)]
+    pub ranking: RankingMode,
+    /// ₪ per km driven for `effective_cost` ranking (default 0.7).
+    pub cost_per_km: Option<f64>,
+    /// ₪ per hour spent travelling for `effective_cost` ranking (default 40).
+    pub time_value_per_hour: Option<f64>,
+    /// Price terms a store lacks at this estimate; off when absent.
+    pub estimate_missing: Option<EstimateMethod>,
+    #[serde(default)]
+    pub missing_policy: MissingPolicy,
+    /// Look up in-store alternatives for missing barcode terms.
+    #[serde(default)]
+    pub suggest_substitutes: bool,
+    /// Price each missing barcode term at its best alternative. Implies `suggest_substitutes`.
+    #[serde(default)]
+    pub auto_substitute: bool,
+}
+
+/// Why an item was suggested in place of a missing product.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubstituteReason {
+    SameManufacturer,
+    SimilarName,
+}
+
+/// An in-store alternative for a missing product.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct Substitute {
+    pub item: ItemPrice,
+    pub reason: SubstituteReason,
+    /// Higher is closer: name similarity plus manufacturer and size bonuses.
+    pub match_score: f64,
+    /// Alternative's pack size over the original's, when both normalize to the same unit.
+    pub size_ratio: Option<f64>,
+}
+
+/// Ranked alternatives, best first, for one missing barcode term.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct SubstitutionSuggestion {
+    pub term_index: usize,
+    pub term: String,
+    pub alternatives: Vec<Substitute>,
+    /// The first alternative is counted in the store's items and totals (`auto_substitute`).
+    pub applied: bool,
+}
+
+/// Where a missing term's fill-in price comes from. Both look only at the stores
+/// in scope (nearby / city / filters) that carry the term.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateMethod {
+    /// Median of the per-store prices.
+    Median,
+    /// Lowest price at any store in scope.
+    CheapestNearby,
+}
+
+/// How incomplete baskets rank against complete ones.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingPolicy {
+    /// Most items found first, then cost.
+    #[default]
+    CoverageFirst,
+    /// Cost of the estimated full basket only — requires `estimate_missing`.
+    EstimatedTotal,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// A fill-in price for a term the store doesn't carry.
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct MissingEstimate {
+    pub term_index: usize,
+    pub term: String,
+    pub estimated_price: f64,
+    pub method: EstimateMethod,
+    /// Stores in scope the estimate is drawn from.
+    pub store_count: usize,
+}
+
+/// How stores with equal coverage are ordered.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingMode {
+    /// Cheapest basket first.
+    #[default]
+    Price,
+    /// Basket plus the round trip's driving and time cost — requires `user_location`.
+    EffectiveCost,
+}
+
+/// Why a store ranked where it did. Present when the store's distance is known.
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct RankScore {
+    pub basket_total: f64,
+    pub distance_km: f64,
+    /// Round-trip driving cost.
+    pub travel_cost: f64,
+    /// Round-trip time, valued at `time_value_per_hour`.
+    pub time_cost: f64,
+    pub effective_cost: f64,
+}
+
+/// Sub-chain IDs are only unique within their chain.
+#[derive(Debug, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct SubChainRef {
+    pub chain_id: String,
+    pub sub_chain_id: i32,
+}
+
+/// Which stores a compare may rank. Each non-empty `include_*` list narrows the
+/// candidates, each `exclude_*` list removes from them; all of them combine.
+#[derive(Debug, Deserialize, Default, Clone, ToSchema)]
+pub struct StoreFilter {
+    #[serde(default)]
+    pub include_chains: Vec<String>,
+    #[serde(default)]
+    pub exclude_chains: Vec<String>,
+    #[serde(default)]
+    pub include_sub_chains: Vec<SubChainRef>,
+    #[serde(default)]
+    pub exclude_sub_chains: Vec<SubChainRef>,
+    /// Store IDs as returned by the API (`StoreInfo.id`), not chain-local store numbers.
+    #[serde(default)]
+    pub include_store_ids: Vec<i32>,
+    #[serde(default)]
+    pub exclude_store_ids: Vec<i32>,
+}
+
+impl StoreFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include_chains.is_empty()
+            && self.exclude_chains.is_empty()
+            && self.include_sub_chains.is_empty()
+            && self.exclude_sub_chains.is_empty()
+            && self.include_store_ids.is_empty()
+            && self.exclude_store_ids.is_empty()
+    }
+}
+
+/// Either `grocery_list` or `share_id` (a short link from POST /api/shares) names the list.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PriceComparisonRequest {
+    #[serde(default)]
+    pub grocery_list: Vec<String>,
+    pub share_id: Option<String>,
+    #[serde(flatten)]
+    pub options: CompareOptions,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct StoreInfo {
     pub id: i32,
     pub chain_id: String,
@@ -136,27 +290,53 @@ pub struct StoreInfo {
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
     pub distance_km: Option<f64>,
+    pub last_price_update: Option<chrono::NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct ItemPrice {
     pub item_code: String,
     pub item_name: String,
     pub price: f64,
     pub unit_of_measure: Option<String>,
     pub manufacturer_name: Option<String>,
+    pub price_update_date: Option<chrono::NaiveDateTime>,
+    pub is_stale: bool,
+    /// How well the item answers its term, 0–1: 1 for a barcode hit, scored for
+    /// free-text terms. None outside compare matching.
+    pub match_confidence: Option<f64>,
+    /// Runners-up considered for a free-text term, best first.
+    pub alternatives: Vec<MatchAlternative>,
+    /// Index of the grocery list term this item answers (v2 uses it to build basket lines).
+    #[serde(skip)]
+    pub term_index: usize,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct MatchAlternative {
+    pub item_code: String,
+    pub item_name: String,
+    pub price: f64,
+    pub match_confidence: f64,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct StoreComparison {
     pub store: StoreInfo,
     pub items: Vec<ItemPrice>,
+    /// Found items only.
     pub total_price: f64,
     pub items_found: usize,
     pub items_missing: Vec<String>,
+    /// Found items plus `missing_estimates`; set when `estimate_missing` is.
+    pub estimated_total: Option<f64>,
+    /// One per missing term that some store in scope carries.
+    pub missing_estimates: Vec<MissingEstimate>,
+    pub substitutions: Vec<SubstitutionSuggestion>,
+    pub score: Option<RankScore>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PriceComparisonResponse {
     pub stores: Vec<StoreComparison>,
     pub best_store: Option<StoreComparison>,
@@ -165,8 +345,281 @@ pub struct PriceComparisonResponse {
     pub has_more: bool,
 }
 
+/// v2 grocery list entry. `barcode` (EAN-13) gives an exact cross-store match, as
+/// does `cluster_id` for items without one; otherwise `name` is matched against item names.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct GroceryListEntry {
+    pub barcode: Option<String>,
+    /// A product cluster (GET /admin/clusters); used when there's no valid barcode.
+    #[serde(default)]
+    pub cluster_id: Option<i32>,
+    pub name: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: u32,
+}
+
+fn default_quantity() -> u32 {
+    1
+}
+
+/// POST /api/v2/compare-prices — either `grocery_list` or `share_id` names the list.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PriceComparisonRequestV2 {
+    #[serde(default)]
+    pub grocery_list: Vec<GroceryListEntry>,
+    pub share_id: Option<String>,
+    #[serde(flatten)]
+    pub options: CompareOptions,
+}
+
+/// One grocery list entry as priced in one store.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct BasketLine {
+    pub entry: GroceryListEntry,
+    pub item: Option<ItemPrice>,
+    /// item price × entry quantity; None when the store doesn't carry it.
+    pub line_total: Option<f64>,
+    /// Fill-in for a missing item, already × quantity.
+    pub estimated_line_total: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct StoreBasket {
+    pub store: StoreInfo,
+    /// One line per grocery list entry, in request order.
+    pub lines: Vec<BasketLine>,
+    /// Found lines only.
+    pub total_price: f64,
+    pub items_found: usize,
+    pub items_missing: usize,
+    /// Found lines plus estimated ones; set when `estimate_missing` is.
+    pub estimated_total: Option<f64>,
+    pub substitutions: Vec<SubstitutionSuggestion>,
+    /// Scored on the quantity-weighted total.
+    pub score: Option<RankScore>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Pagination {
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PriceComparisonResponseV2 {
+    pub stores: Vec<StoreBasket>,
+    /// Best store overall — only set on page 1.
+    pub best_store_id: Option<i32>,
+    pub pagination: Pagination,
+}
+
+/// Returned by GET /api/stores/:id — the store plus chain and pricing metadata.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoreDetail {
+    #[serde(flatten)]
+    pub store: StoreInfo,
+    pub chain_name: Option<String>,
+    pub zip_code: Option<String>,
+    pub bikoret_no: Option<i32>,
+    pub item_count: i64,
+    /// This store's basket cost relative to the average of other stores in its
+    /// city, over barcoded items they share (100 = city average, 90 = 10% cheaper).
+    /// None when the city has too little overlap to say.
+    pub price_level_index: Option<f64>,
+    /// How many shared items the index was computed over.
+    pub price_level_items: i64,
+}
+
+/// Sort order for a product's store list.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProductSort {
+    #[default]
+    Price,
+    /// Nearest first — requires latitude and longitude.
+    Distance,
+}
+
+/// Query string for GET /api/products/:barcode. With coordinates, each store
+/// carries `distance_km`.
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductQuery {
+    pub sort: Option<ProductSort>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Query string for GET /api/items/resolve.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResolveQuery {
+    /// Free-text grocery term, e.g. "קוטג'".
+    pub q: String,
+    /// Families to return (default 10, at most 30).
+    pub limit: Option<usize>,
+}
+
+/// One barcode in a product family.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ProductVariant {
+    pub barcode: String,
+    pub name: String,
+    pub quantity: Option<String>,
+    pub unit_of_measure: Option<String>,
+    /// Pack size in `size_unit`, when it normalizes.
+    pub size_amount: Option<f64>,
+    pub size_unit: Option<BaseUnit>,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub store_count: usize,
+}
+
+/// Barcodes that are the same product in different sizes: same manufacturer,
+/// same name once sizes are stripped.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ProductFamily {
+    /// Name of the most widely stocked variant.
+    pub name: String,
+    pub manufacturer: Option<String>,
+    /// Best variant's relevance to the term, 0–1.
+    pub match_confidence: f64,
+    pub min_price: f64,
+    pub max_price: f64,
+    /// Stores carrying the most widely stocked variant.
+    pub store_count: usize,
+    /// Smallest pack first.
+    pub variants: Vec<ProductVariant>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TermResolution {
+    pub term: String,
+    /// Most relevant first.
+    pub families: Vec<ProductFamily>,
+}
+
+/// One store's current price for a product.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ProductStorePrice {
+    pub store: StoreInfo,
+    pub price: f64,
+    pub price_update_date: Option<chrono::NaiveDateTime>,
+    pub is_stale: bool,
+}
+
+/// Price spread across the stores carrying a product.
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct PriceStats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub store_count: usize,
+}
+
+/// Returned by GET /api/products/:barcode.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductDetail {
+    pub barcode: String,
+    pub canonical_name: String,
+    pub manufacturer: Option<String>,
+    pub quantity: Option<String>,
+    pub unit_of_measure: Option<String>,
+    pub first_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// None when no store currently lists the product.
+    pub price_stats: Option<PriceStats>,
+    pub stores: Vec<ProductStorePrice>,
+}
+
+/// Body of POST /api/auth/register and /api/auth/login.
+#[derive(Deserialize, ToSchema)]
+pub struct Credentials {
+    pub email: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials").field("email", &self.email).finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct UserInfo {
+    pub id: i64,
+    pub email: String,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A fresh session: send `token` as `Authorization: Bearer …` until `expires_at`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionResponse {
+    pub token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub user: UserInfo,
+}
+
+/// Body of POST /api/lists and PUT /api/lists/:id — the whole list, replacing any previous items.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SavedListInput {
+    pub name: String,
+    pub grocery_list: Vec<GroceryListEntry>,
+}
+
+/// A grocery list stored on the server.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedList {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub grocery_list: Vec<GroceryListEntry>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One row of GET /api/lists.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedListSummary {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub item_count: i64,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Body of POST /api/shares.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ShareCreateRequest {
+    pub name: Option<String>,
+    pub grocery_list: Vec<GroceryListEntry>,
+    /// Lets anyone holding the link replace the list (default read-only).
+    #[serde(default)]
+    pub editable: bool,
+    /// Omit for a link that never expires.
+    pub expires_in_hours: Option<f64>,
+}
+
+/// Body of PUT /api/shares/:id (editable shares only).
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ShareUpdateRequest {
+    pub name: Option<String>,
+    pub grocery_list: Vec<GroceryListEntry>,
+}
+
+/// A shared list, addressed by its short `id`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SharedList {
+    pub id: String,
+    pub name: Option<String>,
+    pub grocery_list: Vec<GroceryListEntry>,
+    pub editable: bool,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 /// A single item row returned by the store-items and item-search endpoints.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct StoreItemRow {
     pub item_code: String,
     pub item_name: String,
@@ -174,14 +627,452 @@ pub struct StoreItemRow {
     pub item_price: f64,
     pub unit_of_measure: Option<String>,
     pub quantity: Option<String>,
+    pub price_update_date: Option<chrono::NaiveDateTime>,
+    pub is_stale: bool,
 }
 
 /// Paginated item list returned by GET /api/stores/:id/items and GET /api/items.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginatedItems {
     pub items: Vec<StoreItemRow>,
     pub total: i64,
     pub page: usize,
     pub page_size: usize,
     pub has_more: bool,
+    /// Brands among all matching items (ignoring any `brand` filter), most items first.
+    /// Only computed for page 1; empty on later pages.
+    pub brands: Vec<BrandFacet>,
+}
+
+/// One brand of a brand facet.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BrandFacet {
+    /// Manufacturer ID, accepted by the `brand` filter and GET /api/brands/:id.
+    pub id: i32,
+    pub name: String,
+    /// Distinct matching items of this brand.
+    pub item_count: i64,
+}
+
+/// One chain's prices for a brand's products.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BrandChainStats {
+    pub chain_id: String,
+    pub chain_name: Option<String>,
+    /// Distinct products of the brand the chain lists.
+    pub item_count: i64,
+    /// Mean, over those products, of the chain's average current price.
+    pub avg_price: f64,
+    pub min_price: f64,
+    pub max_price: f64,
+    /// Products the chain shares with at least one other chain.
+    pub shared_items: i64,
+    /// The chain's total over shared products relative to the cross-chain average
+    /// (100 = average, 90 = 10% cheaper). None when fewer than 3 products are shared.
+    pub price_index: Option<f64>,
+}
+
+/// Returned by GET /api/brands/:id.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BrandDetail {
+    pub id: i32,
+    pub name: String,
+    /// Every `manufacturer_name` spelling mapped to this brand.
+    pub aliases: Vec<String>,
+    /// Chains carrying the brand, cheapest price index first.
+    pub chains: Vec<BrandChainStats>,
+}
+
+/// Snapshot of one connection pool's utilization.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub max_connections: u32,
+    /// in_use / max_connections — 1.0 means requests are queueing for a connection.
+    pub saturation: f64,
+}
+
+/// Returned by GET /health/pool.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolStatsResponse {
+    pub api: PoolStats,
+    pub ingest: Option<PoolStats>,
+}
+
+/// Latest price_update_date seen for a chain, across all of its stores.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ChainFreshness {
+    pub chain_id: String,
+    pub display_name: Option<String>,
+    pub last_price_update: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Degraded,
+    Failed,
+}
+
+/// One dependency check in GET /ready and GET /health/details.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Data age for one chain, judged against the configured staleness threshold.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ChainDataAge {
+    pub chain_id: String,
+    pub display_name: Option<String>,
+    pub last_price_update: Option<chrono::NaiveDateTime>,
+    pub age_hours: Option<f64>,
+    pub is_stale: bool,
+}
+
+/// Returned by GET /ready — overall status is the worst individual check.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: CheckStatus,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// Returned by GET /health/details.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthDetails {
+    pub status: CheckStatus,
+    pub timestamp: String,
+    pub checks: Vec<HealthCheck>,
+    pub chains: Vec<ChainDataAge>,
+    pub pool: PoolStatsResponse,
+}
+
+/// One chain as listed by GET /api/chains.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ChainSummary {
+    pub chain_id: String,
+    pub display_name: Option<String>,
+    pub store_count: i64,
+    pub item_count: i64,
+    pub last_price_update: Option<chrono::NaiveDateTime>,
+    pub age_hours: Option<f64>,
+    pub is_stale: bool,
+}
+
+/// Body of PUT /admin/chains/:chain_id.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChainNameUpdate {
+    pub display_name: String,
+}
+
+/// A `chain_names` row.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChainName {
+    pub chain_id: String,
+    pub display_name: String,
+}
+
+/// Body of POST /admin/synonyms and PUT /admin/synonyms/:id.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SynonymInput {
+    pub term: String,
+    pub alias: String,
+    /// False makes a one-way rule: searches for `term` also match `alias`, not the
+    /// reverse. Default true.
+    #[serde(default = "default_bidirectional")]
+    pub bidirectional: bool,
+}
+
+fn default_bidirectional() -> bool {
+    true
+}
+
+/// A `search_synonyms` rule.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchSynonym {
+    pub id: i32,
+    pub term: String,
+    pub alias: String,
+    pub bidirectional: bool,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Review state of a product cluster member. `confirmed` and `rejected` are operator
+/// overrides that survive rebuilds; `auto` members are recomputed each time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberStatus {
+    Auto,
+    Confirmed,
+    Rejected,
+}
+
+impl MemberStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemberStatus::Auto => "auto",
+            MemberStatus::Confirmed => "confirmed",
+            MemberStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_db(status: &str) -> Self {
+        match status {
+            "confirmed" => MemberStatus::Confirmed,
+            "rejected" => MemberStatus::Rejected,
+            _ => MemberStatus::Auto,
+        }
+    }
+}
+
+/// One chain's item in a product cluster.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClusterMember {
+    pub chain_id: String,
+    pub item_code: String,
+    pub item_name: String,
+    /// 0–1: 0.6 for a name match, plus 0.2 each when size and manufacturer matched too.
+    /// Confirmed members are 1.0.
+    pub confidence: f64,
+    pub status: MemberStatus,
+}
+
+/// Non-catalog items from several chains judged to be the same product. Compare
+/// matches it like a barcode via a `cluster:<id>` term or an entry's `cluster_id`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductCluster {
+    pub id: i32,
+    pub name: String,
+    pub manufacturer: Option<String>,
+    pub size_amount: Option<f64>,
+    pub size_unit: Option<BaseUnit>,
+    pub members: Vec<ClusterMember>,
+}
+
+/// Returned by GET /admin/clusters.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClusterListResponse {
+    pub clusters: Vec<ProductCluster>,
+    pub pagination: Pagination,
+}
+
+/// Returned by POST /admin/clusters/rebuild.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClusterRebuildSummary {
+    pub clusters: usize,
+    /// Automatic members written; overrides are kept as they were.
+    pub auto_members: usize,
+}
+
+/// Body of PUT /admin/clusters/:id/members/:chain_id/:item_code.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ClusterMemberOverride {
+    /// `confirmed` (moving the item here if it's clustered elsewhere) or `rejected`.
+    pub status: MemberStatus,
+}
+
+/// One chain's name for a catalog product.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductNameVariant {
+    pub chain_id: String,
+    pub chain_name: Option<String>,
+    pub name: String,
+    /// Ingested files of the chain that listed the product under this name.
+    pub seen_count: i64,
+    pub last_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Returned by GET /admin/products/:barcode/names — most seen first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductNames {
+    pub barcode: String,
+    pub canonical_name: String,
+    pub variants: Vec<ProductNameVariant>,
+}
+
+/// A node of the category tree; top-level categories have no parent.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Category {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub parent_id: Option<i32>,
+}
+
+/// What a category rule is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// Whole words of the normalized item name.
+    Keyword,
+    /// Whole words of the normalized manufacturer name.
+    Manufacturer,
+    /// Item code prefix, or an inclusive `from-to` range of equal-length prefixes.
+    BarcodePrefix,
+}
+
+impl RuleKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuleKind::Keyword => "keyword",
+            RuleKind::Manufacturer => "manufacturer",
+            RuleKind::BarcodePrefix => "barcode_prefix",
+        }
+    }
+
+    pub fn from_db(kind: &str) -> Self {
+        match kind {
+            "manufacturer" => RuleKind::Manufacturer,
+            "barcode_prefix" => RuleKind::BarcodePrefix,
+            _ => RuleKind::Keyword,
+        }
+    }
+}
+
+/// A `category_rules` row.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CategoryRule {
+    pub id: i32,
+    pub category_id: i32,
+    pub kind: RuleKind,
+    pub pattern: String,
+    /// Higher wins over the rule kind and pattern length.
+    pub priority: i32,
+}
+
+/// Body of POST /admin/categories/rules.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CategoryRuleInput {
+    pub category_id: i32,
+    pub kind: RuleKind,
+    pub pattern: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Body of PUT /admin/categories/items/:item_code.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ItemCategoryOverride {
+    pub category_id: i32,
+    /// The chain whose item this is. May be omitted for EAN-13 barcodes, pinning
+    /// the product in every chain that carries it.
+    pub chain_id: Option<String>,
+}
+
+/// Returned by PUT /admin/categories/items/:item_code.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ItemCategoryUpdate {
+    /// Chain items now pinned to the category.
+    pub items: usize,
+}
+
+/// Returned by POST /admin/categories/reclassify.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReclassifySummary {
+    /// Chain items a rule put in a category; pinned items aren't counted.
+    pub classified: usize,
+    pub unclassified: usize,
+}
+
+/// Body of PUT /admin/manufacturers/aliases.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ManufacturerAliasInput {
+    /// A `manufacturer_name` spelling, exactly as chains send it.
+    pub alias: String,
+    /// None marks the spelling as "no brand" (a placeholder).
+    pub manufacturer_id: Option<i32>,
+}
+
+/// A `manufacturer_aliases` row.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ManufacturerAlias {
+    pub alias: String,
+    pub manufacturer_id: Option<i32>,
+    /// `auto` (resolved at ingest) or `manual`.
+    pub source: String,
+}
+
+/// Body of POST /admin/manufacturers/:id/merge.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ManufacturerMerge {
+    /// The manufacturer that takes over the merged one's aliases.
+    pub into: i32,
+}
+
+/// Returned by POST /admin/manufacturers/:id/merge.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ManufacturerMergeSummary {
+    pub aliases_moved: usize,
+}
+
+/// Returned by POST /admin/products/names/elect.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NameElectionSummary {
+    /// Products with more than one name variant.
+    pub products: usize,
+    pub renamed: usize,
+}
+
+/// One `processed_files` row.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LedgerEntry {
+    pub filename: String,
+    pub file_size: i64,
+    pub processed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Returned by GET /admin/ingest/ledger — newest first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LedgerResponse {
+    pub entries: Vec<LedgerEntry>,
+    pub pagination: Pagination,
+}
+
+/// Rows deleted by a chain or store purge.
+#[derive(Debug, Serialize, Default, ToSchema)]
+pub struct PurgeSummary {
+    pub stores: u64,
+    pub items: u64,
+    pub price_history: u64,
+}
+
+/// Ingestion state as seen by the admin API.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IngestStatus {
+    pub paused: bool,
+    pub scan_running: bool,
+    /// Unprocessed XML files in the watch directory; None if it couldn't be read.
+    pub pending_files: Option<usize>,
+}
+
+/// Body of PUT /admin/ingest/paused.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IngestPauseUpdate {
+    pub paused: bool,
+}
+
+/// Data age for one store, as listed by GET /api/freshness.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct StoreFreshness {
+    pub id: i32,
+    pub chain_id: String,
+    pub store_id: i32,
+    pub store_name: Option<String>,
+    pub city: Option<String>,
+    pub last_price_update: Option<chrono::NaiveDateTime>,
+    pub age_hours: Option<f64>,
+    pub is_stale: bool,
+}
+
+/// Returned by GET /api/freshness — chains and stores, stalest first.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshnessResponse {
+    pub stale_after_hours: f64,
+    pub chains: Vec<ChainDataAge>,
+    pub stores: Vec<StoreFreshness>,
 }
@@ -0,0 +1,132 @@
+//! Canonical product name election.
+//!
+//! Every chain's name for a barcode is kept as a variant with how often it was seen
+//! (`product_name_variants`). The elected name is the clean variant with the widest
+//! support: spellings that normalize alike pool their votes, and a name that is a
+//! prefix of a longer one — typically a chain truncating to its column width — votes
+//! for the longer one. Names from trusted chains win over support; ties go to the
+//! longer name.
+
+use std::collections::HashMap;
+
+use crate::hebrew;
+
+/// Shorter names are codes or abbreviations, not product names.
+const MIN_NAME_LEN: usize = 3;
+
+/// One chain's spelling of a product name.
+#[derive(Debug, Clone)]
+pub struct NameVariant {
+    pub chain_id: String,
+    pub name: String,
+    pub seen_count: i64,
+}
+
+/// False for names that are too short, have no letters, carry encoding damage, or
+/// end in a truncation mark.
+pub fn is_clean(name: &str) -> bool {
+    let name = name.trim();
+    name.chars().count() >= MIN_NAME_LEN
+        && name.chars().any(char::is_alphabetic)
+        && !name.contains(['?', '\u{FFFD}'])
+        && !name.ends_with(['.', '-', '…'])
+}
+
+/// One spelling after pooling: the most seen raw form of a normalized name.
+struct Spelling<'a> {
+    normalized: String,
+    display: &'a str,
+    display_seen: i64,
+    seen: i64,
+    chains: Vec<&'a str>,
+    trusted: bool,
+}
+
+/// The name to use for a product, or None when no variant is clean.
+pub fn elect(variants: &[NameVariant], trusted_chains: &[String]) -> Option<String> {
+    let mut spellings: HashMap<String, Spelling> = HashMap::new();
+    for variant in variants.iter().filter(|v| is_clean(&v.name)) {
+        let normalized = hebrew::normalize(&variant.name);
+        let spelling = spellings.entry(normalized.clone()).or_insert_with(|| Spelling {
+            normalized,
+            display: variant.name.trim(),
+            display_seen: 0,
+            seen: 0,
+            chains: Vec::new(),
+            trusted: false,
+        });
+        if variant.seen_count > spelling.display_seen {
+            spelling.display = variant.name.trim();
+            spelling.display_seen = variant.seen_count;
+        }
+        spelling.seen += variant.seen_count;
+        if !spelling.chains.contains(&variant.chain_id.as_str()) {
+            spelling.chains.push(&variant.chain_id);
+        }
+        spelling.trusted |= trusted_chains.contains(&variant.chain_id);
+    }
+
+    let spellings: Vec<Spelling> = spellings.into_values().collect();
+    spellings
+        .iter()
+        .map(|candidate| {
+            // The candidate's own support plus that of every spelling it extends.
+            let mut chains: Vec<&str> = Vec::new();
+            let mut seen = 0;
+            for voter in spellings.iter().filter(|s| candidate.normalized.starts_with(&s.normalized)) {
+                seen += voter.seen;
+                for chain in &voter.chains {
+                    if !chains.contains(chain) {
+                        chains.push(chain);
+                    }
+                }
+            }
+            let score = (candidate.trusted, chains.len(), seen, candidate.display.chars().count());
+            (score, candidate.display)
+        })
+        // The name itself breaks exact ties so the result doesn't depend on map order.
+        .max_by(|(a, a_name), (b, b_name)| a.cmp(b).then_with(|| b_name.cmp(a_name)))
+        .map(|(_, name)| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(chain_id: &str, name: &str, seen_count: i64) -> NameVariant {
+        NameVariant { chain_id: chain_id.to_string(), name: name.to_string(), seen_count }
+    }
+
+    #[test]
+    fn truncated_and_misspelled_names_lose() {
+        let variants = [
+            variant("1", "שוקולד מריר 70% עלית 100 ג", 40),
+            variant("2", "שקולד מריר 70% עלית 100 גרם", 3),
+            variant("3", "שוקולד מריר 70% עלית 100 גרם", 5),
+            variant("4", "שוקולד מרי?", 90),
+        ];
+        // "100 ג" is cut from "100 גרם", and the two spellings of "שוקולד" pool their votes.
+        assert_eq!(elect(&variants, &[]).as_deref(), Some("שוקולד מריר 70% עלית 100 גרם"));
+    }
+
+    #[test]
+    fn trusted_chain_beats_support() {
+        let variants = [
+            variant("1", "חלב 3% תנובה 1 ליטר", 10),
+            variant("2", "חלב 3% תנובה 1 ליטר", 10),
+            variant("3", "חלב טרי 3% בקרטון", 1),
+        ];
+        assert_eq!(elect(&variants, &[]).as_deref(), Some("חלב 3% תנובה 1 ליטר"));
+        assert_eq!(elect(&variants, &["3".to_string()]).as_deref(), Some("חלב טרי 3% בקרטון"));
+    }
+
+    #[test]
+    fn nothing_clean_elects_nothing() {
+        assert!(!is_clean("12"));
+        assert!(!is_clean("1234567"));
+        assert!(!is_clean("במבה אסם..."));
+        assert!(is_clean("במבה אסם 80 גרם"));
+        assert_eq!(elect(&[variant("1", "??", 4)], &[]), None);
+        assert_eq!(elect(&[], &[]), None);
+    }
+}
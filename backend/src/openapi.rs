@@ -0,0 +1,309 @@
+use axum::Router;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::api::{self, AppState};
+use crate::error::{FieldError, Problem};
+use crate::{accounts, admin, health, models, shares, units};
+
+/// OpenAPI 3 document, generated from the handler `#[utoipa::path]` annotations
+/// and the serde models. Versioned routes are declared relative to their prefix
+/// (see [`V1Api`], [`V2Api`]) and nested in by [`spec`]. A copy is checked in at
+/// `backend/openapi.json` for the frontend's type generation; the test below keeps it current.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Shop Saver API", description = "Grocery price comparison across Israeli supermarket chains."),
+    paths(
+        health::health_check,
+        health::pool_stats,
+        health::ready,
+        health::health_details,
+    ),
+    components(schemas(
+        Problem,
+        FieldError,
+        models::PoolStats,
+        models::PoolStatsResponse,
+        models::CheckStatus,
+        models::HealthCheck,
+        models::ReadinessResponse,
+        models::HealthDetails,
+        models::ChainDataAge,
+    )),
+    tags(
+        (name = "stores", description = "Store lookup"),
+        (name = "items", description = "Item search and browse"),
+        (name = "compare", description = "Basket price comparison"),
+        (name = "chains", description = "Supermarket chains"),
+        (name = "freshness", description = "Data age per chain and store"),
+        (name = "health", description = "Liveness, readiness and metrics"),
+        (name = "accounts", description = "Sign-in and saved grocery lists"),
+        (name = "shares", description = "Short links to grocery lists"),
+        (name = "admin", description = "Operator API — mounted only when ADMIN_TOKEN is set"),
+    ),
+    modifiers(&SecuritySchemes)
+)]
+pub struct ApiDoc;
+
+/// `/admin` — requires the admin token.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        admin::put_chain_name,
+        admin::purge_chain,
+        admin::purge_store,
+        admin::get_ingest_status,
+        admin::rescan,
+        admin::put_ingest_paused,
+        admin::get_ledger,
+        admin::reingest_file,
+        admin::list_synonyms,
+        admin::create_synonym,
+        admin::update_synonym,
+        admin::delete_synonym,
+        admin::rebuild_clusters,
+        admin::list_clusters,
+        admin::get_cluster,
+        admin::put_cluster_member,
+        admin::delete_cluster_member,
+        admin::list_category_rules,
+        admin::create_category_rule,
+        admin::delete_category_rule,
+        admin::reclassify,
+        admin::put_item_category,
+        admin::delete_item_category,
+        admin::elect_names,
+        admin::get_product_names,
+        admin::put_manufacturer_alias,
+        admin::merge_manufacturer,
+    ),
+    components(schemas(
+        models::ChainNameUpdate,
+        models::ChainName,
+        models::PurgeSummary,
+        models::IngestStatus,
+        models::IngestPauseUpdate,
+        models::LedgerEntry,
+        models::LedgerResponse,
+        models::SynonymInput,
+        models::SearchSynonym,
+        models::MemberStatus,
+        models::ClusterMember,
+        models::ProductCluster,
+        models::ClusterListResponse,
+        models::ClusterRebuildSummary,
+        models::ClusterMemberOverride,
+        models::RuleKind,
+        models::CategoryRule,
+        models::CategoryRuleInput,
+        models::ItemCategoryOverride,
+        models::ItemCategoryUpdate,
+        models::ReclassifySummary,
+        models::ProductNameVariant,
+        models::ProductNames,
+        models::NameElectionSummary,
+        models::ManufacturerAliasInput,
+        models::ManufacturerAlias,
+        models::ManufacturerMerge,
+        models::ManufacturerMergeSummary,
+    )),
+)]
+pub struct AdminApi;
+
+/// `admin_token` is the operator secret; `session_token` is what /auth/login returns.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        for name in ["admin_token", "session_token"] {
+            components.add_security_scheme(
+                name,
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+/// `/api/v1` (also served unprefixed at `/api`) — deprecated in favour of v2.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        api::get_nearby_stores,
+        api::get_all_stores_handler,
+        api::get_store_handler,
+        api::get_store_items_handler,
+        api::compare_prices,
+        api::search_items,
+        api::resolve_term_handler,
+        api::search_items_handler,
+        api::get_categories_handler,
+        api::get_brand_handler,
+        api::get_product_handler,
+        api::get_chains_handler,
+        api::get_chain_stores_handler,
+        api::get_freshness_handler,
+        accounts::register,
+        accounts::login,
+        accounts::logout,
+        accounts::me,
+        accounts::get_lists,
+        accounts::create_list,
+        accounts::get_list,
+        accounts::update_list,
+        accounts::delete_list,
+        shares::create_share,
+        shares::get_share,
+        shares::update_share,
+    ),
+    components(schemas(
+        models::LocationQuery,
+        models::CompareOptions,
+        models::StoreFilter,
+        models::RankingMode,
+        models::RankScore,
+        models::EstimateMethod,
+        models::MissingPolicy,
+        models::MissingEstimate,
+        models::SubstituteReason,
+        models::Substitute,
+        models::SubstitutionSuggestion,
+        models::SubChainRef,
+        models::PriceComparisonRequest,
+        models::PriceComparisonResponse,
+        models::StoreComparison,
+        models::StoreInfo,
+        models::StoreDetail,
+        models::ChainSummary,
+        models::ItemPrice,
+        models::MatchAlternative,
+        models::ProductSearchResult,
+        models::StoreItemRow,
+        models::PaginatedItems,
+        models::Category,
+        models::BrandFacet,
+        models::BrandChainStats,
+        models::BrandDetail,
+        models::ProductSort,
+        models::ProductStorePrice,
+        models::PriceStats,
+        models::ProductDetail,
+        models::ProductVariant,
+        models::ProductFamily,
+        models::TermResolution,
+        units::BaseUnit,
+        models::StoreFreshness,
+        models::FreshnessResponse,
+        models::GroceryListEntry,
+        models::Credentials,
+        models::UserInfo,
+        models::SessionResponse,
+        models::SavedListInput,
+        models::SavedList,
+        models::SavedListSummary,
+        models::ShareCreateRequest,
+        models::ShareUpdateRequest,
+        models::SharedList,
+    ))
+)]
+pub struct V1Api;
+
+/// `/api/v2` — object grocery lists and basket-shaped compare results.
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        api::get_nearby_stores,
+        api::get_all_stores_handler,
+        api::get_store_handler,
+        api::get_store_items_handler,
+        api::compare_prices_v2,
+        api::search_items,
+        api::resolve_term_handler,
+        api::search_items_handler,
+        api::get_categories_handler,
+        api::get_brand_handler,
+        api::get_product_handler,
+        api::get_chains_handler,
+        api::get_chain_stores_handler,
+        api::get_freshness_handler,
+        accounts::register,
+        accounts::login,
+        accounts::logout,
+        accounts::me,
+        accounts::get_lists,
+        accounts::create_list,
+        accounts::get_list,
+        accounts::update_list,
+        accounts::delete_list,
+        accounts::compare_saved_list,
+        shares::create_share,
+        shares::get_share,
+        shares::update_share,
+    ),
+    components(schemas(
+        models::GroceryListEntry,
+        models::PriceComparisonRequestV2,
+        models::PriceComparisonResponseV2,
+        models::StoreBasket,
+        models::BasketLine,
+        models::Pagination,
+    ))
+)]
+pub struct V2Api;
+
+/// The full document: unversioned routes plus each API version under its prefix.
+pub fn spec() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+        .nest("/api/v1", versioned(V1Api::openapi(), "v1", true))
+        .nest("/api/v2", versioned(V2Api::openapi(), "v2", false))
+        .nest("/admin", AdminApi::openapi())
+}
+
+/// Shared handlers appear in several versions, so prefix operation IDs with the
+/// version to keep them unique, and flag deprecated versions' operations.
+fn versioned(mut api: utoipa::openapi::OpenApi, version: &str, deprecated: bool) -> utoipa::openapi::OpenApi {
+    for item in api.paths.paths.values_mut() {
+        for op in [&mut item.get, &mut item.post, &mut item.put, &mut item.patch, &mut item.delete]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(id) = &op.operation_id {
+                op.operation_id = Some(format!("{}_{}", version, id));
+            }
+            if deprecated {
+                op.deprecated = Some(utoipa::openapi::Deprecated::True);
+            }
+        }
+    }
+    api
+}
+
+/// `/api/openapi.json` plus the bundled Swagger UI at `/api/docs`.
+pub fn docs_router() -> Router<AppState> {
+    SwaggerUi::new("/api/docs")
+        .url("/api/openapi.json", spec())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::spec;
+
+    /// Regenerate with `UPDATE_OPENAPI=1 cargo test openapi`, then the frontend's types
+    /// with `npm run gen:api-types` in frontend/.
+    #[test]
+    fn checked_in_openapi_json_is_current() {
+        let spec = spec().to_pretty_json().expect("spec serializes") + "\n";
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(path, &spec).expect("write openapi.json");
+            return;
+        }
+        let on_disk = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            on_disk == spec,
+            "backend/openapi.json is out of date — run `UPDATE_OPENAPI=1 cargo test openapi`"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::cmp::Ordering;
+
+use crate::models::{CompareOptions, MissingPolicy, RankScore, RankingMode, StoreComparison};
+
+/// Fuel plus wear, ₪ per km.
+pub const DEFAULT_COST_PER_KM: f64 = 0.7;
+pub const DEFAULT_TIME_VALUE_PER_HOUR: f64 = 40.0;
+/// Average door-to-door speed for urban trips.
+const URBAN_SPEED_KMH: f64 = 30.0;
+
+/// Ranking settings for one compare request.
+#[derive(Debug, Clone, Copy)]
+pub struct Ranking {
+    pub mode: RankingMode,
+    pub policy: MissingPolicy,
+    pub cost_per_km: f64,
+    pub time_value_per_hour: f64,
+}
+
+impl Ranking {
+    pub fn from_options(options: &CompareOptions) -> Self {
+        Self {
+            mode: options.ranking,
+            policy: options.missing_policy,
+            cost_per_km: options.cost_per_km.unwrap_or(DEFAULT_COST_PER_KM),
+            time_value_per_hour: options.time_value_per_hour.unwrap_or(DEFAULT_TIME_VALUE_PER_HOUR),
+        }
+    }
+
+    /// Breakdown for a basket `distance_km` away (one way; the trip is costed there and back).
+    pub fn score(&self, basket_total: f64, distance_km: Option<f64>) -> Option<RankScore> {
+        let distance_km = distance_km?;
+        let round_trip_km = 2.0 * distance_km;
+        let travel_cost = round_trip_km * self.cost_per_km;
+        let time_cost = round_trip_km / URBAN_SPEED_KMH * self.time_value_per_hour;
+        Some(RankScore {
+            basket_total,
+            distance_km,
+            travel_cost,
+            time_cost,
+            effective_cost: basket_total + travel_cost + time_cost,
+        })
+    }
+
+    /// The basket total a store is scored and ranked on: the estimated full basket
+    /// under `estimated_total`, else the found items only.
+    pub fn basket_total(&self, total_price: f64, estimated_total: Option<f64>) -> f64 {
+        match (self.policy, estimated_total) {
+            (MissingPolicy::EstimatedTotal, Some(estimated)) => estimated,
+            _ => total_price,
+        }
+    }
+
+    /// The cost stores are ordered by.
+    pub fn sort_cost(&self, basket_total: f64, score: Option<&RankScore>) -> f64 {
+        match (self.mode, score) {
+            (RankingMode::EffectiveCost, Some(score)) => score.effective_cost,
+            _ => basket_total,
+        }
+    }
+
+    /// Orders two stores given as (items_found, sort_cost). Coverage first unless the
+    /// policy ranks on the estimated full basket, where it only breaks ties.
+    pub fn cmp(&self, a: (usize, f64), b: (usize, f64)) -> Ordering {
+        let by_cost = a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+        match self.policy {
+            MissingPolicy::CoverageFirst => b.0.cmp(&a.0).then(by_cost),
+            MissingPolicy::EstimatedTotal => by_cost.then(b.0.cmp(&a.0)),
+        }
+    }
+
+    /// Sorts comparisons per [`Ranking::cmp`], pricing each term at `quantities[term_index]`
+    /// units (1 when absent) so v2 baskets rank on their weighted totals. Run it over every
+    /// store in scope before paging, or a better store on a later page never surfaces.
+    /// Ties keep input order.
+    pub fn rank(&self, comparisons: &mut [StoreComparison], quantities: &[u32]) {
+        let weight = |term_index: usize| quantities.get(term_index).copied().unwrap_or(1) as f64;
+        let cost = |c: &StoreComparison| {
+            let total: f64 = c.items.iter().map(|i| i.price * weight(i.term_index)).sum();
+            let estimated = c.estimated_total.map(|_| {
+                total + c.missing_estimates.iter().map(|e| e.estimated_price * weight(e.term_index)).sum::<f64>()
+            });
+            let basket_total = self.basket_total(total, estimated);
+            self.sort_cost(basket_total, self.score(basket_total, c.store.distance_km).as_ref())
+        };
+        comparisons.sort_by(|a, b| self.cmp((a.items_found, cost(a)), (b.items_found, cost(b))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EstimateMethod, ItemPrice, MissingEstimate, StoreInfo};
+
+    fn priced(term_index: usize, price: f64) -> ItemPrice {
+        ItemPrice {
+            item_code: format!("item-{}", term_index),
+            item_name: "x".to_string(),
+            price,
+            unit_of_measure: None,
+            manufacturer_name: None,
+            price_update_date: None,
+            is_stale: false,
+            match_confidence: None,
+            alternatives: vec![],
+            term_index,
+        }
+    }
+
+    fn ranking(mode: RankingMode) -> Ranking {
+        Ranking { mode, policy: MissingPolicy::CoverageFirst, cost_per_km: 1.0, time_value_per_hour: 60.0 }
+    }
+
+    #[test]
+    fn round_trip_costed() {
+        let score = ranking(RankingMode::EffectiveCost).score(100.0, Some(15.0)).unwrap();
+        assert_eq!(score.travel_cost, 30.0);
+        // 30 km at 30 km/h is an hour.
+        assert_eq!(score.time_cost, 60.0);
+        assert_eq!(score.effective_cost, 190.0);
+        assert!(ranking(RankingMode::EffectiveCost).score(100.0, None).is_none());
+    }
+
+    #[test]
+    fn nearby_store_beats_cheaper_far_one() {
+        let r = ranking(RankingMode::EffectiveCost);
+        let near = r.score(110.0, Some(1.0));
+        let far = r.score(100.0, Some(14.0));
+        let near_cost = r.sort_cost(110.0, near.as_ref());
+        let far_cost = r.sort_cost(100.0, far.as_ref());
+        assert_eq!(r.cmp((3, near_cost), (3, far_cost)), Ordering::Less);
+
+        let by_price = ranking(RankingMode::Price);
+        assert_eq!(by_price.sort_cost(110.0, near.as_ref()), 110.0);
+        // Coverage still comes first.
+        assert_eq!(r.cmp((2, near_cost), (3, far_cost)), Ordering::Greater);
+    }
+
+    fn comparison(id: i32, items: Vec<ItemPrice>, distance_km: f64, r: &Ranking) -> StoreComparison {
+        let total_price = items.iter().map(|i| i.price).sum();
+        StoreComparison {
+            store: StoreInfo {
+                id,
+                chain_id: "7290027600007".to_string(),
+                sub_chain_id: 1,
+                store_id: id,
+                store_name: None,
+                address: None,
+                city: None,
+                latitude: None,
+                longitude: None,
+                distance_km: Some(distance_km),
+                last_price_update: None,
+            },
+            items_found: items.len(),
+            items,
+            total_price,
+            items_missing: vec![],
+            estimated_total: None,
+            missing_estimates: vec![],
+            substitutions: vec![],
+            score: r.score(total_price, Some(distance_km)),
+        }
+    }
+
+    #[test]
+    fn best_store_found_beyond_first_page() {
+        let r = ranking(RankingMode::EffectiveCost);
+        // Coverage order (store id) puts store 12 on page 2 of 10; it is the nearest and cheapest.
+        let mut stores: Vec<StoreComparison> =
+            (1..=11).map(|id| comparison(id, vec![priced(0, 100.0)], 5.0, &r)).collect();
+        stores.push(comparison(12, vec![priced(0, 95.0)], 1.0, &r));
+        r.rank(&mut stores, &[]);
+        let page: Vec<i32> = stores.iter().take(10).map(|c| c.store.id).collect();
+        assert_eq!(page[0], 12);
+        assert_eq!(page[1..], [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn estimated_basket_ranked_beyond_first_page() {
+        let r = Ranking { policy: MissingPolicy::EstimatedTotal, ..ranking(RankingMode::Price) };
+        let stores = || {
+            // Complete 10 + 5 baskets at stores 1–11; store 12, last in coverage order,
+            // lacks term 1 and is estimated at 8 + 6.
+            let mut stores: Vec<StoreComparison> =
+                (1..=11).map(|id| comparison(id, vec![priced(0, 10.0), priced(1, 5.0)], 5.0, &r)).collect();
+            let mut partial = comparison(12, vec![priced(0, 8.0)], 5.0, &r);
+            partial.estimated_total = Some(14.0);
+            partial.missing_estimates = vec![MissingEstimate {
+                term_index: 1,
+                term: "b".to_string(),
+                estimated_price: 6.0,
+                method: EstimateMethod::Median,
+                store_count: 11,
+            }];
+            stores.push(partial);
+            stores
+        };
+        let mut single = stores();
+        r.rank(&mut single, &[]);
+        assert_eq!(single[0].store.id, 12);
+        // Three of term 1: 8 + 18 = 26 against 10 + 15 = 25.
+        let mut weighted = stores();
+        r.rank(&mut weighted, &[1, 3]);
+        assert_eq!(weighted[0].store.id, 1);
+        assert_eq!(weighted[11].store.id, 12);
+    }
+
+    #[test]
+    fn estimated_total_outranks_coverage() {
+        let r = Ranking { policy: MissingPolicy::EstimatedTotal, ..ranking(RankingMode::Price) };
+        // 18 of 20 found, 150 with the two estimates, against a complete 180 basket.
+        let partial = r.basket_total(140.0, Some(150.0));
+        assert_eq!(partial, 150.0);
+        assert_eq!(r.cmp((18, partial), (20, 180.0)), Ordering::Less);
+        assert_eq!(r.cmp((18, 180.0), (20, 180.0)), Ordering::Greater);
+        assert_eq!(ranking(RankingMode::Price).basket_total(140.0, Some(150.0)), 140.0);
+    }
+}
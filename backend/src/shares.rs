@@ -0,0 +1,226 @@
+use anyhow::Context;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::{rejection::{JsonRejection, PathRejection}, Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::api::{entry_errors, AppState};
+use crate::database::DatabaseManager;
+use crate::error::{validate, ApiError, FieldError, Problem};
+use crate::models::{GroceryListEntry, ShareCreateRequest, ShareUpdateRequest, SharedList};
+
+const SHARE_ID_LEN: usize = 10;
+const SHARE_ID_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const MAX_SHARE_NAME_LEN: usize = 100;
+const MAX_EXPIRY_HOURS: f64 = 24.0 * 365.0;
+/// Expired shares keep answering `share_expired` this long before they're deleted.
+const EXPIRED_SHARE_GRACE_DAYS: i64 = 30;
+
+/// A random base62 ID (~59 bits). Bytes ≥ 248 are redrawn so every symbol is equally likely.
+fn new_share_id() -> String {
+    let mut id = String::with_capacity(SHARE_ID_LEN);
+    let mut buf = [0u8; 16];
+    while id.len() < SHARE_ID_LEN {
+        OsRng.fill_bytes(&mut buf);
+        for b in buf.iter().filter(|&&b| b < 248) {
+            if id.len() == SHARE_ID_LEN {
+                break;
+            }
+            id.push(SHARE_ID_ALPHABET[(*b % 62) as usize] as char);
+        }
+    }
+    id
+}
+
+fn is_share_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 16 && id.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn share_name_errors(name: Option<&str>) -> Vec<FieldError> {
+    match name {
+        Some(name) if name.chars().count() > MAX_SHARE_NAME_LEN => vec![FieldError::new(
+            "name",
+            "name_length",
+            format!("name may be at most {} characters", MAX_SHARE_NAME_LEN),
+        )],
+        _ => vec![],
+    }
+}
+
+fn normalized_name(name: Option<String>) -> Option<String> {
+    name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+fn share_not_found(id: &str) -> ApiError {
+    ApiError::not_found("share_not_found", format!("no shared list {}", id))
+}
+
+/// An unexpired share, or the matching 404.
+async fn live_share(db: &DatabaseManager, id: &str) -> Result<SharedList, ApiError> {
+    if !is_share_id(id) {
+        return Err(share_not_found(id));
+    }
+    let share = db
+        .get_share(id)
+        .await
+        .with_context(|| format!("fetching shared list {}", id))?
+        .ok_or_else(|| share_not_found(id))?;
+    if share.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(ApiError::not_found("share_expired", format!("shared list {} has expired", id)));
+    }
+    Ok(share)
+}
+
+/// The entries behind a share ID — how compare requests accept a short link.
+pub(crate) async fn shared_entries(db: &DatabaseManager, id: &str) -> Result<Vec<GroceryListEntry>, ApiError> {
+    Ok(live_share(db, id).await?.grocery_list)
+}
+
+/// Creates a short link for a grocery list.
+#[utoipa::path(
+    post,
+    path = "/shares",
+    tag = "shares",
+    request_body = ShareCreateRequest,
+    responses(
+        (status = 201, description = "Share created; link it by `id`", body = SharedList),
+        (status = 400, description = "Invalid list, name or expiry", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_share(
+    State(db): State<Arc<DatabaseManager>>,
+    request: Result<Json<ShareCreateRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<SharedList>), ApiError> {
+    let Json(request) = request?;
+    let name = normalized_name(request.name);
+    let mut errors = entry_errors(&request.grocery_list);
+    errors.extend(share_name_errors(name.as_deref()));
+    if let Some(hours) = request.expires_in_hours
+        && !(hours > 0.0 && hours <= MAX_EXPIRY_HOURS)
+    {
+        errors.push(FieldError::new(
+            "expires_in_hours",
+            "expiry_out_of_range",
+            format!("expires_in_hours must be between 0 and {}", MAX_EXPIRY_HOURS),
+        ));
+    }
+    validate(errors)?;
+
+    if let Err(e) = db.purge_expired_shares(Duration::days(EXPIRED_SHARE_GRACE_DAYS)).await {
+        warn!("Error purging expired shares: {}", e);
+    }
+
+    let expires_at = request
+        .expires_in_hours
+        .map(|hours| Utc::now() + Duration::seconds((hours * 3600.0) as i64));
+    // A collision at ~59 bits is vanishingly rare; retry a couple of times anyway.
+    for _ in 0..3 {
+        let id = new_share_id();
+        if let Some(share) = db
+            .create_share(&id, name.as_deref(), &request.grocery_list, request.editable, expires_at)
+            .await
+            .context("creating shared list")?
+        {
+            return Ok((StatusCode::CREATED, Json(share)));
+        }
+    }
+    Err(anyhow::anyhow!("no free share ID after 3 attempts").into())
+}
+
+#[utoipa::path(
+    get,
+    path = "/shares/{id}",
+    tag = "shares",
+    params(("id" = String, Path, description = "Short share ID")),
+    responses(
+        (status = 200, description = "The shared list", body = SharedList),
+        (status = 404, description = "Unknown (`share_not_found`) or expired (`share_expired`)", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_share(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<String>, PathRejection>,
+) -> Result<Json<SharedList>, ApiError> {
+    let Path(id) = id?;
+    Ok(Json(live_share(&db, &id).await?))
+}
+
+/// Replaces an editable share's name and list. Read-only shares answer 403.
+#[utoipa::path(
+    put,
+    path = "/shares/{id}",
+    tag = "shares",
+    params(("id" = String, Path, description = "Short share ID")),
+    request_body = ShareUpdateRequest,
+    responses(
+        (status = 200, description = "Updated share", body = SharedList),
+        (status = 400, description = "Invalid list or name", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Share is read-only", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Unknown or expired share", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn update_share(
+    State(db): State<Arc<DatabaseManager>>,
+    id: Result<Path<String>, PathRejection>,
+    request: Result<Json<ShareUpdateRequest>, JsonRejection>,
+) -> Result<Json<SharedList>, ApiError> {
+    let Path(id) = id?;
+    let Json(request) = request?;
+    let name = normalized_name(request.name);
+    let mut errors = entry_errors(&request.grocery_list);
+    errors.extend(share_name_errors(name.as_deref()));
+    validate(errors)?;
+
+    if !live_share(&db, &id).await?.editable {
+        return Err(ApiError::forbidden("share_read_only", format!("shared list {} is read-only", id)));
+    }
+    let share = db
+        .update_share(&id, name.as_deref(), &request.grocery_list)
+        .await
+        .with_context(|| format!("updating shared list {}", id))?
+        // Expired between the check and the update.
+        .ok_or_else(|| share_not_found(&id))?;
+    Ok(Json(share))
+}
+
+/// Short-link routes — the same in every API version.
+pub fn share_routes() -> Router<AppState> {
+    Router::new()
+        .route("/shares", post(create_share))
+        .route("/shares/:id", get(get_share).put(update_share))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_ids_are_base62() {
+        let id = new_share_id();
+        assert_eq!(id.len(), SHARE_ID_LEN);
+        assert!(is_share_id(&id));
+        assert_ne!(id, new_share_id());
+    }
+
+    #[test]
+    fn share_id_format() {
+        assert!(is_share_id("aZ09xY12Qk"));
+        assert!(!is_share_id(""));
+        assert!(!is_share_id("../etc"));
+        assert!(!is_share_id("a".repeat(17).as_str()));
+    }
+
+    #[test]
+    fn blank_names_dropped() {
+        assert_eq!(normalized_name(Some("  ".to_string())), None);
+        assert_eq!(normalized_name(Some(" שבת ".to_string())), Some("שבת".to_string()));
+        assert_eq!(share_name_errors(Some("x".repeat(101).as_str()))[0].code, "name_length");
+    }
+}
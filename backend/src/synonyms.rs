@@ -0,0 +1,57 @@
+//! Search-term expansion through the operator-managed `search_synonyms` table.
+//!
+//! A term is searched as itself plus one variant per rule whose words appear in
+//! it, with those words swapped for the rule's other side: "שמנת מתוקה 38%" is
+//! also searched as "שמנת להקצפה 38%". Expansion is one level deep.
+
+use crate::hebrew;
+
+/// Variants searched per term, the term itself included.
+const MAX_VARIANTS: usize = 5;
+
+/// A rule as applied, normalized: `from` in a term may be read as `to`. Bidirectional
+/// rows yield a rule each way.
+pub type SynonymRule = (String, String);
+
+/// `term` normalized, followed by its rule expansions (deduplicated).
+pub fn expand(term: &str, rules: &[SynonymRule]) -> Vec<String> {
+    let normalized = hebrew::normalize(term);
+    let words: Vec<&str> = normalized.split(' ').collect();
+    let mut variants = vec![normalized.clone()];
+    for (from, to) in rules {
+        let from_words: Vec<&str> = from.split(' ').collect();
+        let Some(start) = words.windows(from_words.len()).position(|w| w == from_words.as_slice()) else {
+            continue;
+        };
+        let variant = [&words[..start], &[to.as_str()], &words[start + from_words.len()..]].concat().join(" ");
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+        if variants.len() == MAX_VARIANTS {
+            break;
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(from: &str, to: &str) -> SynonymRule {
+        (hebrew::normalize(from), hebrew::normalize(to))
+    }
+
+    #[test]
+    fn whole_words_replaced() {
+        let rules = [rule("שמנת מתוקה", "שמנת להקצפה"), rule("קולה", "קוקה קולה"), rule("חלב", "משקה חלב")];
+        assert_eq!(
+            expand("שמנת מתוקה 38%", &rules),
+            [hebrew::normalize("שמנת מתוקה 38%"), hebrew::normalize("שמנת להקצפה 38%")]
+        );
+        assert_eq!(expand("קולה זירו", &rules), ["קלה זרו", "קקה קלה זרו"]);
+        // "חלבון" contains "חלב" but not as a word.
+        assert_eq!(expand("חלבון", &rules), ["חלבנ"]);
+        assert_eq!(expand("", &rules), [""]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Pack sizes from the price files, normalized so "1 ליטר" and "1000 מ"ל" compare equal.
+//!
+//! Chains fill `Quantity` with the amount and put the unit in `UnitQty` or
+//! `UnitOfMeasure` — the latter sometimes with a per-price prefix ("100 גרם").
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BaseUnit {
+    Gram,
+    Milliliter,
+    Unit,
+}
+
+impl BaseUnit {
+    /// The serialized name, as stored in `product_clusters.size_unit`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BaseUnit::Gram => "gram",
+            BaseUnit::Milliliter => "milliliter",
+            BaseUnit::Unit => "unit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [BaseUnit::Gram, BaseUnit::Milliliter, BaseUnit::Unit].into_iter().find(|u| u.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub amount: f64,
+    pub unit: BaseUnit,
+}
+
+/// Parses a unit label into (factor to the base unit, base unit).
+pub fn parse_unit(label: &str) -> Option<(f64, BaseUnit)> {
+    // Drop a leading per-price amount ("100 גרם") and gershayim/punctuation ("ק\"ג", "יח'").
+    let label = label.trim().trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace());
+    let key: String = label
+        .chars()
+        .filter(|c| !matches!(c, '"' | '\'' | '״' | '׳' | '.' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect();
+    let unit = match key.as_str() {
+        "גרם" | "גר" | "ג" | "g" | "gr" | "gram" | "grams" => (1.0, BaseUnit::Gram),
+        "קילוגרם" | "קילו" | "קג" | "kg" | "kilogram" => (1000.0, BaseUnit::Gram),
+        "מיליליטר" | "מל" | "ml" | "milliliter" | "millilitre" => (1.0, BaseUnit::Milliliter),
+        "ליטר" | "ל" | "l" | "lt" | "ltr" | "liter" | "litre" => (1000.0, BaseUnit::Milliliter),
+        "יחידה" | "יחידות" | "יח" | "unit" | "units" => (1.0, BaseUnit::Unit),
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// `quantity` × the first of `units` that parses. None when the amount isn't a
+/// positive number or no unit is recognized.
+pub fn normalized_size(quantity: Option<&str>, units: &[Option<&str>]) -> Option<Size> {
+    let amount: f64 = quantity?.trim().parse().ok()?;
+    if !(amount > 0.0 && amount.is_finite()) {
+        return None;
+    }
+    let (factor, unit) = units.iter().flatten().find_map(|label| parse_unit(label))?;
+    Some(Size { amount: amount * factor, unit })
+}
+
+/// `name` without its pack size: unit words, amounts in front of them and fused
+/// forms like "250גרם" go; everything else (including "5%") stays. Lowercased.
+pub fn strip_size(name: &str) -> String {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let is_amount = |w: &str| w.replace(',', ".").parse::<f64>().is_ok();
+    words
+        .iter()
+        .enumerate()
+        .filter(|(i, word)| {
+            let unit = parse_unit(word).is_some();
+            let amount_before_unit = is_amount(word) && words.get(i + 1).is_some_and(|next| parse_unit(next).is_some());
+            !unit && !amount_before_unit
+        })
+        .map(|(_, word)| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `candidate` / `original`, when both are in the same base unit.
+pub fn size_ratio(candidate: Size, original: Size) -> Option<f64> {
+    (candidate.unit == original.unit).then(|| candidate.amount / original.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_parsed() {
+        assert_eq!(parse_unit("גרם"), Some((1.0, BaseUnit::Gram)));
+        assert_eq!(parse_unit("100 גרם"), Some((1.0, BaseUnit::Gram)));
+        assert_eq!(parse_unit("ק\"ג"), Some((1000.0, BaseUnit::Gram)));
+        assert_eq!(parse_unit("מ״ל"), Some((1.0, BaseUnit::Milliliter)));
+        assert_eq!(parse_unit(" Liter "), Some((1000.0, BaseUnit::Milliliter)));
+        assert_eq!(parse_unit("יח'"), Some((1.0, BaseUnit::Unit)));
+        assert_eq!(parse_unit("Unknown"), None);
+    }
+
+    #[test]
+    fn sizes_compare_across_units() {
+        let litre = normalized_size(Some("1.00"), &[Some("ליטר")]).unwrap();
+        let ml = normalized_size(Some("1000"), &[None, Some("מ\"ל")]).unwrap();
+        assert_eq!(size_ratio(ml, litre), Some(1.0));
+        let grams = normalized_size(Some("500"), &[Some("גרם")]).unwrap();
+        assert_eq!(size_ratio(grams, litre), None);
+        assert_eq!(normalized_size(Some("0"), &[Some("גרם")]), None);
+        assert_eq!(normalized_size(Some("abc"), &[Some("גרם")]), None);
+    }
+
+    #[test]
+    fn sizes_stripped_from_names() {
+        assert_eq!(strip_size("קוטג' 5% 250 גרם"), "קוטג' 5%");
+        assert_eq!(strip_size("קוטג' 5% 500גרם"), "קוטג' 5%");
+        assert_eq!(strip_size("Milk 3% 1 L"), "milk 3%");
+        assert_eq!(strip_size("במבה 80"), "במבה 80");
+    }
+}
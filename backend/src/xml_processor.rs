@@ -1,13 +1,116 @@
 use anyhow::Result;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::{path::Path, sync::mpsc, thread, time::Duration};
+use std::{
+    path::Path,
+    sync::{atomic::{AtomicBool, AtomicI64, Ordering}, mpsc},
+    thread,
+    time::Duration,
+};
 use tokio::fs;
 use tracing::{error, info};
 use sqlx::Row;
 
 use crate::database::{DatabaseManager, is_ean13};
+use crate::metrics;
 use crate::models::{XmlRoot, Item, StoresFullRoot};
 
+/// Unix time of the watcher thread's last loop iteration (0 = never started).
+/// The loop wakes at least once a second, so a stale value means it has died.
+static WATCHER_HEARTBEAT: AtomicI64 = AtomicI64::new(0);
+
+/// When the file watcher thread last checked in, if it has ever run.
+pub fn watcher_heartbeat() -> Option<chrono::DateTime<chrono::Utc>> {
+    match WATCHER_HEARTBEAT.load(Ordering::Relaxed) {
+        0 => None,
+        ts => chrono::DateTime::from_timestamp(ts, 0),
+    }
+}
+
+/// Set by the admin API. While paused the watcher ignores new files and scans stop
+/// early; anything that arrives stays unprocessed in the ledger for the next scan.
+static INGEST_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// True while a watch-directory scan is running, so operators can't stack them.
+static SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn ingest_paused() -> bool {
+    INGEST_PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_ingest_paused(paused: bool) {
+    INGEST_PAUSED.store(paused, Ordering::Relaxed);
+}
+
+pub fn scan_in_progress() -> bool {
+    SCAN_RUNNING.load(Ordering::Relaxed)
+}
+
+/// Holds [`SCAN_RUNNING`] for the lifetime of one scan.
+struct ScanGuard;
+
+impl ScanGuard {
+    fn acquire() -> Option<Self> {
+        SCAN_RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        SCAN_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Promotional files have a different XML schema (Promotions, not Items) and are not ingested.
+fn is_promo_file(filename_lower: &str) -> bool {
+    filename_lower.contains("promo")
+}
+
+/// Number of XML files in `watch_directory` that still need ingesting: not promo
+/// files, and not in the processed_files ledger with their current size.
+pub async fn pending_file_count(db: &DatabaseManager, watch_directory: &str) -> Result<usize> {
+    let processed = db.processed_file_sizes().await?;
+    let mut dir = fs::read_dir(watch_directory).await?;
+    let mut pending = 0usize;
+    while let Some(entry) = dir.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("xml") {
+            continue;
+        }
+        let filename = entry.file_name().to_string_lossy().to_string();
+        if is_promo_file(&filename.to_lowercase()) {
+            continue;
+        }
+        let size = entry.metadata().await.map(|m| m.len() as i64).unwrap_or(0);
+        if processed.get(&filename) != Some(&size) {
+            pending += 1;
+        }
+    }
+    Ok(pending)
+}
+
+/// Best-effort chain ID for a price file, used to label metrics before (or
+/// without) a successful parse: the first 13-digit run in the filename, e.g.
+/// `7290058108879-001-Price-…` or `PriceFull7290027600007-…`.
+fn chain_from_filename(filename: &str) -> &str {
+    let bytes = filename.as_bytes();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if end - start == 13 {
+            return &filename[start..end];
+        }
+        start = end;
+    }
+    "unknown"
+}
+
 pub struct XmlFileProcessor {
     db_manager: DatabaseManager,
     watch_directory: String,
@@ -29,6 +132,7 @@ impl XmlFileProcessor {
             .file_name()
             .map(|n| n.to_string_lossy().to_lowercase())
             .unwrap_or_default();
+        let chain = chain_from_filename(&filename);
 
         // Size cap on untrusted retailer XML so one huge/malicious file can't
         // exhaust memory (ARCHITECTURE.md §5.2). Largest real PriceFull files
@@ -39,6 +143,8 @@ impl XmlFileProcessor {
             .unwrap_or(256 * 1024 * 1024);
         let size = fs::metadata(file_path).await?.len();
         if size > max_bytes {
+            metrics::record_ingest_error(chain, "size");
+            metrics::record_ingest_file(chain, "failed");
             return Err(anyhow::anyhow!(
                 "XML parsing error: file {} is {} bytes, over the {} byte cap",
                 filename, size, max_bytes
@@ -50,22 +156,34 @@ impl XmlFileProcessor {
         if filename.contains("storesfull") || filename.contains("stores") {
             match serde_xml_rs::from_str::<StoresFullRoot>(&content) {
                 Ok(stores_data) => {
+                    let chain_id = stores_data.chain_id.clone();
                     self.process_stores_full(stores_data).await?;
+                    metrics::record_ingest_file(&chain_id, "processed");
                     info!("Successfully processed StoresFull: {}", file_path_str);
                 }
                 Err(e) => {
                     error!("Failed to parse StoresFull XML {}: {}", file_path_str, e);
+                    metrics::record_ingest_error(chain, "parse");
+                    metrics::record_ingest_file(chain, "failed");
                     return Err(anyhow::anyhow!("StoresFull XML parsing error: {}", e));
                 }
             }
         } else {
             match serde_xml_rs::from_str::<XmlRoot>(&content) {
                 Ok(xml_data) => {
-                    self.process_xml_data(xml_data, &file_path_str).await?;
+                    let chain_id = xml_data.chain_id.clone();
+                    if let Err(e) = self.process_xml_data(xml_data, &file_path_str).await {
+                        metrics::record_ingest_error(&chain_id, "store");
+                        metrics::record_ingest_file(&chain_id, "failed");
+                        return Err(e);
+                    }
+                    metrics::record_ingest_file(&chain_id, "processed");
                     info!("Successfully processed: {}", file_path_str);
                 }
                 Err(e) => {
                     error!("Failed to parse XML file {}: {}", file_path_str, e);
+                    metrics::record_ingest_error(chain, "parse");
+                    metrics::record_ingest_file(chain, "failed");
                     return Err(anyhow::anyhow!("XML parsing error: {}", e));
                 }
             }
@@ -75,16 +193,30 @@ impl XmlFileProcessor {
     }
 
     pub async fn scan_existing_files(&self) -> Result<()> {
+        let Some(_guard) = ScanGuard::acquire() else {
+            info!("Scan of {} already running, not starting another", self.watch_directory);
+            return Ok(());
+        };
         info!("Scanning existing XML files in: {}", self.watch_directory);
 
+        // List the backlog first so the queue gauge shows all of it while the scan runs.
         let mut dir = fs::read_dir(&self.watch_directory).await?;
+        let mut files = Vec::new();
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("xml") {
+                let file_size = entry.metadata().await.map(|m| m.len() as i64).unwrap_or(0);
+                files.push((path, file_size, metrics::QueuedFile::enqueue()));
+            }
+        }
         let mut skipped = 0usize;
         let mut processed = 0usize;
 
-        while let Some(entry) = dir.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("xml") {
-                continue;
+        // Each file's queue slot drops after its iteration; a paused scan drops the rest.
+        for (path, file_size, _queued) in files {
+            if ingest_paused() {
+                info!("Ingestion paused, stopping scan early");
+                break;
             }
 
             let filename = path
@@ -92,12 +224,9 @@ impl XmlFileProcessor {
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
 
-            let file_size = entry.metadata().await.map(|m| m.len() as i64).unwrap_or(0);
-
             // Skip promotional files — they have a different XML structure (Promotions, not Items)
             // and we don't need promo data. Mark immediately so they're not re-checked.
-            let lower_fname = filename.to_lowercase();
-            if lower_fname.contains("promo") || lower_fname.starts_with("nullpromo") {
+            if is_promo_file(&filename.to_lowercase()) {
                 if let Err(e) = self.db_manager.mark_file_processed(&filename, file_size).await {
                     error!("Error marking promo file as skipped {}: {}", filename, e);
                 }
@@ -124,10 +253,10 @@ impl XmlFileProcessor {
                     // Parse errors are permanent — the file will never succeed, so mark it
                     // done to avoid retry loops. Transient failures (DB connectivity etc.)
                     // stay unmarked so the next scan retries them.
-                    if e.to_string().contains("parsing error") {
-                        if let Err(e2) = self.db_manager.mark_file_processed(&filename, file_size).await {
-                            error!("Error marking unparseable file {}: {}", filename, e2);
-                        }
+                    if e.to_string().contains("parsing error")
+                        && let Err(e2) = self.db_manager.mark_file_processed(&filename, file_size).await
+                    {
+                        error!("Error marking unparseable file {}: {}", filename, e2);
                     }
                 }
             }
@@ -137,6 +266,26 @@ impl XmlFileProcessor {
             "Scan complete: {} files processed, {} already-done files skipped",
             processed, skipped
         );
+        if processed > 0 {
+            match self.db_manager.elect_canonical_names().await {
+                Ok(summary) => info!("Canonical names elected: {:?}", summary),
+                Err(e) => error!("Error electing canonical names: {}", e),
+            }
+            match self.db_manager.rebuild_product_clusters().await {
+                Ok(summary) => info!("Product clusters rebuilt: {:?}", summary),
+                Err(e) => error!("Error rebuilding product clusters: {}", e),
+            }
+            // Picks up rule changes and items ingested before their rules existed.
+            match self.db_manager.reclassify_items(None, None).await {
+                Ok(summary) => info!("Items reclassified: {:?}", summary),
+                Err(e) => error!("Error reclassifying items: {}", e),
+            }
+            // Items ingested before the manufacturer table existed.
+            match self.db_manager.sync_manufacturers().await {
+                Ok(registered) => info!("Manufacturer spellings registered: {}", registered),
+                Err(e) => error!("Error registering manufacturers: {}", e),
+            }
+        }
         Ok(())
     }
 
@@ -145,10 +294,10 @@ impl XmlFileProcessor {
         
         let mut watcher: RecommendedWatcher = Watcher::new(
             move |res: notify::Result<Event>| {
-                if let Ok(event) = res {
-                    if let Err(e) = tx.send(event) {
-                        error!("Error sending file event: {}", e);
-                    }
+                if let Ok(event) = res
+                    && let Err(e) = tx.send(event)
+                {
+                    error!("Error sending file event: {}", e);
                 }
             },
             notify::Config::default(),
@@ -164,6 +313,7 @@ impl XmlFileProcessor {
 
         thread::spawn(move || {
             loop {
+                WATCHER_HEARTBEAT.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
                 match rx.recv_timeout(Duration::from_secs(1)) {
                     Ok(event) => {
                         if let EventKind::Create(_) | EventKind::Modify(_) = event.kind {
@@ -174,7 +324,11 @@ impl XmlFileProcessor {
                                     let fname_lower = path.file_name()
                                         .map(|n| n.to_string_lossy().to_lowercase())
                                         .unwrap_or_default();
-                                    if fname_lower.contains("promo") {
+                                    if is_promo_file(&fname_lower) {
+                                        continue;
+                                    }
+                                    if ingest_paused() {
+                                        info!("Ingestion paused, leaving {:?} for the next scan", path);
                                         continue;
                                     }
                                     info!("New/modified XML file detected: {:?}", path);
@@ -182,23 +336,25 @@ impl XmlFileProcessor {
                                     let path_clone = path.clone();
                                     let db_for_task = db_clone.clone();
                                     let dir_for_task = watch_dir.clone();
+                                    let queued = metrics::QueuedFile::enqueue();
                                     rt.spawn(async move {
                                         tokio::time::sleep(Duration::from_secs(2)).await;
                                         info!("File ready for processing: {:?}", path_clone);
-                                        if let Ok(metadata) = tokio::fs::metadata(&path_clone).await {
-                                            if metadata.is_file() {
-                                                let processor = XmlFileProcessor { db_manager: db_for_task, watch_directory: dir_for_task };
-                                                if let Err(e) = processor.process_xml_file(&path_clone).await {
-                                                    error!("Error processing new file {:?}: {}", path_clone, e);
-                                                } else {
-                                                    let filename = path_clone.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
-                                                    let file_size = metadata.len() as i64;
-                                                    if let Err(e) = processor.db_manager.mark_file_processed(&filename, file_size).await {
-                                                        error!("Error marking {} as processed: {}", filename, e);
-                                                    }
+                                        if let Ok(metadata) = tokio::fs::metadata(&path_clone).await
+                                            && metadata.is_file()
+                                        {
+                                            let processor = XmlFileProcessor { db_manager: db_for_task, watch_directory: dir_for_task };
+                                            if let Err(e) = processor.process_xml_file(&path_clone).await {
+                                                error!("Error processing new file {:?}: {}", path_clone, e);
+                                            } else {
+                                                let filename = path_clone.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                                                let file_size = metadata.len() as i64;
+                                                if let Err(e) = processor.db_manager.mark_file_processed(&filename, file_size).await {
+                                                    error!("Error marking {} as processed: {}", filename, e);
                                                 }
                                             }
                                         }
+                                        drop(queued);
                                     });
                                 }
                             }
@@ -219,6 +375,15 @@ impl XmlFileProcessor {
         Ok(())
     }
 
+    /// Processes one file from the watch directory regardless of the ledger, then
+    /// records it as processed. Used by the admin force-reingest.
+    pub async fn reingest_file(&self, filename: &str) -> Result<()> {
+        let path = Path::new(&self.watch_directory).join(filename);
+        let file_size = fs::metadata(&path).await?.len() as i64;
+        self.process_xml_file(&path).await?;
+        self.db_manager.mark_file_processed(filename, file_size).await
+    }
+
     async fn process_stores_full(&self, stores_data: StoresFullRoot) -> Result<()> {
         let chain_id = &stores_data.chain_id;
         let mut updated = 0usize;
@@ -251,7 +416,9 @@ impl XmlFileProcessor {
 
         let mut inserted = 0u64;
         let mut skipped = 0u64;
+        let mut failed = 0u64;
         let total = xml_data.items.items.len();
+        let mut new_items = Vec::new();
 
         for item in xml_data.items.items {
             match self.insert_item(store_id, &item, file_path).await {
@@ -259,18 +426,33 @@ impl XmlFileProcessor {
                     if rows_affected > 0 {
                         inserted += 1;
                         // Populate the product catalog for barcode items
-                        if is_ean13(&item.item_code) {
-                            if let Err(e) = self.db_manager.upsert_product(&item).await {
-                                error!("Error upserting product {}: {}", item.item_code, e);
-                            }
+                        if is_ean13(&item.item_code)
+                            && let Err(e) = self.db_manager.upsert_product(&item).await
+                        {
+                            error!("Error upserting product {}: {}", item.item_code, e);
                         }
+                        new_items.push(item);
                     } else {
                         skipped += 1;
                     }
                 }
-                Err(e) => error!("Error inserting item {}: {}", item.item_code, e),
+                Err(e) => {
+                    failed += 1;
+                    error!("Error inserting item {}: {}", item.item_code, e);
+                }
             }
         }
+        metrics::record_ingest_items(&xml_data.chain_id, inserted, failed);
+        if let Err(e) = self.db_manager.record_product_variants(&xml_data.chain_id, &new_items).await {
+            error!("Error recording product variants from {}: {}", file_path, e);
+        }
+        if let Err(e) = self.db_manager.classify_new_items(&xml_data.chain_id, &new_items).await {
+            error!("Error classifying items from {}: {}", file_path, e);
+        }
+        let manufacturers: Vec<String> = new_items.iter().filter_map(|item| item.manufacturer_name.clone()).collect();
+        if let Err(e) = self.db_manager.register_manufacturers(&manufacturers).await {
+            error!("Error registering manufacturers from {}: {}", file_path, e);
+        }
 
         info!(
             "Inserted {}/{} items ({} already existed) from {}",
@@ -371,3 +553,24 @@ impl XmlFileProcessor {
         Ok(rows)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::chain_from_filename;
+
+    #[test]
+    fn chain_id_prefix() {
+        assert_eq!(chain_from_filename("7290058108879-001-price-202506031024.xml"), "7290058108879");
+    }
+
+    #[test]
+    fn chain_id_after_file_type() {
+        assert_eq!(chain_from_filename("pricefull7290027600007-001-202506030300.xml"), "7290027600007");
+    }
+
+    #[test]
+    fn no_chain_id() {
+        assert_eq!(chain_from_filename("stores.xml"), "unknown");
+        assert_eq!(chain_from_filename("price-202506031024.xml"), "unknown");
+    }
+}
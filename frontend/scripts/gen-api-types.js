@@ -0,0 +1,99 @@
+#!/usr/bin/env node
+// Generates src/types/api.generated.ts from the backend's checked-in OpenAPI spec
+// (backend/openapi.json): one exported type per component schema.
+//
+//   node scripts/gen-api-types.js           rewrite the file
+//   node scripts/gen-api-types.js --check   exit 1 when the file is out of date (CI)
+//
+// No dependencies on purpose, so the check runs before `npm ci` and offline.
+const fs = require('fs');
+const path = require('path');
+
+const SPEC = path.resolve(__dirname, '../../backend/openapi.json');
+const OUT = path.resolve(__dirname, '../src/types/api.generated.ts');
+
+const HEADER = `// Generated from backend/openapi.json by scripts/gen-api-types.js — do not edit.
+// Regenerate with \`npm run gen:api-types\`; CI fails when this file is out of date.
+`;
+
+function refName(ref) {
+  return ref.replace('#/components/schemas/', '');
+}
+
+function docComment(description, indent) {
+  if (!description) return '';
+  const lines = description.split('\n');
+  if (lines.length === 1) return `${indent}/** ${lines[0]} */\n`;
+  return `${indent}/**\n${lines.map(l => `${indent} *${l ? ' ' + l : ''}`).join('\n')}\n${indent} */\n`;
+}
+
+// `T | null` rather than `null | T`, however the spec orders it.
+function union(types) {
+  const unique = [...new Set(types)];
+  return [...unique.filter(t => t !== 'null'), ...unique.filter(t => t === 'null')].join(' | ');
+}
+
+function typeOf(schema, indent) {
+  if (schema.$ref) return refName(schema.$ref);
+  if (schema.oneOf) return union(schema.oneOf.map(s => typeOf(s, indent)));
+  if (schema.allOf) return schema.allOf.map(s => typeOf(s, indent)).join(' & ');
+  if (schema.enum) return union(schema.enum.map(v => (v === null ? 'null' : `'${v}'`)));
+  const types = Array.isArray(schema.type) ? schema.type : [schema.type];
+  return union(types.map(t => {
+    switch (t) {
+      case 'string': return 'string';
+      case 'integer':
+      case 'number': return 'number';
+      case 'boolean': return 'boolean';
+      case 'null': return 'null';
+      case 'array': {
+        const item = typeOf(schema.items || {}, indent);
+        return /[|&]/.test(item) ? `(${item})[]` : `${item}[]`;
+      }
+      case 'object': return objectType(schema, indent);
+      default: return 'unknown';
+    }
+  }));
+}
+
+function objectType(schema, indent) {
+  const props = schema.properties || {};
+  if (Object.keys(props).length === 0) {
+    return schema.additionalProperties ? `Record<string, ${typeOf(schema.additionalProperties, indent)}>` : 'Record<string, never>';
+  }
+  const required = new Set(schema.required || []);
+  const inner = indent + '  ';
+  const fields = Object.entries(props).map(([name, prop]) => {
+    const doc = prop.description || (prop.oneOf || []).map(s => s.description).find(Boolean);
+    const optional = required.has(name) ? '' : '?';
+    return `${docComment(doc, inner)}${inner}${name}${optional}: ${typeOf(prop, inner)};`;
+  });
+  return `{\n${fields.join('\n')}\n${indent}}`;
+}
+
+function generate(spec) {
+  const schemas = spec.components.schemas;
+  const decls = Object.keys(schemas).sort().map(name => {
+    const schema = schemas[name];
+    const doc = docComment(schema.description, '');
+    const body = typeOf(schema, '');
+    if (body.startsWith('{')) return `${doc}export interface ${name} ${body}\n`;
+    return `${doc}export type ${name} = ${body};\n`;
+  });
+  return `${HEADER}\n${decls.join('\n')}`;
+}
+
+if (require.main === module) {
+  const output = generate(JSON.parse(fs.readFileSync(SPEC, 'utf8')));
+  if (process.argv.includes('--check')) {
+    const current = fs.existsSync(OUT) ? fs.readFileSync(OUT, 'utf8') : '';
+    if (current !== output) {
+      console.error(`${path.relative(process.cwd(), OUT)} is out of date; run \`npm run gen:api-types\`.`);
+      process.exit(1);
+    }
+  } else {
+    fs.writeFileSync(OUT, output);
+  }
+}
+
+module.exports = { generate };
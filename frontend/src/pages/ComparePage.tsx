@@ -13,8 +13,10 @@ import {
   DialogContent,
   DialogTitle,
   Divider,
+  FormControlLabel,
   IconButton,
   Stack,
+  Switch,
   Table,
   TableBody,
   TableCell,
@@ -34,10 +36,10 @@ import { apiService } from '../services/api';
 import { useCart } from '../context/CartContext';
 import {
   GroceryItem,
-  PriceComparisonRequest,
-  PriceComparisonResponse,
+  PriceComparisonRequestV2,
+  PriceComparisonResponseV2,
   ProductSearchResult,
-  StoreComparison,
+  StoreBasket,
   UserLocation,
 } from '../types';
 
@@ -51,12 +53,8 @@ declare class BarcodeDetector {
 const PAGE_SIZE = 10;
 
 // ---------------------------------------------------------------------------
-// URL encoding helpers for share-list feature
+// Decoding for old ?q= share links (new links are ?s=<short id>)
 // ---------------------------------------------------------------------------
-function encodeList(items: GroceryItem[]): string {
-  return btoa(encodeURIComponent(JSON.stringify(items)));
-}
-
 function decodeList(encoded: string): GroceryItem[] | null {
   try {
     return JSON.parse(decodeURIComponent(atob(encoded)));
@@ -80,8 +78,8 @@ const ComparePage: React.FC = () => {
   const [gpsStatus, setGpsStatus] = useState<'idle' | 'active' | 'denied'>('idle');
 
   // --- results state ---
-  const [results, setResults] = useState<StoreComparison[]>([]);
-  const [bestStore, setBestStore] = useState<StoreComparison | null>(null);
+  const [results, setResults] = useState<StoreBasket[]>([]);
+  const [bestStore, setBestStore] = useState<StoreBasket | null>(null);
   const [totalStores, setTotalStores] = useState(0);
   const [hasMore, setHasMore] = useState(false);
   const [currentPage, setCurrentPage] = useState(1);
@@ -92,6 +90,9 @@ const ComparePage: React.FC = () => {
   const [loadingMore, setLoadingMore] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
+  // --- ranking: fold the round trip into the cost (GPS only) ---
+  const [rankByTravel, setRankByTravel] = useState(false);
+
   // --- share ---
   const [shareCopied, setShareCopied] = useState(false);
 
@@ -117,12 +118,17 @@ const ComparePage: React.FC = () => {
   useEffect(() => { loadingMoreRef.current = loadingMore; }, [loadingMore]);
 
   // ---------------------------------------------------------------------------
-  // On mount: restore list from URL (?q=...) if present
+  // On mount: restore list from URL (?s=<share id>, or legacy ?q=...) if present
   // ---------------------------------------------------------------------------
   useEffect(() => {
     const params = new URLSearchParams(window.location.search);
+    const s = params.get('s');
     const q = params.get('q');
-    if (q) {
+    if (s) {
+      apiService.getShare(s)
+        .then(share => setItems(share.grocery_list.map(e => ({ barcode: e.barcode ?? null, name: e.name }))))
+        .catch(() => setError('הקישור לרשימה אינו תקף או שפג תוקפו'));
+    } else if (q) {
       const decoded = decodeList(q);
       if (decoded && decoded.length > 0) setItems(decoded);
     }
@@ -163,7 +169,7 @@ const ComparePage: React.FC = () => {
   const addItem = (input: ProductSearchResult | string) => {
     const grocery: GroceryItem = typeof input === 'string'
       ? { barcode: null, name: input.trim() }
-      : { barcode: input.barcode, name: input.name };
+      : { barcode: input.barcode ?? null, name: input.name };
 
     if (!grocery.name || items.some(i =>
       (i.barcode && i.barcode === grocery.barcode) || i.name === grocery.name
@@ -175,18 +181,18 @@ const ComparePage: React.FC = () => {
 
   const removeItem = (name: string) => cartRemoveItem(name);
 
-  // Resolve a grocery list term (may be a barcode) back to a display name.
-  const resolveTermName = (term: string): string => {
-    const found = items.find(i => i.barcode === term || i.name === term);
-    return found ? found.name : term;
-  };
-
   // ---------------------------------------------------------------------------
   // Share list
   // ---------------------------------------------------------------------------
   const shareList = async () => {
-    const encoded = encodeList(items);
-    const url = `${window.location.origin}${window.location.pathname}?q=${encoded}`;
+    let url: string;
+    try {
+      const share = await apiService.createShare(items.map(i => ({ barcode: i.barcode, name: i.name, quantity: 1 })));
+      url = `${window.location.origin}${window.location.pathname}?s=${share.id}`;
+    } catch {
+      setError('לא ניתן ליצור קישור לשיתוף');
+      return;
+    }
     try {
       await navigator.clipboard.writeText(url);
       setShareCopied(true);
@@ -241,14 +247,10 @@ const ComparePage: React.FC = () => {
         if (barcodes.length > 0) {
           const raw = barcodes[0].rawValue;
           stopScan();
-          // Try to resolve barcode to a product name via the search API
+          // Resolve the barcode to its catalog name; unknown barcodes (404) keep the raw code
           try {
-            const found = await apiService.searchItemNames(raw);
-            if (found.length > 0) {
-              addItem(found[0]);
-            } else {
-              addItem({ barcode: raw, name: raw });
-            }
+            const product = await apiService.getProduct(raw);
+            addItem({ barcode: product.barcode, name: product.canonical_name });
           } catch {
             addItem({ barcode: raw, name: raw });
           }
@@ -291,24 +293,25 @@ const ComparePage: React.FC = () => {
     else setLoadingMore(true);
 
     try {
-      const payload: PriceComparisonRequest = {
-        grocery_list: grocery.map(i => i.barcode ?? i.name),
+      const payload: PriceComparisonRequestV2 = {
+        grocery_list: grocery.map(i => ({ barcode: i.barcode, name: i.name, quantity: 1 })),
         page,
         page_size: PAGE_SIZE,
         ...(location ? { user_location: location } : {}),
+        ...(location && rankByTravel ? { ranking: 'effective_cost' as const } : {}),
         ...(!location && city.trim() ? { city: city.trim() } : {}),
       };
-      const data: PriceComparisonResponse = await apiService.comparePrices(payload);
+      const data: PriceComparisonResponseV2 = await apiService.comparePrices(payload);
 
       if (append) {
         setResults(prev => [...prev, ...data.stores]);
       } else {
         setResults(data.stores);
-        setBestStore(data.best_store ?? null);
+        setBestStore(data.stores.find(s => s.store.id === data.best_store_id) ?? null);
         setSearched(true);
       }
-      setTotalStores(data.total_stores);
-      setHasMore(data.has_more);
+      setTotalStores(data.pagination.total);
+      setHasMore(data.pagination.has_more);
       setCurrentPage(page);
     } catch (e: any) {
       const isNetwork = !e?.response;
@@ -317,7 +320,7 @@ const ComparePage: React.FC = () => {
       setLoading(false);
       setLoadingMore(false);
     }
-  }, [location, city]);
+  }, [location, city, rankByTravel]);
 
   const runSearch = () => {
     setResults([]);
@@ -343,7 +346,7 @@ const ComparePage: React.FC = () => {
   // ---------------------------------------------------------------------------
   // Render a store comparison card
   // ---------------------------------------------------------------------------
-  const renderCard = (sc: StoreComparison, isBest: boolean) => {
+  const renderCard = (sc: StoreBasket, isBest: boolean) => {
     const s = sc.store;
     const label = s.store_name || `${s.chain_id} — סניף ${s.store_id}`;
     const locationStr = [s.city, s.address].filter(Boolean).join(' · ');
@@ -381,8 +384,15 @@ const ComparePage: React.FC = () => {
                 ₪{sc.total_price.toFixed(2)}
               </Typography>
               <Typography variant="caption" color="text.secondary">
-                {sc.items_found} / {sc.items_found + sc.items_missing.length} פריטים
+                {sc.items_found} / {sc.lines.length} פריטים
               </Typography>
+              {sc.score && (
+                <Tooltip title={`נסיעה ₪${sc.score.travel_cost.toFixed(2)} · זמן ₪${sc.score.time_cost.toFixed(2)} (הלוך ושוב)`}>
+                  <Typography variant="caption" color="text.secondary" display="block">
+                    כולל נסיעה: ₪{sc.score.effective_cost.toFixed(2)}
+                  </Typography>
+                </Tooltip>
+              )}
             </Box>
           </Box>
 
@@ -391,7 +401,7 @@ const ComparePage: React.FC = () => {
           {/* Per-item price breakdown */}
           <Table size="small" sx={{ '& td': { border: 'none', py: 0.4, px: 0.5 } }}>
             <TableBody>
-              {sc.items.map(it => (
+              {sc.lines.flatMap(line => (line.item ? [line.item] : [])).map(it => (
                 <TableRow key={it.item_code}>
                   <TableCell sx={{ color: 'text.primary' }}>
                     <Typography variant="body2" noWrap sx={{ maxWidth: 260 }}>{it.item_name}</Typography>
@@ -406,11 +416,11 @@ const ComparePage: React.FC = () => {
                   </TableCell>
                 </TableRow>
               ))}
-              {sc.items_missing.map(term => (
-                <TableRow key={term}>
+              {sc.lines.filter(line => !line.item).map(line => (
+                <TableRow key={line.entry.barcode ?? line.entry.name}>
                   <TableCell colSpan={2}>
                     <Typography variant="body2" color="warning.main">
-                      {resolveTermName(term)} — לא נמצא בסניף זה
+                      {line.entry.name} — לא נמצא בסניף זה
                     </Typography>
                   </TableCell>
                 </TableRow>
@@ -494,13 +504,19 @@ const ComparePage: React.FC = () => {
         </Button>
 
         {gpsStatus === 'active' && location ? (
-          <Chip
-            icon={<LocationOnIcon />}
-            label={`GPS פעיל · ${location.radius_km ?? 10} ק"מ`}
-            color="success"
-            variant="outlined"
-            onDelete={() => { setLocation(null); setGpsStatus('denied'); }}
-          />
+          <>
+            <Chip
+              icon={<LocationOnIcon />}
+              label={`GPS פעיל · ${location.radius_km ?? 10} ק"מ`}
+              color="success"
+              variant="outlined"
+              onDelete={() => { setLocation(null); setGpsStatus('denied'); }}
+            />
+            <FormControlLabel
+              control={<Switch size="small" checked={rankByTravel} onChange={e => setRankByTravel(e.target.checked)} />}
+              label="כולל עלות נסיעה"
+            />
+          </>
         ) : (
           <Box sx={{ display: 'flex', gap: 1, alignItems: 'center' }}>
             <TextField
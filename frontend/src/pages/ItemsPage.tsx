@@ -1,11 +1,11 @@
 import React, { useCallback, useEffect, useRef, useState } from 'react';
 import {
   Alert, Box, Button, CircularProgress, Container,
-  Slider, TextField, Typography,
+  MenuItem, Slider, TextField, Typography,
 } from '@mui/material';
 import AddShoppingCartIcon from '@mui/icons-material/AddShoppingCart';
 import { apiService } from '../services/api';
-import { StoreItemRow } from '../types';
+import { BrandFacet, Category, StoreItemRow } from '../types';
 import { useCart } from '../context/CartContext';
 
 const PAGE_SIZE = 30;
@@ -16,6 +16,10 @@ const ItemsPage: React.FC = () => {
   const [items, setItems] = useState<StoreItemRow[]>([]);
   const [query, setQuery] = useState('');
   const [priceRange, setPriceRange] = useState<[number, number]>([0, PRICE_MAX]);
+  const [categories, setCategories] = useState<Category[]>([]);
+  const [category, setCategory] = useState('');
+  const [brands, setBrands] = useState<BrandFacet[]>([]);
+  const [brand, setBrand] = useState('');
   const [page, setPage] = useState(1);
   const [total, setTotal] = useState(0);
   const [hasMore, setHasMore] = useState(false);
@@ -31,12 +35,20 @@ const ItemsPage: React.FC = () => {
   const loadingMoreRef = useRef(loadingMore);
   const queryRef = useRef(query);
   const priceRangeRef = useRef(priceRange);
+  const categoryRef = useRef(category);
+  const brandRef = useRef(brand);
 
   useEffect(() => { pageRef.current = page; }, [page]);
   useEffect(() => { hasMoreRef.current = hasMore; }, [hasMore]);
   useEffect(() => { loadingMoreRef.current = loadingMore; }, [loadingMore]);
   useEffect(() => { queryRef.current = query; }, [query]);
   useEffect(() => { priceRangeRef.current = priceRange; }, [priceRange]);
+  useEffect(() => { categoryRef.current = category; }, [category]);
+  useEffect(() => { brandRef.current = brand; }, [brand]);
+
+  useEffect(() => {
+    apiService.getCategories().then(setCategories).catch(() => setCategories([]));
+  }, []);
 
   const fetchPage = useCallback(async (pageNum: number, append: boolean) => {
     const q = queryRef.current;
@@ -47,9 +59,11 @@ const ItemsPage: React.FC = () => {
 
     try {
       const data = await apiService.searchItemsPaginated(
-        q, min > 0 ? min : undefined, max < PRICE_MAX ? max : undefined, pageNum, PAGE_SIZE
+        q, min > 0 ? min : undefined, max < PRICE_MAX ? max : undefined, pageNum, PAGE_SIZE,
+        categoryRef.current || undefined, brandRef.current ? Number(brandRef.current) : undefined
       );
       setItems(prev => append ? [...prev, ...data.items] : data.items);
+      if (!append) setBrands(data.brands);
       setTotal(data.total);
       setHasMore(data.has_more);
       setPage(pageNum);
@@ -109,6 +123,50 @@ const ItemsPage: React.FC = () => {
         sx={{ mb: 2 }}
       />
 
+      {/* Category */}
+      {categories.length > 0 && (
+        <TextField
+          select
+          fullWidth
+          size="small"
+          label="קטגוריה"
+          value={category}
+          onChange={e => {
+            categoryRef.current = e.target.value;
+            setCategory(e.target.value);
+            triggerSearch();
+          }}
+          sx={{ mb: 2 }}
+        >
+          <MenuItem value="">כל הקטגוריות</MenuItem>
+          {categories.map(c => (
+            <MenuItem key={c.id} value={c.slug} sx={{ pl: c.parent_id ? 4 : 2 }}>{c.name}</MenuItem>
+          ))}
+        </TextField>
+      )}
+
+      {/* Brand — from the facet of the current results */}
+      {(brands.length > 0 || brand) && (
+        <TextField
+          select
+          fullWidth
+          size="small"
+          label="מותג"
+          value={brand}
+          onChange={e => {
+            brandRef.current = e.target.value;
+            setBrand(e.target.value);
+            triggerSearch();
+          }}
+          sx={{ mb: 2 }}
+        >
+          <MenuItem value="">כל המותגים</MenuItem>
+          {brands.map(b => (
+            <MenuItem key={b.id} value={String(b.id)}>{b.name} ({b.item_count})</MenuItem>
+          ))}
+        </TextField>
+      )}
+
       {/* Price range */}
       <Box sx={{ px: 1, mb: 3 }}>
         <Typography variant="body2" color="text.secondary" gutterBottom>
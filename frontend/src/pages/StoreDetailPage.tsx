@@ -7,7 +7,7 @@ import ArrowBackIcon from '@mui/icons-material/ArrowBack';
 import AddShoppingCartIcon from '@mui/icons-material/AddShoppingCart';
 import { useNavigate, useParams } from 'react-router-dom';
 import { apiService } from '../services/api';
-import { BackendStoreInfo, StoreItemRow } from '../types';
+import { StoreDetail, StoreItemRow } from '../types';
 import { useCart } from '../context/CartContext';
 
 const PAGE_SIZE = 30;
@@ -17,7 +17,7 @@ const StoreDetailPage: React.FC = () => {
   const navigate = useNavigate();
   const { addItem, contains } = useCart();
 
-  const [store, setStore] = useState<BackendStoreInfo | null>(null);
+  const [store, setStore] = useState<StoreDetail | null>(null);
   const [items, setItems] = useState<StoreItemRow[]>([]);
   const [query, setQuery] = useState('');
   const [page, setPage] = useState(1);
@@ -30,13 +30,11 @@ const StoreDetailPage: React.FC = () => {
   const sentinelRef = useRef<HTMLDivElement | null>(null);
   const debounceRef = useRef<ReturnType<typeof setTimeout> | null>(null);
 
-  // Load store info from the stores list (no dedicated /api/stores/:id endpoint needed)
   useEffect(() => {
     if (!id) return;
-    apiService.getAllStores().then(all => {
-      const found = all.find(s => s.id === Number(id));
-      setStore(found ?? null);
-    }).catch(() => { /* header falls back to the store id */ });
+    apiService.getStore(Number(id))
+      .then(setStore)
+      .catch(() => { /* header falls back to the store id */ });
   }, [id]);
 
   const loadItems = useCallback(async (pageNum: number, append: boolean, q: string) => {
@@ -85,6 +83,7 @@ const StoreDetailPage: React.FC = () => {
 
   const storeLabel = store ? (store.store_name || store.chain_id) : `חנות ${id}`;
   const storeLocation = store ? [store.city, store.address].filter(Boolean).join(' · ') : '';
+  const priceLevel = store?.price_level_index;
 
   return (
     <Container maxWidth="md" sx={{ mt: 3, mb: 8 }}>
@@ -96,6 +95,14 @@ const StoreDetailPage: React.FC = () => {
       {storeLocation && (
         <Typography variant="body2" color="text.secondary" sx={{ mb: 2 }}>{storeLocation}</Typography>
       )}
+      {store?.chain_name && store.chain_name !== storeLabel && (
+        <Typography variant="body2" color="text.secondary">{store.chain_name}</Typography>
+      )}
+      {priceLevel != null && (
+        <Typography variant="body2" color={priceLevel <= 100 ? 'success.main' : 'error.main'} sx={{ mb: 2 }}>
+          רמת מחירים {priceLevel.toFixed(1)} (ממוצע העיר = 100)
+        </Typography>
+      )}
       <Divider sx={{ mb: 2 }} />
 
       {error && <Alert severity="error" sx={{ mb: 2 }}>{error}</Alert>}
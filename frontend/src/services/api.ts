@@ -1,11 +1,17 @@
 import axios from 'axios';
 import {
   UserLocation,
-  PriceComparisonRequest,
-  PriceComparisonResponse,
+  PriceComparisonRequestV2,
+  PriceComparisonResponseV2,
   BackendStoreInfo,
   ProductSearchResult,
   PaginatedItemsResponse,
+  Category,
+  ProductDetail,
+  StoreDetail,
+  GroceryListEntry,
+  SharedList,
+  TermResolution,
 } from '../types';
 
 // Production build: same-origin relative URLs — nginx proxies /api/ to the backend.
@@ -14,6 +20,8 @@ const API_BASE_URL =
   process.env.REACT_APP_API_URL ??
   (process.env.NODE_ENV === 'production' ? '' : 'http://127.0.0.1:3000');
 
+// On v2, the current version; v1 (and its unversioned /api/... alias) is deprecated
+// and kept only for app shells cached before versioning.
 const api = axios.create({
   baseURL: API_BASE_URL,
   headers: {
@@ -22,7 +30,7 @@ const api = axios.create({
 });
 
 export const apiService = {
-  // GET /api/stores/nearby — stores within a radius of the user's location
+  // GET /api/v2/stores/nearby — stores within a radius of the user's location
   getNearbyStores: async (location: UserLocation): Promise<BackendStoreInfo[]> => {
     const params = new URLSearchParams({
       latitude: String(location.latitude),
@@ -31,56 +39,98 @@ export const apiService = {
     if (location.radius_km != null) {
       params.set('radius_km', String(location.radius_km));
     }
-    const res = await api.get(`/api/stores/nearby?${params.toString()}`);
+    const res = await api.get(`/api/v2/stores/nearby?${params.toString()}`);
     return res.data as BackendStoreInfo[];
   },
 
-  // GET /api/stores — all stores with coordinates (for the map page)
+  // GET /api/v2/stores — all stores with coordinates (for the map page)
   getAllStores: async (): Promise<BackendStoreInfo[]> => {
-    const res = await api.get('/api/stores');
+    const res = await api.get('/api/v2/stores');
     return res.data as BackendStoreInfo[];
   },
 
-  // GET /api/stores/:id/items — items for one store, paginated
+  // GET /api/v2/stores/:id — one store with chain name, item count and price level
+  getStore: async (storeId: number): Promise<StoreDetail> => {
+    const res = await api.get(`/api/v2/stores/${storeId}`);
+    return res.data as StoreDetail;
+  },
+
+  // GET /api/v2/stores/:id/items — items for one store, paginated
   getStoreItems: async (
     storeId: number,
     query?: string,
     page = 1,
-    limit = 20
+    limit = 20,
+    category?: string
   ): Promise<PaginatedItemsResponse> => {
     const params: Record<string, string | number> = { page, limit };
     if (query) params.q = query;
-    const res = await api.get(`/api/stores/${storeId}/items`, { params });
+    if (category) params.category = category;
+    const res = await api.get(`/api/v2/stores/${storeId}/items`, { params });
     return res.data as PaginatedItemsResponse;
   },
 
-  // GET /api/items — paginated item search across all stores
+  // GET /api/v2/items — paginated item search across all stores
   searchItemsPaginated: async (
     query?: string,
     minPrice?: number,
     maxPrice?: number,
     page = 1,
-    limit = 20
+    limit = 20,
+    category?: string,
+    brand?: number
   ): Promise<PaginatedItemsResponse> => {
     const params: Record<string, string | number> = { page, limit };
     if (query) params.q = query;
     if (minPrice != null) params.min_price = minPrice;
     if (maxPrice != null) params.max_price = maxPrice;
-    const res = await api.get('/api/items', { params });
+    if (category) params.category = category;
+    if (brand != null) params.brand = brand;
+    const res = await api.get('/api/v2/items', { params });
     return res.data as PaginatedItemsResponse;
   },
 
-  // POST /api/compare-prices — compare basket across stores (location/city optional, paginated)
-  comparePrices: async (payload: PriceComparisonRequest): Promise<PriceComparisonResponse> => {
-    const res = await api.post('/api/compare-prices', payload);
-    return res.data as PriceComparisonResponse;
+  // GET /api/v2/categories — the category tree, for the items filter
+  getCategories: async (): Promise<Category[]> => {
+    const res = await api.get('/api/v2/categories');
+    return res.data as Category[];
+  },
+
+  // POST /api/v2/compare-prices — compare basket across stores (location/city optional, paginated)
+  comparePrices: async (payload: PriceComparisonRequestV2): Promise<PriceComparisonResponseV2> => {
+    const res = await api.post('/api/v2/compare-prices', payload);
+    return res.data as PriceComparisonResponseV2;
   },
 
-  // GET /api/items/search — autocomplete: {barcode, name} pairs
+  // GET /api/v2/items/search — autocomplete: {barcode, name} pairs
   searchItemNames: async (q: string): Promise<ProductSearchResult[]> => {
-    const res = await api.get('/api/items/search', { params: { q } });
+    const res = await api.get('/api/v2/items/search', { params: { q } });
     return res.data as ProductSearchResult[];
   },
+
+  // GET /api/v2/items/resolve — candidate product families for a free-text term
+  resolveTerm: async (q: string, limit?: number): Promise<TermResolution> => {
+    const res = await api.get('/api/v2/items/resolve', { params: { q, limit } });
+    return res.data as TermResolution;
+  },
+
+  // GET /api/v2/products/:barcode — catalog entry plus every store's current price
+  getProduct: async (barcode: string): Promise<ProductDetail> => {
+    const res = await api.get(`/api/v2/products/${encodeURIComponent(barcode)}`);
+    return res.data as ProductDetail;
+  },
+
+  // POST /api/v2/shares — store the list server-side and get a short link ID
+  createShare: async (grocery_list: GroceryListEntry[]): Promise<SharedList> => {
+    const res = await api.post('/api/v2/shares', { grocery_list });
+    return res.data as SharedList;
+  },
+
+  // GET /api/v2/shares/:id — 404 once the share is unknown or expired
+  getShare: async (id: string): Promise<SharedList> => {
+    const res = await api.get(`/api/v2/shares/${encodeURIComponent(id)}`);
+    return res.data as SharedList;
+  },
 };
 
 export default apiService;
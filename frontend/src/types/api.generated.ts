@@ -0,0 +1,769 @@
+// Generated from backend/openapi.json by scripts/gen-api-types.js — do not edit.
+// Regenerate with `npm run gen:api-types`; CI fails when this file is out of date.
+
+export type BaseUnit = 'gram' | 'milliliter' | 'unit';
+
+/** One grocery list entry as priced in one store. */
+export interface BasketLine {
+  entry: GroceryListEntry;
+  /** Fill-in for a missing item, already × quantity. */
+  estimated_line_total?: number | null;
+  item?: ItemPrice | null;
+  /** item price × entry quantity; None when the store doesn't carry it. */
+  line_total?: number | null;
+}
+
+/** One chain's prices for a brand's products. */
+export interface BrandChainStats {
+  /** Mean, over those products, of the chain's average current price. */
+  avg_price: number;
+  chain_id: string;
+  chain_name?: string | null;
+  /** Distinct products of the brand the chain lists. */
+  item_count: number;
+  max_price: number;
+  min_price: number;
+  /**
+   * The chain's total over shared products relative to the cross-chain average
+   * (100 = average, 90 = 10% cheaper). None when fewer than 3 products are shared.
+   */
+  price_index?: number | null;
+  /** Products the chain shares with at least one other chain. */
+  shared_items: number;
+}
+
+/** Returned by GET /api/brands/:id. */
+export interface BrandDetail {
+  /** Every `manufacturer_name` spelling mapped to this brand. */
+  aliases: string[];
+  /** Chains carrying the brand, cheapest price index first. */
+  chains: BrandChainStats[];
+  id: number;
+  name: string;
+}
+
+/** One brand of a brand facet. */
+export interface BrandFacet {
+  /** Manufacturer ID, accepted by the `brand` filter and GET /api/brands/:id. */
+  id: number;
+  /** Distinct matching items of this brand. */
+  item_count: number;
+  name: string;
+}
+
+/** A node of the category tree; top-level categories have no parent. */
+export interface Category {
+  id: number;
+  name: string;
+  parent_id?: number | null;
+  slug: string;
+}
+
+/** A `category_rules` row. */
+export interface CategoryRule {
+  category_id: number;
+  id: number;
+  kind: RuleKind;
+  pattern: string;
+  /** Higher wins over the rule kind and pattern length. */
+  priority: number;
+}
+
+/** Body of POST /admin/categories/rules. */
+export interface CategoryRuleInput {
+  category_id: number;
+  kind: RuleKind;
+  pattern: string;
+  priority?: number;
+}
+
+/** Data age for one chain, judged against the configured staleness threshold. */
+export interface ChainDataAge {
+  age_hours?: number | null;
+  chain_id: string;
+  display_name?: string | null;
+  is_stale: boolean;
+  last_price_update?: string | null;
+}
+
+/** A `chain_names` row. */
+export interface ChainName {
+  chain_id: string;
+  display_name: string;
+}
+
+/** Body of PUT /admin/chains/:chain_id. */
+export interface ChainNameUpdate {
+  display_name: string;
+}
+
+/** One chain as listed by GET /api/chains. */
+export interface ChainSummary {
+  age_hours?: number | null;
+  chain_id: string;
+  display_name?: string | null;
+  is_stale: boolean;
+  item_count: number;
+  last_price_update?: string | null;
+  store_count: number;
+}
+
+export type CheckStatus = 'ok' | 'degraded' | 'failed';
+
+/** Returned by GET /admin/clusters. */
+export interface ClusterListResponse {
+  clusters: ProductCluster[];
+  pagination: Pagination;
+}
+
+/** One chain's item in a product cluster. */
+export interface ClusterMember {
+  chain_id: string;
+  /**
+   * 0–1: 0.6 for a name match, plus 0.2 each when size and manufacturer matched too.
+   * Confirmed members are 1.0.
+   */
+  confidence: number;
+  item_code: string;
+  item_name: string;
+  status: MemberStatus;
+}
+
+/** Body of PUT /admin/clusters/:id/members/:chain_id/:item_code. */
+export interface ClusterMemberOverride {
+  /** `confirmed` (moving the item here if it's clustered elsewhere) or `rejected`. */
+  status: MemberStatus;
+}
+
+/** Returned by POST /admin/clusters/rebuild. */
+export interface ClusterRebuildSummary {
+  /** Automatic members written; overrides are kept as they were. */
+  auto_members: number;
+  clusters: number;
+}
+
+/** Compare options shared by every API version; flattened into the request body. */
+export type CompareOptions = StoreFilter & {
+  /** Price each missing barcode term at its best alternative. Implies `suggest_substitutes`. */
+  auto_substitute?: boolean;
+  city?: string | null;
+  /** ₪ per km driven for `effective_cost` ranking (default 0.7). */
+  cost_per_km?: number | null;
+  /** Price terms a store lacks at this estimate; off when absent. */
+  estimate_missing?: EstimateMethod | null;
+  max_data_age_hours?: number | null;
+  missing_policy?: MissingPolicy;
+  page?: number | null;
+  page_size?: number | null;
+  ranking?: RankingMode;
+  /** Look up in-store alternatives for missing barcode terms. */
+  suggest_substitutes?: boolean;
+  /** ₪ per hour spent travelling for `effective_cost` ranking (default 40). */
+  time_value_per_hour?: number | null;
+  user_location?: LocationQuery | null;
+};
+
+/** Body of POST /api/auth/register and /api/auth/login. */
+export interface Credentials {
+  email: string;
+  password: string;
+}
+
+/**
+ * Where a missing term's fill-in price comes from. Both look only at the stores
+ * in scope (nearby / city / filters) that carry the term.
+ */
+export type EstimateMethod = 'median' | 'cheapest_nearby';
+
+/** One invalid input field, as reported in a validation problem. */
+export interface FieldError {
+  code: string;
+  field: string;
+  message: string;
+}
+
+/** Returned by GET /api/freshness — chains and stores, stalest first. */
+export interface FreshnessResponse {
+  chains: ChainDataAge[];
+  stale_after_hours: number;
+  stores: StoreFreshness[];
+}
+
+/**
+ * v2 grocery list entry. `barcode` (EAN-13) gives an exact cross-store match, as
+ * does `cluster_id` for items without one; otherwise `name` is matched against item names.
+ */
+export interface GroceryListEntry {
+  barcode?: string | null;
+  /** A product cluster (GET /admin/clusters); used when there's no valid barcode. */
+  cluster_id?: number | null;
+  name: string;
+  quantity?: number;
+}
+
+/** One dependency check in GET /ready and GET /health/details. */
+export interface HealthCheck {
+  detail: string;
+  name: string;
+  status: CheckStatus;
+}
+
+/** Returned by GET /health/details. */
+export interface HealthDetails {
+  chains: ChainDataAge[];
+  checks: HealthCheck[];
+  pool: PoolStatsResponse;
+  status: CheckStatus;
+  timestamp: string;
+}
+
+/** Body of PUT /admin/ingest/paused. */
+export interface IngestPauseUpdate {
+  paused: boolean;
+}
+
+/** Ingestion state as seen by the admin API. */
+export interface IngestStatus {
+  paused: boolean;
+  /** Unprocessed XML files in the watch directory; None if it couldn't be read. */
+  pending_files?: number | null;
+  scan_running: boolean;
+}
+
+/** Body of PUT /admin/categories/items/:item_code. */
+export interface ItemCategoryOverride {
+  category_id: number;
+  /**
+   * The chain whose item this is. May be omitted for EAN-13 barcodes, pinning
+   * the product in every chain that carries it.
+   */
+  chain_id?: string | null;
+}
+
+/** Returned by PUT /admin/categories/items/:item_code. */
+export interface ItemCategoryUpdate {
+  /** Chain items now pinned to the category. */
+  items: number;
+}
+
+export interface ItemPrice {
+  /** Runners-up considered for a free-text term, best first. */
+  alternatives: MatchAlternative[];
+  is_stale: boolean;
+  item_code: string;
+  item_name: string;
+  manufacturer_name?: string | null;
+  /**
+   * How well the item answers its term, 0–1: 1 for a barcode hit, scored for
+   * free-text terms. None outside compare matching.
+   */
+  match_confidence?: number | null;
+  price: number;
+  price_update_date?: string | null;
+  unit_of_measure?: string | null;
+}
+
+/** One `processed_files` row. */
+export interface LedgerEntry {
+  file_size: number;
+  filename: string;
+  processed_at?: string | null;
+}
+
+/** Returned by GET /admin/ingest/ledger — newest first. */
+export interface LedgerResponse {
+  entries: LedgerEntry[];
+  pagination: Pagination;
+}
+
+export interface LocationQuery {
+  latitude: number;
+  longitude: number;
+  radius_km?: number | null;
+}
+
+/** A `manufacturer_aliases` row. */
+export interface ManufacturerAlias {
+  alias: string;
+  manufacturer_id?: number | null;
+  /** `auto` (resolved at ingest) or `manual`. */
+  source: string;
+}
+
+/** Body of PUT /admin/manufacturers/aliases. */
+export interface ManufacturerAliasInput {
+  /** A `manufacturer_name` spelling, exactly as chains send it. */
+  alias: string;
+  /** None marks the spelling as "no brand" (a placeholder). */
+  manufacturer_id?: number | null;
+}
+
+/** Body of POST /admin/manufacturers/:id/merge. */
+export interface ManufacturerMerge {
+  /** The manufacturer that takes over the merged one's aliases. */
+  into: number;
+}
+
+/** Returned by POST /admin/manufacturers/:id/merge. */
+export interface ManufacturerMergeSummary {
+  aliases_moved: number;
+}
+
+export interface MatchAlternative {
+  item_code: string;
+  item_name: string;
+  match_confidence: number;
+  price: number;
+}
+
+/**
+ * Review state of a product cluster member. `confirmed` and `rejected` are operator
+ * overrides that survive rebuilds; `auto` members are recomputed each time.
+ */
+export type MemberStatus = 'auto' | 'confirmed' | 'rejected';
+
+/** A fill-in price for a term the store doesn't carry. */
+export interface MissingEstimate {
+  estimated_price: number;
+  method: EstimateMethod;
+  /** Stores in scope the estimate is drawn from. */
+  store_count: number;
+  term: string;
+  term_index: number;
+}
+
+/** How incomplete baskets rank against complete ones. */
+export type MissingPolicy = 'coverage_first' | 'estimated_total';
+
+/** Returned by POST /admin/products/names/elect. */
+export interface NameElectionSummary {
+  /** Products with more than one name variant. */
+  products: number;
+  renamed: number;
+}
+
+/** Paginated item list returned by GET /api/stores/:id/items and GET /api/items. */
+export interface PaginatedItems {
+  /**
+   * Brands among all matching items (ignoring any `brand` filter), most items first.
+   * Only computed for page 1; empty on later pages.
+   */
+  brands: BrandFacet[];
+  has_more: boolean;
+  items: StoreItemRow[];
+  page: number;
+  page_size: number;
+  total: number;
+}
+
+export interface Pagination {
+  has_more: boolean;
+  page: number;
+  page_size: number;
+  total: number;
+}
+
+/** Snapshot of one connection pool's utilization. */
+export interface PoolStats {
+  idle: number;
+  in_use: number;
+  max_connections: number;
+  /** in_use / max_connections — 1.0 means requests are queueing for a connection. */
+  saturation: number;
+  size: number;
+}
+
+/** Returned by GET /health/pool. */
+export interface PoolStatsResponse {
+  api: PoolStats;
+  ingest?: PoolStats | null;
+}
+
+/** Either `grocery_list` or `share_id` (a short link from POST /api/shares) names the list. */
+export type PriceComparisonRequest = CompareOptions & {
+  grocery_list?: string[];
+  share_id?: string | null;
+};
+
+/** POST /api/v2/compare-prices — either `grocery_list` or `share_id` names the list. */
+export type PriceComparisonRequestV2 = CompareOptions & {
+  grocery_list?: GroceryListEntry[];
+  share_id?: string | null;
+};
+
+export interface PriceComparisonResponse {
+  best_store?: StoreComparison | null;
+  has_more: boolean;
+  requested_items: string[];
+  stores: StoreComparison[];
+  total_stores: number;
+}
+
+export interface PriceComparisonResponseV2 {
+  /** Best store overall — only set on page 1. */
+  best_store_id?: number | null;
+  pagination: Pagination;
+  stores: StoreBasket[];
+}
+
+/** Price spread across the stores carrying a product. */
+export interface PriceStats {
+  avg: number;
+  max: number;
+  min: number;
+  store_count: number;
+}
+
+/** RFC 7807 problem details — the body of every API error response. */
+export interface Problem {
+  code: string;
+  detail: string;
+  errors?: FieldError[];
+  request_id?: string | null;
+  status: number;
+  title: string;
+  type: string;
+}
+
+/**
+ * Non-catalog items from several chains judged to be the same product. Compare
+ * matches it like a barcode via a `cluster:<id>` term or an entry's `cluster_id`.
+ */
+export interface ProductCluster {
+  id: number;
+  manufacturer?: string | null;
+  members: ClusterMember[];
+  name: string;
+  size_amount?: number | null;
+  size_unit?: BaseUnit | null;
+}
+
+/** Returned by GET /api/products/:barcode. */
+export interface ProductDetail {
+  barcode: string;
+  canonical_name: string;
+  first_seen_at?: string | null;
+  manufacturer?: string | null;
+  /** None when no store currently lists the product. */
+  price_stats?: PriceStats | null;
+  quantity?: string | null;
+  stores: ProductStorePrice[];
+  unit_of_measure?: string | null;
+}
+
+/**
+ * Barcodes that are the same product in different sizes: same manufacturer,
+ * same name once sizes are stripped.
+ */
+export interface ProductFamily {
+  manufacturer?: string | null;
+  /** Best variant's relevance to the term, 0–1. */
+  match_confidence: number;
+  max_price: number;
+  min_price: number;
+  /** Name of the most widely stocked variant. */
+  name: string;
+  /** Stores carrying the most widely stocked variant. */
+  store_count: number;
+  /** Smallest pack first. */
+  variants: ProductVariant[];
+}
+
+/** One chain's name for a catalog product. */
+export interface ProductNameVariant {
+  chain_id: string;
+  chain_name?: string | null;
+  last_seen_at?: string | null;
+  name: string;
+  /** Ingested files of the chain that listed the product under this name. */
+  seen_count: number;
+}
+
+/** Returned by GET /admin/products/:barcode/names — most seen first. */
+export interface ProductNames {
+  barcode: string;
+  canonical_name: string;
+  variants: ProductNameVariant[];
+}
+
+export interface ProductSearchResult {
+  barcode?: string | null;
+  name: string;
+}
+
+/** Sort order for a product's store list. */
+export type ProductSort = 'price' | 'distance';
+
+/** One store's current price for a product. */
+export interface ProductStorePrice {
+  is_stale: boolean;
+  price: number;
+  price_update_date?: string | null;
+  store: StoreInfo;
+}
+
+/** One barcode in a product family. */
+export interface ProductVariant {
+  barcode: string;
+  max_price: number;
+  min_price: number;
+  name: string;
+  quantity?: string | null;
+  /** Pack size in `size_unit`, when it normalizes. */
+  size_amount?: number | null;
+  size_unit?: BaseUnit | null;
+  store_count: number;
+  unit_of_measure?: string | null;
+}
+
+/** Rows deleted by a chain or store purge. */
+export interface PurgeSummary {
+  items: number;
+  price_history: number;
+  stores: number;
+}
+
+/** Why a store ranked where it did. Present when the store's distance is known. */
+export interface RankScore {
+  basket_total: number;
+  distance_km: number;
+  effective_cost: number;
+  /** Round-trip time, valued at `time_value_per_hour`. */
+  time_cost: number;
+  /** Round-trip driving cost. */
+  travel_cost: number;
+}
+
+/** How stores with equal coverage are ordered. */
+export type RankingMode = 'price' | 'effective_cost';
+
+/** Returned by GET /ready — overall status is the worst individual check. */
+export interface ReadinessResponse {
+  checks: HealthCheck[];
+  status: CheckStatus;
+}
+
+/** Returned by POST /admin/categories/reclassify. */
+export interface ReclassifySummary {
+  /** Chain items a rule put in a category; pinned items aren't counted. */
+  classified: number;
+  unclassified: number;
+}
+
+/** What a category rule is matched against. */
+export type RuleKind = 'keyword' | 'manufacturer' | 'barcode_prefix';
+
+/** A grocery list stored on the server. */
+export interface SavedList {
+  created_at?: string | null;
+  grocery_list: GroceryListEntry[];
+  id: string;
+  name: string;
+  updated_at?: string | null;
+}
+
+/** Body of POST /api/lists and PUT /api/lists/:id — the whole list, replacing any previous items. */
+export interface SavedListInput {
+  grocery_list: GroceryListEntry[];
+  name: string;
+}
+
+/** One row of GET /api/lists. */
+export interface SavedListSummary {
+  id: string;
+  item_count: number;
+  name: string;
+  updated_at?: string | null;
+}
+
+/** A `search_synonyms` rule. */
+export interface SearchSynonym {
+  alias: string;
+  bidirectional: boolean;
+  created_at?: string | null;
+  id: number;
+  term: string;
+}
+
+/** A fresh session: send `token` as `Authorization: Bearer …` until `expires_at`. */
+export interface SessionResponse {
+  expires_at: string;
+  token: string;
+  user: UserInfo;
+}
+
+/** Body of POST /api/shares. */
+export interface ShareCreateRequest {
+  /** Lets anyone holding the link replace the list (default read-only). */
+  editable?: boolean;
+  /** Omit for a link that never expires. */
+  expires_in_hours?: number | null;
+  grocery_list: GroceryListEntry[];
+  name?: string | null;
+}
+
+/** Body of PUT /api/shares/:id (editable shares only). */
+export interface ShareUpdateRequest {
+  grocery_list: GroceryListEntry[];
+  name?: string | null;
+}
+
+/** A shared list, addressed by its short `id`. */
+export interface SharedList {
+  created_at?: string | null;
+  editable: boolean;
+  expires_at?: string | null;
+  grocery_list: GroceryListEntry[];
+  id: string;
+  name?: string | null;
+  updated_at?: string | null;
+}
+
+export interface StoreBasket {
+  /** Found lines plus estimated ones; set when `estimate_missing` is. */
+  estimated_total?: number | null;
+  items_found: number;
+  items_missing: number;
+  /** One line per grocery list entry, in request order. */
+  lines: BasketLine[];
+  /** Scored on the quantity-weighted total. */
+  score?: RankScore | null;
+  store: StoreInfo;
+  substitutions: SubstitutionSuggestion[];
+  /** Found lines only. */
+  total_price: number;
+}
+
+export interface StoreComparison {
+  /** Found items plus `missing_estimates`; set when `estimate_missing` is. */
+  estimated_total?: number | null;
+  items: ItemPrice[];
+  items_found: number;
+  items_missing: string[];
+  /** One per missing term that some store in scope carries. */
+  missing_estimates: MissingEstimate[];
+  score?: RankScore | null;
+  store: StoreInfo;
+  substitutions: SubstitutionSuggestion[];
+  /** Found items only. */
+  total_price: number;
+}
+
+/** Returned by GET /api/stores/:id — the store plus chain and pricing metadata. */
+export type StoreDetail = StoreInfo & {
+  bikoret_no?: number | null;
+  chain_name?: string | null;
+  item_count: number;
+  /**
+   * This store's basket cost relative to the average of other stores in its
+   * city, over barcoded items they share (100 = city average, 90 = 10% cheaper).
+   * None when the city has too little overlap to say.
+   */
+  price_level_index?: number | null;
+  /** How many shared items the index was computed over. */
+  price_level_items: number;
+  zip_code?: string | null;
+};
+
+/**
+ * Which stores a compare may rank. Each non-empty `include_*` list narrows the
+ * candidates, each `exclude_*` list removes from them; all of them combine.
+ */
+export interface StoreFilter {
+  exclude_chains?: string[];
+  exclude_store_ids?: number[];
+  exclude_sub_chains?: SubChainRef[];
+  include_chains?: string[];
+  /** Store IDs as returned by the API (`StoreInfo.id`), not chain-local store numbers. */
+  include_store_ids?: number[];
+  include_sub_chains?: SubChainRef[];
+}
+
+/** Data age for one store, as listed by GET /api/freshness. */
+export interface StoreFreshness {
+  age_hours?: number | null;
+  chain_id: string;
+  city?: string | null;
+  id: number;
+  is_stale: boolean;
+  last_price_update?: string | null;
+  store_id: number;
+  store_name?: string | null;
+}
+
+export interface StoreInfo {
+  address?: string | null;
+  chain_id: string;
+  city?: string | null;
+  distance_km?: number | null;
+  id: number;
+  last_price_update?: string | null;
+  latitude?: number | null;
+  longitude?: number | null;
+  store_id: number;
+  store_name?: string | null;
+  sub_chain_id: number;
+}
+
+/** A single item row returned by the store-items and item-search endpoints. */
+export interface StoreItemRow {
+  is_stale: boolean;
+  item_code: string;
+  item_name: string;
+  item_price: number;
+  manufacturer_name?: string | null;
+  price_update_date?: string | null;
+  quantity?: string | null;
+  unit_of_measure?: string | null;
+}
+
+/** Sub-chain IDs are only unique within their chain. */
+export interface SubChainRef {
+  chain_id: string;
+  sub_chain_id: number;
+}
+
+/** An in-store alternative for a missing product. */
+export interface Substitute {
+  item: ItemPrice;
+  /** Higher is closer: name similarity plus manufacturer and size bonuses. */
+  match_score: number;
+  reason: SubstituteReason;
+  /** Alternative's pack size over the original's, when both normalize to the same unit. */
+  size_ratio?: number | null;
+}
+
+/** Why an item was suggested in place of a missing product. */
+export type SubstituteReason = 'same_manufacturer' | 'similar_name';
+
+/** Ranked alternatives, best first, for one missing barcode term. */
+export interface SubstitutionSuggestion {
+  alternatives: Substitute[];
+  /** The first alternative is counted in the store's items and totals (`auto_substitute`). */
+  applied: boolean;
+  term: string;
+  term_index: number;
+}
+
+/** Body of POST /admin/synonyms and PUT /admin/synonyms/:id. */
+export interface SynonymInput {
+  alias: string;
+  /**
+   * False makes a one-way rule: searches for `term` also match `alias`, not the
+   * reverse. Default true.
+   */
+  bidirectional?: boolean;
+  term: string;
+}
+
+export interface TermResolution {
+  /** Most relevant first. */
+  families: ProductFamily[];
+  term: string;
+}
+
+export interface UserInfo {
+  created_at?: string | null;
+  email: string;
+  id: number;
+}
@@ -1,43 +1,4 @@
-export interface Store {
-  id: number;
-  chain_id: string;
-  subchain_id: string;
-  store_id: string;
-  store_name: string;
-  address: string;
-  city: string;
-  zip_code?: string;
-  last_update_date: string;
-  last_update_time: string;
-  processed_at: string;
-}
-
-export interface Item {
-  id: number;
-  store_pk: number;
-  item_code: string;
-  item_type: number;
-  item_name: string;
-  manufacturer_item_description?: string;
-  unit_qty?: string;
-  quantity?: string;
-  unit_of_measure?: string;
-  is_weighted?: boolean;
-  qty_in_package?: number;
-  item_price: number;
-  unit_of_measure_price?: number;
-  allow_discount?: boolean;
-  item_status?: number;
-  item_id?: number;
-  price_update_date: string;
-  manufacturer_name?: string;
-  manufacturer_country?: string;
-  manufacturer_item_description_en?: string;
-  manufacturer_item_description_he?: string;
-  item_name_en?: string;
-  item_name_he?: string;
-  processed_at: string;
-}
+import type { LocationQuery, PaginatedItems, StoreInfo } from './api.generated';
 
 export interface SearchFilters {
   storeName?: string;
@@ -48,93 +9,17 @@ export interface SearchFilters {
   city?: string;
 }
 
-export interface ApiResponse<T> {
-  data: T[];
-  total: number;
-  page: number;
-  limit: number;
-}
-
-// A product returned by the autocomplete search.
-// barcode is set when the item has a valid EAN-13 code (most packaged goods).
-// When barcode is present the frontend sends it to the API for exact cross-store matching.
-export interface ProductSearchResult {
-  barcode: string | null;
-  name: string;
-}
-
 // An item in the user's grocery list — tracks both the display name and the barcode (if known).
 export interface GroceryItem {
   barcode: string | null;
   name: string;
 }
 
-// Backend API types for price comparison flow
-export interface UserLocation {
-  latitude: number;
-  longitude: number;
-  radius_km?: number;
-}
-
-export interface PriceComparisonRequest {
-  user_location?: UserLocation;
-  grocery_list: string[];
-  page?: number;
-  page_size?: number;
-  city?: string;
-}
-
-export interface BackendStoreInfo {
-  id: number;
-  chain_id: string;
-  sub_chain_id: number;
-  store_id: number;
-  store_name?: string;
-  address?: string;
-  city?: string;
-  latitude?: number;
-  longitude?: number;
-  distance_km?: number;
-}
-
-export interface ComparisonItemPrice {
-  item_code: string;
-  item_name: string;
-  price: number;
-  unit_of_measure?: string;
-  manufacturer_name?: string;
-}
-
-export interface StoreComparison {
-  store: BackendStoreInfo;
-  items: ComparisonItemPrice[];
-  total_price: number;
-  items_found: number;
-  items_missing: string[];
-}
-
-export interface PriceComparisonResponse {
-  stores: StoreComparison[];
-  best_store?: StoreComparison;
-  requested_items: string[];
-  total_stores: number;
-  has_more: boolean;
-}
-
-// Returned by GET /api/stores/:id/items and GET /api/items
-export interface StoreItemRow {
-  item_code: string;
-  item_name: string;
-  manufacturer_name?: string;
-  item_price: number;
-  unit_of_measure?: string;
-  quantity?: string;
-}
+// Backend API types are generated from backend/openapi.json into api.generated.ts
+// (`npm run gen:api-types`); CI fails when that file drifts from the spec.
+export * from './api.generated';
 
-export interface PaginatedItemsResponse {
-  items: StoreItemRow[];
-  total: number;
-  page: number;
-  page_size: number;
-  has_more: boolean;
-}
\ No newline at end of file
+// Names the pages used before the types were generated.
+export type UserLocation = LocationQuery;
+export type BackendStoreInfo = StoreInfo;
+export type PaginatedItemsResponse = PaginatedItems;